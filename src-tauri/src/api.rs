@@ -1,40 +1,116 @@
 use std::{cmp};
-use std::collections::HashMap;
-use std::path::{PathBuf};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::path::Component::Prefix;
-use std::sync::OnceLock;
-use std::time::SystemTime;
+use std::sync::{Arc, OnceLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime};
 use tokio;
-use tokio::io::AsyncReadExt;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
 use mime_guess::{from_path};
 use encoding_rs::Encoding;
-use chardetng::EncodingDetector;
 use moka::future::Cache;
+use serde_json::Value;
+use tokio::sync::{broadcast, Mutex, RwLock, Semaphore};
 use dirs_next;
 use sysinfo::Disks;
 
 use crate::models::{ CacheKey, CacheVal,
-                     Item, Folder, Params, TextContent, ApiError, HomeType, DiskInfo};
+                     Item, MetaType, OrdItem, Folder, Params, TextContent, ApiError, HomeType, DiskInfo,
+                     HexContent, HexSearch, WriteOpts, WriteInfo, ImgInfo, Thumb,
+                     StructContent, StructFormat, TableOpts, TableContent, MarkdownContent, HtmlContent, DbContent, OfficeContent, PdfContent, MediaInfo,
+                     DiffOpts, DiffContent, SearchOpts, TextSearch, AccessPolicy, StateChanged, Bookmark};
 use crate::path_ext::PathExt;
 use crate::system_time_ext::SystemTimeExt;
-use crate::dir::{get_items_win32, update_items, sort_items, get_arg_path };
+use crate::dir::{read_items, update_items, update_items_by_file, get_sort_meta_types, sort_items, get_arg_path };
+use crate::img::get_img_info;
+use crate::archive::{split_archive_path, is_archive_file, read_archive_file, get_archive_entries, get_archive_items, ArchiveEntry};
+use crate::sqlite::{split_db_path, read_db_rows, DB_TAKE_N, DB_TAKE_MAX};
+use crate::compress::{get_codec, get_codec_by_ext, decompress, decompress_head, strip_codec_ext};
+use crate::thumb::{get_thumb_key, read_thumb_cache, write_thumb_cache, make_thumb, make_thumb_from_buf, THUMB_WORKERS};
+use crate::lang::{detect_lang, get_lang_by_name};
+use crate::text::{get_encoding, detect_encoding, decode_text, get_detection,
+                  get_eol_info, has_final_newline, get_indent_info, convert_eol, encode_text,
+                  update_encodings, RememberedEnc, TextBuf, ENC_KEY, TEXT_MAX_LEN, TEXT_SAMPLE_LEN};
+use crate::structured::{get_struct_format, parse_struct, to_node, get_children, STRUCT_MAX_LEN, STRUCT_TAKE_N};
+use crate::table::{TableIndex, sniff_delimiter, sniff_header, build_index, read_rows, get_view,
+                   TABLE_SAMPLE_LEN, TABLE_TAKE_N, TABLE_TAKE_MAX};
+use crate::markdown::{render_markdown, MD_MAX_LEN};
+use crate::html::{sanitize_html, SafeHtml, HTML_MAX_LEN};
+use crate::office::read_office;
+use crate::pdf::{read_pdf, PDF_TAKE_N, PDF_TAKE_MAX};
+use crate::media::{get_cover, get_media_info, is_media};
+use crate::diff::diff_texts;
+use crate::search::search_file;
+use crate::policy::{Policy, resolve_path};
+use crate::settings::{Settings, get_settings_path, write_atomic};
+use crate::bookmark::{BOOKMARK_KEY, Bookmarks, get_bookmark_path, parse_bookmarks, to_bookmarks_json};
+use crate::hex::{to_rows, get_magic, parse_pattern, search_bytes, HEX_MAX_LEN};
 
 static INSTANCE: OnceLock<Api> = OnceLock::new();
+const SETTINGS_SAVE_DELAY: Duration = Duration::from_millis(500);
 
 pub fn get_instance() -> &'static Api {
     INSTANCE.get_or_init(|| Api::new())
 }
 
+///
+/// snapshots are written in the order taken. a failed write is tried again by the next save
+async fn save_settings(settings: &RwLock<Settings>, saving: &Mutex<()>) -> Result<(), ApiError> {
+    let _guard = saving.lock().await;
+    let Some((path, buf)) = settings.write().await.take_snapshot()? else {
+        return Ok(());
+    };
+    let res = tokio::task::spawn_blocking(move || write_atomic(&path, &buf)).await
+        .map_err(|e| ApiError::Settings(e.to_string()))?;
+    if res.is_err() {
+        settings.write().await.set_dirty();
+    }
+    res
+}
+
 pub struct Api {
     cache_folder: Cache<CacheKey, CacheVal>,
-    state: Cache<String, String>,
+    settings: Arc<RwLock<Settings>>,
+    // one write of the settings at a time, and whether one is scheduled
+    settings_saving: Arc<Mutex<()>>,
+    settings_pending: Arc<AtomicBool>,
+    state_tx: broadcast::Sender<StateChanged>,
+    // prefixes of the windows by label
+    state_subs: RwLock<HashMap<String, Vec<String>>>,
+    bookmarks: RwLock<Bookmarks>,
+    thumb_pool: Semaphore,
+    cache_struct: Cache<String, (StructFormat, Arc<Value>)>,
+    cache_archive: Cache<String, Arc<Vec<ArchiveEntry>>>,
+    cache_table: Cache<String, Arc<TableIndex>>,
+    cache_table_view: Cache<String, Arc<Vec<usize>>>,
+    searches: Cache<String, Arc<AtomicBool>>,
+    // folders listed and files opened for viewing, served by the stream protocol.
+    // kept for the session, a folder still shown is never evicted
+    opened_dirs: RwLock<HashSet<PathBuf>>,
+    opened_files: RwLock<HashSet<PathBuf>>,
+    policy: Policy,
 }
 
 impl Default for Api {
     fn default() -> Self {
         Api {
             cache_folder: Cache::new(100),
-            state: Cache::new(100),
+            settings: Arc::new(RwLock::new(Settings::default())),
+            settings_saving: Arc::new(Mutex::new(())),
+            settings_pending: Arc::new(AtomicBool::new(false)),
+            bookmarks: RwLock::new(Bookmarks::default()),
+            state_tx: broadcast::Sender::new(100),
+            state_subs: RwLock::new(HashMap::new()),
+            thumb_pool: Semaphore::new(THUMB_WORKERS),
+            cache_struct: Cache::new(10),
+            cache_archive: Cache::new(10),
+            cache_table: Cache::new(10),
+            cache_table_view: Cache::new(30),
+            searches: Cache::new(100),
+            opened_dirs: RwLock::new(HashSet::new()),
+            opened_files: RwLock::new(HashSet::new()),
+            policy: Policy::default(),
         }
     }
 }
@@ -46,7 +122,21 @@ impl Api {
         Api {
             cache_folder: Cache::new(100),
             // cache_paths: Cache::new(100),
-            state: Cache::new(100),
+            settings: Arc::new(RwLock::new(Settings::load(get_settings_path()))),
+            settings_saving: Arc::new(Mutex::new(())),
+            settings_pending: Arc::new(AtomicBool::new(false)),
+            bookmarks: RwLock::new(Bookmarks::load(get_bookmark_path())),
+            state_tx: broadcast::Sender::new(100),
+            state_subs: RwLock::new(HashMap::new()),
+            thumb_pool: Semaphore::new(THUMB_WORKERS),
+            cache_struct: Cache::new(10),
+            cache_archive: Cache::new(10),
+            cache_table: Cache::new(10),
+            cache_table_view: Cache::new(30),
+            searches: Cache::new(100),
+            opened_dirs: RwLock::new(HashSet::new()),
+            opened_files: RwLock::new(HashSet::new()),
+            policy: Policy::load(),
         }
     }
 
@@ -61,9 +151,17 @@ impl Api {
             cache_nm,
             ..
         } = params.clone();
+        let mut abs = self.policy.check_read(&path_str)?;
         let mut folder = Folder::default();
-        let mut abs = std::path::absolute(PathBuf::from(path_str))?;
-        let is_file = abs.is_file();
+        // an archive or a database itself is listed as a folder, tables are its files
+        let opt_archive = split_archive_path(&abs);
+        let opt_db = if opt_archive.is_none() { split_db_path(&abs) } else { None };
+        let is_file = match (&opt_archive, &opt_db) {
+            (Some((archive, inner)), _) => self.get_archive_entries(archive).await
+                .is_ok_and(|entries| is_archive_file(&entries, inner)),
+            (_, Some((_, inner))) => !inner.is_empty(),
+            _ => abs.is_file(),
+        };
         if is_file {  // file -> dir
             abs.pop();
         }
@@ -111,7 +209,11 @@ impl Api {
         item.nm = item_name;
         item.dir = !is_file;
         let mut system_time : Option<SystemTime> = None;
-        match abs.metadata() {
+        let meta_path = match opt_archive.as_ref().or(opt_db.as_ref()) {
+            Some((container, _)) => container.clone(),
+            None => abs.clone(),
+        };
+        match meta_path.metadata() {
             Ok(meta) => {
                 // system_time = meta.modified().ok();
                 system_time = match meta.modified() {
@@ -148,7 +250,7 @@ impl Api {
                 Some(mut cache_val) => {
                     if cache_val.ordering != ordering  {
                         println!("sort");
-                        sort_items(&mut cache_val.items, &ordering);
+                        cache_val.items = self.sort_folder_items(&abs, cache_val.items, &meta_types, &ordering).await?;
 
                         cache_val.ordering = ordering.clone();
                         cache_val = cache_val.clone();
@@ -160,10 +262,10 @@ impl Api {
                 }
                 None => {
                     println!("read folder");
-                    let mut items_new = get_items_win32(abs.to_string_lossy().as_ref(), &meta_types).unwrap_or(vec![]);
+                    let mut items_new = self.read_items(&abs, &meta_types).await;
                     update_items(&mut items_new, &meta_types);
 
-                    sort_items(&mut items_new, &ordering);
+                    let items_new = self.sort_folder_items(&abs, items_new, &meta_types, &ordering).await?;
 
                     let cache_val = CacheVal {
                         ordering: ordering.clone(),
@@ -174,9 +276,9 @@ impl Api {
                 }
             };
         } else {
-            sorted_items = get_items_win32(abs.to_string_lossy().as_ref(), &meta_types).unwrap_or(vec![]);
-            sort_items(&mut sorted_items, &ordering);
-
+            sorted_items = self.read_items(&abs, &meta_types).await;
+            update_items(&mut sorted_items, &meta_types);
+            sorted_items = self.sort_folder_items(&abs, sorted_items, &meta_types, &ordering).await?;
         }
         let len_items = sorted_items.len();
         let mut skip = skip_n.unwrap_or(0);
@@ -187,6 +289,8 @@ impl Api {
             None =>  len_items - skip
         };
         let items_sliced: Vec<Item> = sorted_items.iter().skip(skip as usize).take(take as usize).cloned().collect();
+        // the meta read from the files only for the page
+        let items_sliced = self.update_folder_items(&abs, items_sliced, meta_types.clone()).await?;
 
         folder.skip_n = Some(skip);
         folder.take_n = Some(take);
@@ -196,27 +300,107 @@ impl Api {
         folder.item.items = Some(items_sliced);
         // folder.item.has = if meta_types.contains(&MetaType::Has) { Some(len_items > 0) } else { None };
 
+        self.add_opened_dir(&abs).await;
         Ok(folder)
     }
 
-    pub async fn set_state(&self, key: String, opt_val: Option<String>) -> Result<Option<String>, ApiError> {
-        match opt_val.clone() {
-            None => {
-                self.state.remove(&key).await;
-            },
-            Some(val) => {
-                self.state.insert(key.clone(), val.clone()).await;
+    ///
+    /// the meta read from the files is read for all items only if the ordering compares it
+    async fn sort_folder_items(&self, abs: &Path, items: Vec<Item>, meta_types: &[MetaType], ordering: &[OrdItem]) -> Result<Vec<Item>, ApiError> {
+        let mut items = self.update_folder_items(abs, items, get_sort_meta_types(meta_types, ordering)).await?;
+        sort_items(&mut items, ordering);
+        Ok(items)
+    }
+
+    ///
+    /// items with the meta read from the files by `update_items_by_file`, off the runtime
+    async fn update_folder_items(&self, abs: &Path, mut items: Vec<Item>, meta_types: Vec<MetaType>) -> Result<Vec<Item>, ApiError> {
+        if !meta_types.iter().any(|meta_type| matches!(meta_type, MetaType::Img | MetaType::Pages | MetaType::Media)) {
+            return Ok(items);
+        }
+        let base_dir = abs.to_string_lossy().to_string();
+        tokio::task::spawn_blocking(move || {
+            update_items_by_file(&base_dir, &mut items, &meta_types);
+            items
+        }).await
+            .map_err(|e| ApiError::Folder(e.to_string()))
+    }
+
+    ///
+    /// items of the folder, of the folder in an archive (`C:\drops\build.zip\bin`) or tables of a database
+    async fn read_items(&self, abs: &Path, meta_types: &Vec<MetaType>) -> Vec<Item> {
+        match split_archive_path(abs) {
+            Some((archive, inner)) => match self.get_archive_entries(&archive).await {
+                Ok(entries) => get_archive_items(&archive, &entries, &inner, meta_types).unwrap_or(vec![]),
+                Err(_) => vec![],
             },
+            None => read_items(abs.to_string_lossy().as_ref(), meta_types).unwrap_or(vec![]),
+        }
+    }
+
+    ///
+    /// entries of the archive cached by path, size and mtime,
+    /// so a `.tar.gz` or `.7z` is not decompressed again for paging and sorting
+    async fn get_archive_entries(&self, archive: &Path) -> Result<Arc<Vec<ArchiveEntry>>, ApiError> {
+        let meta = archive.metadata()?;
+        let key = format!("{}|{}|{}", archive.to_string_lossy(), meta.len(), meta.modified()?.to_ms());
+        if let Some(entries) = self.cache_archive.get(&key).await {
+            return Ok(entries);
+        }
+        let path = archive.to_path_buf();
+        let entries = tokio::task::spawn_blocking(move || get_archive_entries(&path))
+            .await
+            .map_err(|e| ApiError::Archive(e.to_string()))??;
+        let entries = Arc::new(entries);
+        self.cache_archive.insert(key, entries.clone()).await;
+        Ok(entries)
+    }
+
+    ///
+    /// folder read by `get_folder`, its files but not subfolders are served by the stream protocol
+    async fn add_opened_dir(&self, path: &Path) {
+        if let Ok(dir) = resolve_path(path) {
+            self.opened_dirs.write().await.insert(dir);
+        }
+    }
+
+    ///
+    /// file opened for viewing such as an image of a rendered document
+    async fn add_opened_file(&self, path: &Path) {
+        if let Ok(path) = resolve_path(path) {
+            self.opened_files.write().await.insert(path);
+        }
+    }
+
+    ///
+    /// canonical path of an opened file or a file in an opened folder, symlinks and `..` are resolved
+    pub async fn get_opened_file(&self, path_str: &str) -> Option<PathBuf> {
+        let path = resolve_path(Path::new(path_str)).ok()?;
+        if !path.is_file() || !self.policy.is_allowed(&path) {
+            return None;
+        }
+        let is_opened = match path.parent() {
+            Some(dir) if self.opened_dirs.read().await.contains(dir) => true,
+            _ => self.opened_files.read().await.contains(&path),
         };
+        is_opened.then_some(path)
+    }
+
+    pub async fn set_state(&self, key: String, opt_val: Option<Value>, source: Option<String>) -> Result<Option<Value>, ApiError> {
+        let old = self.settings.write().await.set(&key, opt_val.clone())?;
+        self.save_settings_later();
+        self.notify_state(key, old, opt_val.clone(), source);
         Ok(opt_val)
     }
 
-    pub async fn get_state(&self, key: &String, default_val: Option<String>) -> Result<Option<String>, ApiError> {
-        let opt_val = self.state.get(key).await;
-        match (opt_val.clone(), default_val.clone()) {
+    pub async fn get_state(&self, key: &String, default_val: Option<Value>, source: Option<String>) -> Result<Option<Value>, ApiError> {
+        let mut settings = self.settings.write().await;
+        match (settings.get(key), default_val) {
             (None, Some(val)) => {
-                self.state.insert(key.clone(), val.clone()).await;
-                Ok(default_val)
+                settings.set(key, Some(val.clone()))?;
+                self.save_settings_later();
+                self.notify_state(key.clone(), None, Some(val.clone()), source);
+                Ok(Some(val))
             }
             (opt_val, _) => {
                 Ok(opt_val)
@@ -224,17 +408,142 @@ impl Api {
         }
     }
 
-    pub async fn read_txt(&self, path_str: &str) -> Result<TextContent, ApiError> {
-        let path = PathBuf::from(path_str);
+    pub async fn list_state(&self, prefix: &str) -> Result<BTreeMap<String, Value>, ApiError> {
+        Ok(self.settings.read().await.list(prefix))
+    }
 
-        let mut file = tokio::fs::File::open(&path).await?;
-        let mut reader = tokio::io::BufReader::new(file);
+    ///
+    /// writes the settings once after a burst of changes, outside of the lock
+    fn save_settings_later(&self) {
+        if self.settings_pending.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let (settings, saving, pending) = (self.settings.clone(), self.settings_saving.clone(), self.settings_pending.clone());
+        tokio::spawn(async move {
+            tokio::time::sleep(SETTINGS_SAVE_DELAY).await;
+            pending.store(false, Ordering::SeqCst);
+            let _ = save_settings(&settings, &saving).await;
+        });
+    }
 
-        let mut sample = vec![0u8; 16 * 1024];
-        let n = reader.read(&mut sample).await?;
-        sample.truncate(n);
+    ///
+    /// writes the changes of the settings not written yet, such as on exit
+    pub async fn flush_settings(&self) -> Result<(), ApiError> {
+        save_settings(&self.settings, &self.settings_saving).await
+    }
 
-        let mime_type = match infer::get(&sample) {
+    fn notify_state(&self, key: String, old: Option<Value>, new: Option<Value>, source: Option<String>) {
+        if old != new {
+            // no receiver until the events are mounted
+            let _ = self.state_tx.send(StateChanged { key, old, new, source });
+        }
+    }
+
+    ///
+    /// state changes to emit as events
+    pub fn watch_state(&self) -> broadcast::Receiver<StateChanged> {
+        self.state_tx.subscribe()
+    }
+
+    ///
+    /// the window gets the changes of its prefixes only, all of them until the first one.
+    /// `None` clears the prefixes of the window
+    pub async fn subscribe_state(&self, label: String, prefix: Option<String>) -> Result<Vec<String>, ApiError> {
+        let mut subs = self.state_subs.write().await;
+        let Some(prefix) = prefix else {
+            subs.remove(&label);
+            return Ok(vec![]);
+        };
+        let prefixes = subs.entry(label).or_default();
+        if !prefixes.contains(&prefix) {
+            prefixes.push(prefix);
+        }
+        Ok(prefixes.clone())
+    }
+
+    ///
+    /// windows subscribed to other prefixes than of the key
+    pub async fn get_state_excluded(&self, key: &str) -> Vec<String> {
+        self.state_subs.read().await.iter()
+            .filter(|(_, prefixes)| !prefixes.iter().any(|prefix| key.starts_with(prefix.as_str())))
+            .map(|(label, _)| label.clone())
+            .collect()
+    }
+
+    ///
+    /// remembered in the settings by `ENC_KEY`, so kept after a restart
+    pub async fn set_encoding(&self, path_str: &str, enc: Option<String>) -> Result<Option<String>, ApiError> {
+        let key = self.policy.check_read(path_str)?.to_string_lossy().to_string();
+        let opt_nm = match enc {
+            Some(label) => Some(get_encoding(&label)?.name().to_string()),
+            None => None,
+        };
+        let mut settings = self.settings.write().await;
+        let old = settings.get(ENC_KEY);
+        let items: Vec<RememberedEnc> = old.clone().and_then(|val| serde_json::from_value(val).ok()).unwrap_or_default();
+        // reading again in the same encoding changes nothing
+        if items.iter().find(|item| item.path == key).map(|item| &item.enc) == opt_nm.as_ref() {
+            return Ok(opt_nm);
+        }
+        let new = serde_json::to_value(update_encodings(items, &key, opt_nm.clone()))?;
+        settings.set(ENC_KEY, Some(new.clone()))?;
+        drop(settings);
+        self.save_settings_later();
+        self.notify_state(ENC_KEY.to_string(), old, Some(new), None);
+        Ok(opt_nm)
+    }
+
+    pub async fn get_encoding(&self, path_str: &str) -> Result<Option<String>, ApiError> {
+        let key = resolve_path(Path::new(path_str))?.to_string_lossy().to_string();
+        let items: Vec<RememberedEnc> = self.settings.read().await.get(ENC_KEY)
+            .and_then(|val| serde_json::from_value(val).ok())
+            .unwrap_or_default();
+        Ok(items.into_iter().find(|item| item.path == key).map(|item| item.enc))
+    }
+
+    ///
+    /// bytes of the file, of the file in an archive or decompressed, up to `max_len`
+    async fn read_buf(&self, path_str: &str, max_len: u64) -> Result<TextBuf, ApiError> {
+        self.read_buf_by(path_str, max_len, false).await
+    }
+
+    ///
+    /// `read_buf` cut to `max_len` rather than empty when it is over
+    async fn read_buf_head(&self, path_str: &str, max_len: u64) -> Result<TextBuf, ApiError> {
+        self.read_buf_by(path_str, max_len, true).await
+    }
+
+    async fn read_buf_by(&self, path_str: &str, max_len: u64, is_head: bool) -> Result<TextBuf, ApiError> {
+        let path = self.policy.check_read(path_str)?;
+        // files in an archive are read up to max_len, large files only for the sample
+        let opt_archive = split_archive_path(&path)
+            .filter(|(_, inner)| !inner.is_empty());
+        let (sz, tm_ms, mut buffer) = match &opt_archive {
+            Some((archive, inner)) => {
+                // a `.tar.gz` or a solid `.7z` is decompressed up to the entry
+                let (archive, inner) = (archive.clone(), inner.clone());
+                let (entry, buffer) = tokio::task::spawn_blocking(move || read_archive_file(&archive, &inner, max_len))
+                    .await
+                    .map_err(|e| ApiError::Archive(e.to_string()))??;
+                (entry.sz, entry.tm.map(|tm| tm * 1000), buffer)
+            }
+            _ => {
+                let meta = path.metadata()?;
+                let sz = meta.len();
+                let mut buffer = Vec::new();
+                let file = tokio::fs::File::open(&path).await?;
+                let len = match (sz > max_len, is_head) {
+                    (true, false) => TEXT_SAMPLE_LEN,
+                    (true, true) => max_len,
+                    (false, _) => sz,
+                };
+                file.take(len).read_to_end(&mut buffer).await?;
+                (sz, meta.modified().ok().map(|t| t.to_ms()), buffer)
+            }
+        };
+        let sample = &buffer[..cmp::min(buffer.len(), TEXT_SAMPLE_LEN as usize)];
+
+        let mime_type = match infer::get(sample) {
             Some(infer_type) => infer_type.mime_type().to_string(),
             None => from_path(path_str).first_or_octet_stream().to_string()
         };
@@ -246,43 +555,609 @@ impl Api {
         //     }
         // }
 
-        println!("mime_type: {}", mime_type);
+        // single-file compressed text is decompressed up to max_len
+        let codec = get_codec(sample);
+        let mut is_over = sz > max_len;
+        if let Some(codec) = codec {
+            // a large file is decompressed from the file, a large file in an archive has its head only
+            let is_cut = is_over && opt_archive.is_some();
+            let is_from_file = is_over && opt_archive.is_none();
+            let path = path.clone();
+            (buffer, is_over) = tokio::task::spawn_blocking(move || -> Result<(Vec<u8>, bool), ApiError> {
+                let reader: Box<dyn std::io::BufRead> = if is_from_file {
+                    Box::new(std::io::BufReader::new(std::fs::File::open(&path)?))
+                } else {
+                    Box::new(std::io::Cursor::new(buffer))
+                };
+                Ok(match (is_head, is_cut) {
+                    (true, _) => {
+                        let mut head = decompress_head(reader, codec, max_len + 1)?;
+                        let is_over = is_cut || head.len() as u64 > max_len;
+                        head.truncate(max_len as usize);
+                        (head, is_over)
+                    }
+                    (false, true) => (vec![], true),
+                    (false, false) => match decompress(reader, codec, max_len)? {
+                        Some(decompressed) => (decompressed, false),
+                        None => (vec![], true),
+                    },
+                })
+            })
+                .await
+                .map_err(|e| ApiError::Compress(e.to_string()))??;
+        }
 
-        let sz = path.metadata()?.len();
+        Ok(TextBuf {
+            sz,
+            tm_ms,
+            mimetype: mime_type,
+            codec,
+            buffer,
+            is_over,
+        })
+    }
+
+    pub async fn read_txt(&self, path_str: &str, enc: Option<String>) -> Result<TextContent, ApiError> {
+        // explicit encoding is remembered for the file, otherwise use the remembered one
+        let opt_enc_nm = match enc {
+            Some(label) => self.set_encoding(path_str, Some(label)).await?,
+            None => self.get_encoding(path_str).await?,
+        };
+        let opt_encoding: Option<&'static Encoding> = match opt_enc_nm {
+            Some(nm) => Some(get_encoding(&nm)?),
+            None => None,
+        };
+        let TextBuf { sz, tm_ms, mimetype: mime_type, codec, buffer, is_over } = self.read_buf(path_str, TEXT_MAX_LEN).await?;
+        let nm = match codec {
+            Some(codec) => strip_codec_ext(path_str, codec),
+            None => path_str,
+        };
 
-        if sz > 5 * 1024 * 1024 {
+        if is_over {
             // return Err(ApiError::Folder(String::from("Err MimeType")))
             Ok(TextContent {
                 path: path_str.to_string(),
                 mimetype: mime_type,
-                enc: None,
-                text: None
+                enc: opt_encoding.map(|encoding| encoding.name().to_string()),
+                text: None,
+                language: get_lang_by_name(nm).map(|lang| lang.to_string()),
+                sz: Some(sz),
+                tm_ms,
+                codec: codec.map(|codec| codec.to_string()),
+                ..TextContent::default()
             })
         } else {
-            file = tokio::fs::File::open(&path).await?;
-            let mut buffer = Vec::new();
-            file.read_to_end(&mut buffer).await?;
-
-            let mut detector = EncodingDetector::new();
-            detector.feed(&buffer, true);
-            let encoding: &Encoding = detector.guess(None, true);
-
-            let (text, _, had_errors) = encoding.decode(&buffer);
-            let opt_text = if had_errors {
-                None
-            } else {
-                Some(text.into_owned())
+            let (decoded, detection) = match opt_encoding {
+                Some(encoding) => (decode_text(&buffer, encoding, false), None),
+                None => {
+                    let (encoding, assess) = detect_encoding(&buffer);
+                    let decoded = decode_text(&buffer, encoding, true);
+                    let detection = get_detection(&buffer, &decoded, assess);
+                    (decoded, Some(detection))
+                }
             };
 
             Ok(TextContent {
                 path: path_str.to_string(),
                 mimetype: mime_type,
-                enc: Some(encoding.name().to_string()),
-                text: opt_text
+                enc: Some(decoded.encoding.name().to_string()),
+                detection,
+                errs: Some(decoded.errs),
+                err_cnt: Some(decoded.err_cnt),
+                bom: Some(decoded.bom),
+                eol: Some(get_eol_info(&decoded.text)),
+                final_newline: Some(has_final_newline(&decoded.text)),
+                indent: Some(get_indent_info(&decoded.text)),
+                language: Some(detect_lang(nm, &decoded.text)),
+                text: Some(decoded.text),
+                sz: Some(sz),
+                tm_ms,
+                codec: codec.map(|codec| codec.to_string()),
             })
         }
     }
 
+    ///
+    /// (text, encoding, is_over) by `read_txt`. a file larger than `TEXT_MAX_LEN` is cut to
+    /// its head at a line end, decompressed or in an archive as well
+    async fn read_diff_text(&self, path_str: &str) -> Result<(String, Option<String>, bool), ApiError> {
+        let content = self.read_txt(path_str, None).await?;
+        if let Some(text) = content.text {
+            return Ok((text, content.enc, false));
+        }
+        let mut buffer = self.read_buf_head(path_str, TEXT_MAX_LEN).await?.buffer;
+        let encoding = match content.enc {
+            Some(nm) => get_encoding(&nm)?,
+            None => detect_encoding(&buffer).0,
+        };
+        if encoding.is_ascii_compatible() {
+            if let Some(pos) = buffer.iter().rposition(|b| *b == b'\n') {
+                buffer.truncate(pos + 1);
+            }
+        }
+        let decoded = decode_text(&buffer, encoding, true);
+        Ok((decoded.text, Some(decoded.encoding.name().to_string()), true))
+    }
+
+    pub async fn diff_files(&self, left: &str, right: &str, opts: DiffOpts) -> Result<DiffContent, ApiError> {
+        let (old, left_enc, left_over) = self.read_diff_text(left).await?;
+        let (new, right_enc, right_over) = self.read_diff_text(right).await?;
+        let (left, right) = (left.to_string(), right.to_string());
+        let content = tokio::task::spawn_blocking(move || diff_texts(&left, &old, &right, &new, &opts))
+            .await
+            .map_err(|e| ApiError::Diff(e.to_string()))?;
+        Ok(DiffContent {
+            left_enc,
+            right_enc,
+            is_over: content.is_over || left_over || right_over,
+            ..content
+        })
+    }
+
+    pub async fn write_txt(&self, path_str: &str, text: &str, opts: WriteOpts) -> Result<WriteInfo, ApiError> {
+        let path = self.policy.check_write(path_str)?;
+        if split_archive_path(&path).is_some_and(|(_, inner)| !inner.is_empty()) {
+            return Err(ApiError::Archive(format!("Err Read Only: {}", path_str)));
+        }
+        let opt_meta = match path.metadata() {
+            Ok(meta) => Some(meta),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        // text of a compressed file is shown decompressed, so it would be saved over the compressed data
+        let opt_codec = match (get_codec_by_ext(path_str), &opt_meta) {
+            (Some(codec), _) => Some(codec),
+            (None, Some(_)) => {
+                let mut sample = Vec::new();
+                tokio::fs::File::open(&path).await?.take(TEXT_SAMPLE_LEN).read_to_end(&mut sample).await?;
+                get_codec(&sample)
+            }
+            (None, None) => None,
+        };
+        if let Some(codec) = opt_codec {
+            return Err(ApiError::Compress(format!("Err Read Only: {} {}", codec, path_str)));
+        }
+
+        if !opts.force.unwrap_or(false) {
+            let cur_sz = opt_meta.as_ref().map(|meta| meta.len());
+            let cur_tm_ms = opt_meta.as_ref().and_then(|meta| meta.modified().ok()).map(|t| t.to_ms());
+            if opts.sz.is_some() && opts.sz != cur_sz {
+                return Err(ApiError::Conflict(format!("Err Size Changed: {:?} -> {:?}", opts.sz, cur_sz)));
+            }
+            if opts.tm_ms.is_some() && opts.tm_ms != cur_tm_ms {
+                return Err(ApiError::Conflict(format!("Err Modified: {:?} -> {:?}", opts.tm_ms, cur_tm_ms)));
+            }
+        }
+
+        let encoding = match opts.enc {
+            Some(label) => get_encoding(&label)?,
+            None => match self.get_encoding(path_str).await? {
+                Some(nm) => get_encoding(&nm)?,
+                None => encoding_rs::UTF_8,
+            }
+        };
+        let text = convert_eol(text, &opts.eol.unwrap_or_default());
+        let buffer = encode_text(&text, encoding, opts.bom.unwrap_or(false))?;
+
+        // write to a temp file in the same folder, then rename over the original
+        let file_name = match path.file_name() {
+            Some(nm) => nm.to_string_lossy().to_string(),
+            None => return Err(ApiError::Io(format!("Err File Name: {}", path_str))),
+        };
+        let tmp_path = path.with_file_name(format!(".{}.{}.tmp", file_name, std::process::id()));
+        let res: Result<(), ApiError> = async {
+            let mut file = tokio::fs::File::create(&tmp_path).await?;
+            file.write_all(&buffer).await?;
+            file.sync_all().await?;
+            drop(file);
+            if let Some(meta) = &opt_meta {
+                tokio::fs::set_permissions(&tmp_path, meta.permissions()).await?;
+            }
+            tokio::fs::rename(&tmp_path, &path).await?;
+            Ok(())
+        }.await;
+        if let Err(e) = res {
+            let _ = tokio::fs::remove_file(&tmp_path).await;
+            return Err(e);
+        }
+
+        let meta = path.metadata()?;
+        Ok(WriteInfo {
+            path: path_str.to_string(),
+            enc: encoding.name().to_string(),
+            sz: meta.len(),
+            tm_ms: meta.modified()?.to_ms(),
+        })
+    }
+
+    pub async fn read_structured(&self, path_str: &str, path_expr: Option<String>,
+                                 skip_n: Option<usize>, take_n: Option<usize>) -> Result<StructContent, ApiError> {
+        let abs = self.policy.check_read(path_str)?;
+        let meta_path = match split_archive_path(&abs) {
+            Some((archive, _)) => archive,
+            None => abs.clone(),
+        };
+        let meta = meta_path.metadata()?;
+        let key = format!("{}|{}|{}", abs.to_string_lossy(), meta.len(), meta.modified()?.to_ms());
+
+        // parsed documents are cached, so expanding nodes does not parse again
+        let (format, doc) = match self.cache_struct.get(&key).await {
+            Some(cached) => cached,
+            None => {
+                let buf = self.read_buf(path_str, STRUCT_MAX_LEN).await?;
+                if buf.is_over {
+                    return Err(ApiError::Struct(format!("Err Too Large: {}", buf.sz)));
+                }
+                let encoding = match self.get_encoding(path_str).await? {
+                    Some(nm) => get_encoding(&nm)?,
+                    None => detect_encoding(&buf.buffer).0,
+                };
+                let text = decode_text(&buf.buffer, encoding, true).text;
+                let nm = match buf.codec {
+                    Some(codec) => strip_codec_ext(path_str, codec),
+                    None => path_str,
+                };
+                let language = detect_lang(nm, &text);
+                let format = get_struct_format(nm, Some(&language))
+                    .ok_or_else(|| ApiError::Struct(format!("Err Format: {}", language)))?;
+                match parse_struct(&text, format) {
+                    Ok(value) => {
+                        let cached = (format, Arc::new(value));
+                        self.cache_struct.insert(key, cached.clone()).await;
+                        cached
+                    }
+                    Err(err) => return Ok(StructContent {
+                        path: path_str.to_string(),
+                        format: Some(format),
+                        err: Some(err),
+                        ..StructContent::default()
+                    }),
+                }
+            }
+        };
+
+        let path_expr = path_expr.unwrap_or_default();
+        let value = doc.pointer(&path_expr)
+            .ok_or_else(|| ApiError::Struct(format!("Err Path: {}", path_expr)))?;
+        let node = to_node(path_expr.rsplit('/').next().unwrap_or_default(), &path_expr, value);
+        let skip = skip_n.unwrap_or(0);
+        let take = take_n.unwrap_or(STRUCT_TAKE_N);
+        let items = get_children(value, &path_expr, skip, take);
+        Ok(StructContent {
+            path: path_str.to_string(),
+            format: Some(format),
+            tot: node.cnt,
+            skip_n: Some(skip),
+            take_n: Some(items.len()),
+            node: Some(node),
+            items: Some(items),
+            err: None,
+        })
+    }
+
+    pub async fn read_table(&self, path_str: &str, opts: TableOpts) -> Result<TableContent, ApiError> {
+        let path = self.policy.check_read(path_str)?;
+        let meta = path.metadata()?;
+
+        let mut file = tokio::fs::File::open(&path).await?;
+        let mut sample = vec![0u8; TABLE_SAMPLE_LEN as usize];
+        let n = file.read(&mut sample).await?;
+        sample.truncate(n);
+
+        // explicit encoding is remembered for the file like read_txt
+        let opt_enc_nm = match opts.enc {
+            Some(label) => self.set_encoding(path_str, Some(label)).await?,
+            None => self.get_encoding(path_str).await?,
+        };
+        let encoding = match opt_enc_nm {
+            Some(nm) => get_encoding(&nm)?,
+            None => detect_encoding(&sample).0,
+        };
+        if !encoding.is_ascii_compatible() {
+            return Err(ApiError::Table(format!("Err Encoding: {}", encoding.name())));
+        }
+        let delimiter = match opts.delimiter.as_deref().map(|d| d.as_bytes()) {
+            Some([d]) => *d,
+            Some(_) => return Err(ApiError::Table(format!("Err Delimiter: {:?}", opts.delimiter))),
+            None => sniff_delimiter(path_str, &sample),
+        };
+        let has_header = opts.has_header.unwrap_or_else(|| sniff_header(&sample, delimiter, encoding));
+
+        let key = format!("{}|{}|{}|{}|{}|{}", path.to_string_lossy(), meta.len(), meta.modified()?.to_ms(),
+                          encoding.name(), delimiter, has_header);
+        let index = match self.cache_table.get(&key).await {
+            Some(index) => index,
+            None => {
+                let index_path = path.clone();
+                let index = tokio::task::spawn_blocking(move || build_index(&index_path, encoding, delimiter, has_header)).await
+                    .map_err(|e| ApiError::Table(e.to_string()))??;
+                let index = Arc::new(index);
+                self.cache_table.insert(key.clone(), index.clone()).await;
+                index
+            }
+        };
+
+        // sorted and filtered row numbers are cached per file like sorted folder items
+        let sorts = opts.sorts.unwrap_or_default();
+        let filters = opts.filters.unwrap_or_default();
+        let opt_view = if sorts.is_empty() && filters.is_empty() {
+            None
+        } else {
+            let view_key = format!("{}|{:?}|{:?}", key, sorts, filters);
+            match self.cache_table_view.get(&view_key).await {
+                Some(view) => Some(view),
+                None => {
+                    let (view_path, view_index) = (path.clone(), index.clone());
+                    let view = tokio::task::spawn_blocking(move || get_view(&view_path, &view_index, &sorts, &filters)).await
+                        .map_err(|e| ApiError::Table(e.to_string()))??;
+                    let view = Arc::new(view);
+                    self.cache_table_view.insert(view_key, view.clone()).await;
+                    Some(view)
+                }
+            }
+        };
+
+        let tot = opt_view.as_ref().map(|view| view.len()).unwrap_or(index.offsets.len());
+        let skip = cmp::min(opts.skip_n.unwrap_or(0), tot);
+        let take = cmp::min(cmp::min(opts.take_n.unwrap_or(TABLE_TAKE_N), TABLE_TAKE_MAX), tot - skip);
+        let row_nos: Vec<usize> = match &opt_view {
+            Some(view) => view[skip..skip + take].to_vec(),
+            None => (skip..skip + take).collect(),
+        };
+        let offsets: Vec<u64> = row_nos.iter().map(|row_no| index.offsets[*row_no]).collect();
+        let rows = read_rows(&path, &index, &offsets)?;
+
+        Ok(TableContent {
+            path: path_str.to_string(),
+            enc: encoding.name().to_string(),
+            delimiter: (delimiter as char).to_string(),
+            has_header,
+            cols: index.cols.clone(),
+            row_cnt: index.offsets.len(),
+            tot,
+            skip_n: skip,
+            take_n: take,
+            row_nos,
+            rows,
+        })
+    }
+
+    pub async fn read_db(&self, path_str: &str, opts: TableOpts) -> Result<DbContent, ApiError> {
+        let abs = self.policy.check_read(path_str)?;
+        let (db, inner) = split_db_path(&abs)
+            .filter(|(_, inner)| !inner.is_empty())
+            .ok_or_else(|| ApiError::Db(format!("Err Not Table: {}", path_str)))?;
+        let sorts = opts.sorts.unwrap_or_default();
+        let filters = opts.filters.unwrap_or_default();
+        let skip_n = opts.skip_n.unwrap_or(0);
+        let take_n = opts.take_n.unwrap_or(DB_TAKE_N).min(DB_TAKE_MAX);
+        tokio::task::spawn_blocking(move || read_db_rows(&db, &inner, &sorts, &filters, skip_n, take_n))
+            .await
+            .map_err(|e| ApiError::Db(e.to_string()))?
+    }
+
+    pub async fn render_markdown(&self, path_str: &str) -> Result<MarkdownContent, ApiError> {
+        let buf = self.read_buf(path_str, MD_MAX_LEN).await?;
+        if buf.is_over {
+            return Err(ApiError::Html(format!("Err Too Large: {}", buf.sz)));
+        }
+        let encoding = match self.get_encoding(path_str).await? {
+            Some(nm) => get_encoding(&nm)?,
+            None => detect_encoding(&buf.buffer).0,
+        };
+        let text = decode_text(&buf.buffer, encoding, true).text;
+        // images of a file in an archive can not be streamed
+        let abs = resolve_path(Path::new(path_str))?;
+        let opt_base_dir = match split_archive_path(&abs) {
+            Some(_) => None,
+            None => abs.parent().map(|p| p.to_path_buf()),
+        };
+        let (html, toc, local_paths) = tokio::task::spawn_blocking(move || render_markdown(&text, opt_base_dir))
+            .await
+            .map_err(|e| ApiError::Html(e.to_string()))?;
+        for path in &local_paths {
+            self.add_opened_file(path).await;
+        }
+        Ok(MarkdownContent {
+            path: path_str.to_string(),
+            html,
+            toc,
+            sz: Some(buf.sz),
+            tm_ms: buf.tm_ms,
+        })
+    }
+
+    pub async fn read_html_safe(&self, path_str: &str) -> Result<HtmlContent, ApiError> {
+        let buf = self.read_buf(path_str, HTML_MAX_LEN).await?;
+        if buf.is_over {
+            return Err(ApiError::Html(format!("Err Too Large: {}", buf.sz)));
+        }
+        let encoding = match self.get_encoding(path_str).await? {
+            Some(nm) => get_encoding(&nm)?,
+            None => detect_encoding(&buf.buffer).0,
+        };
+        let text = decode_text(&buf.buffer, encoding, true).text;
+        let abs = resolve_path(Path::new(path_str))?;
+        let opt_base_dir = match split_archive_path(&abs) {
+            Some(_) => None,
+            None => abs.parent().map(|p| p.to_path_buf()),
+        };
+        let SafeHtml { html, removed_tags, removed_attrs, remote_urls, local_paths } =
+            tokio::task::spawn_blocking(move || sanitize_html(&text, opt_base_dir))
+                .await
+                .map_err(|e| ApiError::Html(e.to_string()))?;
+        for path in &local_paths {
+            self.add_opened_file(path).await;
+        }
+        Ok(HtmlContent {
+            path: path_str.to_string(),
+            html,
+            removed_tags,
+            removed_attrs,
+            remote_urls,
+            sz: Some(buf.sz),
+            tm_ms: buf.tm_ms,
+        })
+    }
+
+    pub async fn read_office(&self, path_str: &str) -> Result<OfficeContent, ApiError> {
+        let path = self.policy.check_read(path_str)?;
+        tokio::task::spawn_blocking(move || read_office(&path))
+            .await
+            .map_err(|e| ApiError::Office(e.to_string()))?
+    }
+
+    pub async fn read_pdf(&self, path_str: &str, pages: Option<Vec<u32>>) -> Result<PdfContent, ApiError> {
+        let path = self.policy.check_read(path_str)?;
+        let mut page_nos = pages.unwrap_or_else(|| (1..=PDF_TAKE_N as u32).collect());
+        page_nos.truncate(PDF_TAKE_MAX);
+        tokio::task::spawn_blocking(move || read_pdf(&path, &page_nos))
+            .await
+            .map_err(|e| ApiError::Pdf(e.to_string()))?
+    }
+
+    pub async fn media_info(&self, path_str: &str) -> Result<MediaInfo, ApiError> {
+        let path = self.policy.check_read(path_str)?;
+        tokio::task::spawn_blocking(move || get_media_info(&path))
+            .await
+            .map_err(|e| ApiError::Media(e.to_string()))?
+    }
+
+    pub async fn image_info(&self, path_str: &str) -> Result<ImgInfo, ApiError> {
+        let path = self.policy.check_read(path_str)?;
+        get_img_info(&path)
+    }
+
+    pub async fn get_thumb_key(&self, path_str: &str, size: u32) -> Result<String, ApiError> {
+        let path = self.policy.check_read(path_str)?;
+        let meta = path.metadata()?;
+        Ok(get_thumb_key(&path, &meta, size))
+    }
+
+    pub async fn get_thumb(&self, path_str: &str, size: u32) -> Result<Thumb, ApiError> {
+        let path = self.policy.check_read(path_str)?;
+        let key = get_thumb_key(&path, &path.metadata()?, size);
+        if let Some(thumb) = read_thumb_cache(&key) {
+            return Ok(thumb);
+        }
+
+        let _permit = self.thumb_pool.acquire().await
+            .map_err(|e| ApiError::Img(e.to_string()))?;
+        // made by another request while waiting
+        if let Some(thumb) = read_thumb_cache(&key) {
+            return Ok(thumb);
+        }
+        // the embedded cover art for audio and video
+        let thumb = tokio::task::spawn_blocking(move || if is_media(&path.to_string_lossy()) {
+            make_thumb_from_buf(&get_cover(&path)?, size, &key)
+        } else {
+            make_thumb(&path, size, &key)
+        }).await
+            .map_err(|e| ApiError::Img(e.to_string()))??;
+        // the thumb is made again if it is not cached
+        let _ = write_thumb_cache(&thumb);
+        Ok(thumb)
+    }
+
+    pub async fn read_hex(&self, path_str: &str, offset: u64, len: u64) -> Result<HexContent, ApiError> {
+        let path = self.policy.check_read(path_str)?;
+        let sz = path.metadata()?.len();
+
+        let mut file = tokio::fs::File::open(&path).await?;
+        let mut sample = vec![0u8; 16 * 1024];
+        let n = file.read(&mut sample).await?;
+        sample.truncate(n);
+
+        let magic = get_magic(&sample);
+        let mime_type = match &magic {
+            Some(magic) if !magic.mimetype.is_empty() => magic.mimetype.clone(),
+            _ => from_path(path_str).first_or_octet_stream().to_string()
+        };
+
+        let offset = cmp::min(offset, sz);
+        let len = cmp::min(cmp::min(len, HEX_MAX_LEN), sz - offset);
+        file.seek(std::io::SeekFrom::Start(offset)).await?;
+        let mut buffer = vec![0u8; len as usize];
+        file.read_exact(&mut buffer).await?;
+
+        Ok(HexContent {
+            path: path_str.to_string(),
+            mimetype: mime_type,
+            magic,
+            sz,
+            offset,
+            len,
+            rows: to_rows(&buffer, offset),
+        })
+    }
+
+    ///
+    /// scanned off the runtime, cancelled by `cancel_search` of `id` like `search_in_file`
+    pub async fn search_hex(&self, path_str: &str, pattern: &str, offset: u64, take_n: Option<usize>, id: Option<String>) -> Result<HexSearch, ApiError> {
+        let path = self.policy.check_read(path_str)?;
+        let needle = parse_pattern(pattern)?;
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        if let Some(id) = &id {
+            self.searches.insert(id.clone(), cancel.clone()).await;
+        }
+        let res = tokio::task::spawn_blocking(move || search_bytes(&path, &needle, offset, take_n, &cancel))
+            .await
+            .map_err(|e| ApiError::Hex(e.to_string()))?;
+        if let Some(id) = id {
+            self.searches.invalidate(&id).await;
+        }
+        Ok(HexSearch {
+            path: path_str.to_string(),
+            pattern: pattern.to_string(),
+            ..res?
+        })
+    }
+
+    ///
+    /// streamed, so not limited by `TEXT_MAX_LEN` like `read_txt`
+    pub async fn search_in_file(&self, path_str: &str, pattern: &str, opts: SearchOpts) -> Result<TextSearch, ApiError> {
+        let path = self.policy.check_read(path_str)?;
+        // explicit encoding is remembered for the file like read_txt
+        let opt_enc_nm = match opts.enc.clone() {
+            Some(label) => self.set_encoding(path_str, Some(label)).await?,
+            None => self.get_encoding(path_str).await?,
+        };
+        let mut sample = Vec::new();
+        tokio::fs::File::open(&path).await?.take(TEXT_SAMPLE_LEN).read_to_end(&mut sample).await?;
+        let encoding = match (Encoding::for_bom(&sample), opt_enc_nm) {
+            (Some((encoding, _)), _) => encoding,
+            (None, Some(nm)) => get_encoding(&nm)?,
+            (None, None) => detect_encoding(&sample).0,
+        };
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        if let Some(id) = &opts.id {
+            self.searches.insert(id.clone(), cancel.clone()).await;
+        }
+        let opt_id = opts.id.clone();
+        let pattern = pattern.to_string();
+        let res = tokio::task::spawn_blocking(move || search_file(&path, &pattern, encoding, &opts, &cancel))
+            .await
+            .map_err(|e| ApiError::Search(e.to_string()))?;
+        if let Some(id) = opt_id {
+            self.searches.invalidate(&id).await;
+        }
+        res
+    }
+
+    ///
+    /// `true` if the search of the id is running
+    pub async fn cancel_search(&self, id: &str) -> bool {
+        match self.searches.get(id).await {
+            Some(cancel) => {
+                cancel.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+
     pub async fn get_home_dir(&self) -> Result<HashMap<HomeType, String>, ApiError> {
         Ok([
             (HomeType::RootDir, Some(std::path::absolute(PathBuf::from("/"))?)),
@@ -303,14 +1178,22 @@ impl Api {
             (HomeType::RuntimeDir ,dirs_next::runtime_dir()),
             (HomeType::TemplateDir ,dirs_next::template_dir()),
         ].into_iter().filter_map(|(k, opt) | {
-            opt.map(|v| (k, v.to_string_lossy().into_owned()))
+            opt.filter(|v| self.is_allowed(v)).map(|v| (k, v.to_string_lossy().into_owned()))
         }).collect())
     }
 
     pub async fn get_disks(&self) -> Result<Vec<DiskInfo>, ApiError> {
+        // a kiosk sees its allowed roots as the disks
+        let allow = self.policy.get_conf().allow;
+        if !allow.is_empty() {
+            return Ok(allow.into_iter()
+                .filter(|root| Path::new(root).is_dir())
+                .map(|path| DiskInfo { path })
+                .collect());
+        }
         let disks = Disks::new_with_refreshed_list();
         let mut ret: Vec<DiskInfo> = vec![];
-        for disk in &disks {
+        for disk in disks.iter().filter(|disk| self.is_allowed(disk.mount_point())) {
             let disk_info = DiskInfo {
                 path: disk.mount_point().to_string_lossy().into_owned(),
             };
@@ -322,6 +1205,90 @@ impl Api {
     pub async fn get_arg_path(&self) -> Result<Option<String>, ApiError> {
         Ok(get_arg_path())
     }
+
+    pub async fn get_policy(&self) -> Result<AccessPolicy, ApiError> {
+        Ok(self.policy.get_conf())
+    }
+
+    ///
+    /// bookmarks in order with `dir` and `exists` of the targets
+    pub async fn get_bookmarks(&self) -> Result<Vec<Bookmark>, ApiError> {
+        let items = self.bookmarks.read().await.get_items();
+        let allowed: Vec<bool> = items.iter().map(|item| self.is_allowed(Path::new(&item.path))).collect();
+        // a network share can be slow or offline
+        tokio::task::spawn_blocking(move || {
+            items.into_iter().zip(allowed).map(|(item, is_allowed)| {
+                let opt_meta = if is_allowed { std::fs::metadata(&item.path).ok() } else { None };
+                Bookmark {
+                    dir: opt_meta.as_ref().map(|meta| meta.is_dir()),
+                    exists: Some(opt_meta.is_some()),
+                    ..item
+                }
+            }).collect()
+        })
+            .await
+            .map_err(|e| ApiError::Bookmark(e.to_string()))
+    }
+
+    pub async fn add_bookmark(&self, path_str: &str, nm: Option<String>, group: Option<String>) -> Result<Bookmark, ApiError> {
+        let abs = self.policy.check_read(path_str)?.to_string_lossy().to_string();
+        self.update_bookmarks(|bookmarks| bookmarks.add(&abs, nm, group)).await
+    }
+
+    pub async fn remove_bookmark(&self, id: &str) -> Result<Bookmark, ApiError> {
+        self.update_bookmarks(|bookmarks| bookmarks.remove(id)).await
+    }
+
+    pub async fn rename_bookmark(&self, id: &str, nm: String) -> Result<Bookmark, ApiError> {
+        if nm.trim().is_empty() {
+            return Err(ApiError::Bookmark(String::from("Err Empty Name")));
+        }
+        self.update_bookmarks(|bookmarks| bookmarks.update(id, |item| item.nm = nm)).await
+    }
+
+    pub async fn set_bookmark_group(&self, id: &str, group: Option<String>) -> Result<Bookmark, ApiError> {
+        let group = group.filter(|group| !group.trim().is_empty());
+        self.update_bookmarks(|bookmarks| bookmarks.update(id, |item| item.group = group)).await
+    }
+
+    pub async fn reorder_bookmarks(&self, ids: Vec<String>) -> Result<Vec<Bookmark>, ApiError> {
+        self.update_bookmarks(|bookmarks| bookmarks.reorder(&ids)).await
+    }
+
+    ///
+    /// (count of the bookmarks written)
+    pub async fn export_bookmarks(&self, path_str: &str) -> Result<usize, ApiError> {
+        let path = self.policy.check_write(path_str)?;
+        let items = self.bookmarks.read().await.get_items();
+        write_atomic(&path, &to_bookmarks_json(&items)?)?;
+        Ok(items.len())
+    }
+
+    ///
+    /// (count of the bookmarks added)
+    pub async fn import_bookmarks(&self, path_str: &str, replace: bool) -> Result<usize, ApiError> {
+        let path = self.policy.check_read(path_str)?;
+        let imported = parse_bookmarks(&tokio::fs::read_to_string(&path).await?)?;
+        self.update_bookmarks(|bookmarks| bookmarks.import(imported, replace)).await
+    }
+
+    ///
+    /// changes the bookmarks and notifies the items before and after as `BOOKMARK_KEY`
+    async fn update_bookmarks<T>(&self, f: impl FnOnce(&mut Bookmarks) -> Result<T, ApiError>) -> Result<T, ApiError> {
+        let mut bookmarks = self.bookmarks.write().await;
+        let old = bookmarks.get_items();
+        let res = f(&mut bookmarks)?;
+        let new = bookmarks.get_items();
+        drop(bookmarks);
+        self.notify_state(BOOKMARK_KEY.to_string(), Some(serde_json::to_value(old)?), Some(serde_json::to_value(new)?), None);
+        Ok(res)
+    }
+
+    ///
+    /// home dirs and disks outside the policy are not listed
+    fn is_allowed(&self, path: &Path) -> bool {
+        resolve_path(path).is_ok_and(|path| self.policy.is_allowed(&path))
+    }
 }
 
 
@@ -331,6 +1298,8 @@ impl Api {
 mod tests {
     // use crate::{models};
     use super::*;
+    use crate::hex::HEX_CHUNK_LEN;
+    use crate::test_util::temp_dir;
 
 
     #[tokio::test]
@@ -356,6 +1325,29 @@ mod tests {
         assert_eq!(api.get_folder(&params).await.unwrap().base_nm, "C:");
     }
 
+    #[tokio::test]
+    async fn test_opened_file() {
+        let tmp = temp_dir();
+        let base = tmp.path().to_path_buf();
+        std::fs::create_dir_all(base.join("sub")).unwrap();
+        std::fs::write(base.join("a.mp4"), "a").unwrap();
+        std::fs::write(base.join("sub").join("b.mp4"), "b").unwrap();
+        let path_str = |path: PathBuf| path.to_string_lossy().to_string();
+
+        let api = Api::default();
+        assert!(api.get_opened_file(&path_str(base.join("a.mp4"))).await.is_none());
+        api.add_opened_dir(&base).await;
+        assert!(api.get_opened_file(&path_str(base.join("a.mp4"))).await.is_some());
+        assert!(api.get_opened_file(&path_str(base.join("sub").join("..").join("a.mp4"))).await.is_some());
+        assert!(api.get_opened_file(&path_str(base.join("sub").join("b.mp4"))).await.is_none());
+        api.add_opened_file(&base.join("sub").join("b.mp4")).await;
+        assert!(api.get_opened_file(&path_str(base.join("sub").join("b.mp4"))).await.is_some());
+
+        let drive = base.ancestors().last().unwrap();
+        api.add_opened_dir(drive).await;
+        assert!(api.opened_dirs.read().await.contains(&resolve_path(drive).unwrap()));
+    }
+
     #[tokio::test]
     async fn test_permissions() {
         let api = Api::default();
@@ -412,10 +1404,47 @@ mod tests {
     #[tokio::test]
     async fn test_state() {
         let api = Api::default();
-        let s = api.set_state(String::from("a"), Some(String::from("1"))).await;
-        println!("{:?}", s);
-        let s = api.get_state(&String::from("a"), None).await;
-        println!("{:?}", s);
+        let mut rx = api.watch_state();
+        let s = api.set_state(String::from("app.a"), Some(Value::from(1)), Some(String::from("main"))).await;
+        assert_eq!(s.unwrap(), Some(Value::from(1)));
+        let s = api.get_state(&String::from("app.b"), Some(Value::from("x")), None).await;
+        assert_eq!(s.unwrap(), Some(Value::from("x")));
+        assert_eq!(api.list_state("app.").await.unwrap().len(), 2);
+        assert!(api.set_state(String::from("a"), None, None).await.is_err());
+        api.set_state(String::from("app.a"), None, None).await.unwrap();
+
+        let changed = rx.recv().await.unwrap();
+        assert_eq!((changed.key.as_str(), changed.old, changed.new, changed.source.as_deref()), ("app.a", None, Some(Value::from(1)), Some("main")));
+        assert_eq!(rx.recv().await.unwrap().key, "app.b");
+        assert_eq!(rx.recv().await.unwrap().old, Some(Value::from(1)));
+        assert!(rx.try_recv().is_err());
+
+        api.subscribe_state(String::from("main"), Some(String::from("folder."))).await.unwrap();
+        api.subscribe_state(String::from("viewer"), Some(String::from("app."))).await.unwrap();
+        assert_eq!(api.get_state_excluded("app.a").await, vec![String::from("main")]);
+        api.subscribe_state(String::from("main"), None).await.unwrap();
+        assert!(api.get_state_excluded("app.a").await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_bookmarks() {
+        let api = Api::default();
+        let tmp = temp_dir();
+        let dir = tmp.path();
+        let dir_str = dir.to_string_lossy().to_string();
+        let mut rx = api.watch_state();
+        let item = api.add_bookmark(&dir_str, None, None).await.unwrap();
+        assert_eq!(rx.try_recv().unwrap().key, BOOKMARK_KEY);
+        api.add_bookmark(&dir.join("gone.txt").to_string_lossy(), None, Some(String::from("old"))).await.unwrap();
+        let items = api.get_bookmarks().await.unwrap();
+        assert_eq!(items.iter().map(|item| (item.exists, item.dir)).collect::<Vec<_>>(), vec![(Some(true), Some(true)), (Some(false), None)]);
+        assert!(api.rename_bookmark(&item.id, String::from(" ")).await.is_err());
+
+        let file_str = dir.join("bookmarks.json").to_string_lossy().to_string();
+        assert_eq!(api.export_bookmarks(&file_str).await.unwrap(), 2);
+        api.remove_bookmark(&item.id).await.unwrap();
+        assert_eq!(api.import_bookmarks(&file_str, false).await.unwrap(), 1);
+        assert_eq!(api.get_bookmarks().await.unwrap().len(), 2);
     }
 
     #[tokio::test]
@@ -426,6 +1455,176 @@ mod tests {
     }
 
 
+    #[tokio::test]
+    async fn test_search_hex() {
+        let api = Api::default();
+        let tmp = temp_dir();
+        let path = tmp.path().join("search_hex.bin");
+        let mut buf = vec![0u8; HEX_CHUNK_LEN + 16];
+        buf[HEX_CHUNK_LEN - 1..HEX_CHUNK_LEN + 2].copy_from_slice(b"PNG");
+        buf[4..7].copy_from_slice(b"PNG");
+        std::fs::write(&path, &buf).unwrap();
+        let path_str = path.to_string_lossy().to_string();
+
+        let res = api.search_hex(&path_str, "50 4e 47", 0, None, None).await.unwrap();
+        assert_eq!(res.offsets, vec![4, HEX_CHUNK_LEN as u64 - 1]);
+        let res = api.search_hex(&path_str, "\"PNG\"", 0, Some(1), None).await.unwrap();
+        assert_eq!(res.offsets, vec![4]);
+        assert_eq!(res.next_offset, Some(HEX_CHUNK_LEN as u64 - 1));
+
+        let hex = api.read_hex(&path_str, 0, 20).await.unwrap();
+        assert_eq!(hex.sz, buf.len() as u64);
+        assert_eq!(hex.rows.len(), 2);
+        assert_eq!(hex.rows[0].ascii, "....PNG.........");
+    }
+
+    #[tokio::test]
+    async fn test_read_txt_enc() {
+        let api = Api::default();
+        let tmp = temp_dir();
+        let path = tmp.path().join("read_txt_enc.txt");
+        let (bytes, _, _) = encoding_rs::EUC_KR.encode("한글");
+        std::fs::write(&path, &bytes).unwrap();
+        let path_str = path.to_string_lossy().to_string();
+
+        let mut rx = api.watch_state();
+        let res = api.read_txt(&path_str, Some(String::from("cp949"))).await.unwrap();
+        assert_eq!(res.enc, Some(String::from("EUC-KR")));
+        assert_eq!(res.text, Some(String::from("한글")));
+        assert_eq!(rx.try_recv().unwrap().key, ENC_KEY);
+        api.read_txt(&path_str, Some(String::from("euc-kr"))).await.unwrap();
+        assert!(rx.try_recv().is_err());
+        let res = api.read_txt(&path_str, None).await.unwrap();
+        assert_eq!(res.enc, Some(String::from("EUC-KR")));
+        assert!(api.read_txt(&path_str, Some(String::from("abc"))).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_write_txt() {
+        let api = Api::default();
+        let tmp = temp_dir();
+        let path = tmp.path().join("write_txt.txt");
+        let (bytes, _, _) = encoding_rs::EUC_KR.encode("가\r\n나\r\n");
+        std::fs::write(&path, &bytes).unwrap();
+        let path_str = path.to_string_lossy().to_string();
+
+        let res = api.read_txt(&path_str, Some(String::from("euc-kr"))).await.unwrap();
+        let opts = WriteOpts {
+            enc: res.enc.clone(),
+            bom: res.bom,
+            eol: res.eol.map(|eol| eol.eol),
+            sz: res.sz,
+            tm_ms: res.tm_ms,
+            ..WriteOpts::default()
+        };
+        let info = api.write_txt(&path_str, "가\n다\n", opts.clone()).await.unwrap();
+        assert_eq!(info.enc, "EUC-KR");
+        let (expected, _, _) = encoding_rs::EUC_KR.encode("가\r\n다\r\n");
+        assert_eq!(std::fs::read(&path).unwrap(), expected.to_vec());
+
+        let conflicted = WriteOpts { sz: Some(1), ..opts };
+        assert!(matches!(api.write_txt(&path_str, "x", conflicted).await, Err(ApiError::Conflict(_))));
+    }
+
+    #[tokio::test]
+    async fn test_archive() {
+        use std::io::Write;
+        let api = Api::default();
+        let tmp = temp_dir();
+        let path = tmp.path().join("archive.zip");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+        zip.start_file("bin/readme.txt", zip::write::SimpleFileOptions::default()).unwrap();
+        zip.write_all(b"hello\n").unwrap();
+        zip.finish().unwrap();
+
+        let params = Params {
+            path_str: path.join("bin").to_string_lossy().to_string(),
+            meta_types: vec![MetaType::Sz],
+            ..Params::default()
+        };
+        let folder = api.get_folder(&params).await.unwrap();
+        assert_eq!(folder.item.nm, "bin");
+        let items = folder.item.items.unwrap();
+        assert_eq!((items[0].nm.as_str(), items[0].sz), ("readme.txt", Some(6)));
+
+        let params = Params { path_str: path.to_string_lossy().to_string(), ..Params::default() };
+        let folder = api.get_folder(&params).await.unwrap();
+        assert!(folder.item.dir);
+        assert_eq!(folder.item.items.unwrap()[0].nm, "bin");
+
+        let path_str = path.join("bin").join("readme.txt").to_string_lossy().to_string();
+        let res = api.read_txt(&path_str, None).await.unwrap();
+        assert_eq!(res.text, Some(String::from("hello\n")));
+        assert_eq!(res.sz, Some(6));
+        assert!(api.write_txt(&path_str, "x", WriteOpts::default()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_read_txt_gz() {
+        use std::io::Write;
+        let api = Api::default();
+        let tmp = temp_dir();
+        let path = tmp.path().join("read_txt_gz.json.gz");
+        let mut gz = flate2::write::GzEncoder::new(std::fs::File::create(&path).unwrap(), flate2::Compression::default());
+        gz.write_all(b"{\"a\": 1}\n").unwrap();
+        gz.finish().unwrap();
+
+        let res = api.read_txt(&path.to_string_lossy(), None).await.unwrap();
+        assert_eq!(res.codec, Some(String::from("gzip")));
+        assert_eq!(res.text, Some(String::from("{\"a\": 1}\n")));
+        assert_eq!(res.language, Some(String::from("json")));
+        let res = api.write_txt(&path.to_string_lossy(), "x", WriteOpts::default()).await;
+        assert!(matches!(res, Err(ApiError::Compress(_))));
+        // by the content without the extension
+        let renamed = path.with_extension("log");
+        std::fs::rename(&path, &renamed).unwrap();
+        let res = api.write_txt(&renamed.to_string_lossy(), "x", WriteOpts::default()).await;
+        assert!(matches!(res, Err(ApiError::Compress(_))));
+    }
+
+    #[tokio::test]
+    async fn test_read_structured() {
+        let api = Api::default();
+        let tmp = temp_dir();
+        let path = tmp.path().join("read_structured.json");
+        std::fs::write(&path, "{\"servers\": [{\"name\": \"a\"}, {\"name\": \"b\"}]}").unwrap();
+        let path_str = path.to_string_lossy().to_string();
+
+        let res = api.read_structured(&path_str, None, None, None).await.unwrap();
+        assert_eq!(res.format, Some(StructFormat::Json));
+        assert_eq!(res.items.unwrap()[0].path, "/servers");
+        let res = api.read_structured(&path_str, Some(String::from("/servers")), Some(1), Some(10)).await.unwrap();
+        assert_eq!((res.tot, res.skip_n, res.take_n), (Some(2), Some(1), Some(1)));
+        assert_eq!(res.items.unwrap()[0].preview, "{name}");
+        assert!(api.read_structured(&path_str, Some(String::from("/nothing")), None, None).await.is_err());
+
+        std::fs::write(&path, "{\"a\": }").unwrap();
+        let res = api.read_structured(&path_str, None, None, None).await.unwrap();
+        assert_eq!(res.err.map(|err| (err.line, err.col)), Some((1, 7)));
+    }
+
+    #[tokio::test]
+    async fn test_read_table() {
+        use crate::models::{OrderAsc, TableSort};
+        let api = Api::default();
+        let tmp = temp_dir();
+        let path = tmp.path().join("read_table.tsv");
+        std::fs::write(&path, "id\tname\n3\tc\n1\ta\n2\tb\n").unwrap();
+        let path_str = path.to_string_lossy().to_string();
+
+        let res = api.read_table(&path_str, TableOpts { take_n: Some(2), ..TableOpts::default() }).await.unwrap();
+        assert_eq!((res.delimiter.as_str(), res.has_header, res.row_cnt, res.tot), ("\t", true, 3, 3));
+        assert_eq!(res.rows, vec![vec!["3", "c"], vec!["1", "a"]]);
+        let opts = TableOpts {
+            sorts: Some(vec![TableSort { col: 0, asc: OrderAsc::Asc }]),
+            skip_n: Some(1),
+            ..TableOpts::default()
+        };
+        let res = api.read_table(&path_str, opts).await.unwrap();
+        assert_eq!(res.row_nos, vec![2, 0]);
+        assert_eq!(res.rows, vec![vec!["2", "b"], vec!["3", "c"]]);
+    }
+
     #[tokio::test]
     async fn test_read_txt() {
         let api = Api::default();
@@ -434,7 +1633,7 @@ mod tests {
         // let s = r"c:\docs\t1.json";
         let s = r"C:\Users\kkt\Downloads\vite.main.config.ts";
         // let s = r"C:\sources\sample\header-logo.png";
-        match api.read_txt(s).await {
+        match api.read_txt(s, None).await {
             Ok(text_content) => {
                 println!("{:?}", text_content);
             },
@@ -0,0 +1,335 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use flate2::read::GzDecoder;
+use sevenz_rust::{Password, SevenZArchiveEntry, SevenZReader};
+use zip::ZipArchive;
+use crate::models::{ApiError, Item, MetaType};
+use crate::system_time_ext::civil_to_sec;
+
+type Result<T> = std::result::Result<T, ApiError>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+    SevenZ,
+}
+
+const ARCHIVE_EXTS: &[(&str, ArchiveKind)] = &[
+    (".zip", ArchiveKind::Zip),
+    (".jar", ArchiveKind::Zip),
+    (".tar", ArchiveKind::Tar),
+    (".tar.gz", ArchiveKind::TarGz),
+    (".tgz", ArchiveKind::TarGz),
+    (".7z", ArchiveKind::SevenZ),
+];
+
+///
+/// entry of an archive
+///
+/// - nm: full path in the archive, `/` separated without trailing `/`
+/// - tm: unix time
+#[derive(Clone, Debug, Default)]
+pub struct ArchiveEntry {
+    pub nm: String,
+    pub dir: bool,
+    pub sz: u64,
+    pub tm: Option<u64>,
+}
+
+fn get_kind(nm: &str) -> Option<ArchiveKind> {
+    let nm = nm.to_lowercase();
+    ARCHIVE_EXTS.iter().find(|(ext, _)| nm.ends_with(ext)).map(|(_, kind)| *kind)
+}
+
+pub fn is_archive(nm: &str) -> bool {
+    get_kind(nm).is_some()
+}
+
+///
+/// `C:\drops\build.zip\bin\a.txt` -> (`C:\drops\build.zip`, `bin/a.txt`)
+///
+/// `None` if no archive file is in the path
+pub fn split_archive_path(path: &Path) -> Option<(PathBuf, String)> {
+    split_container_path(path, |p| {
+        p.file_name().is_some_and(|nm| is_archive(&nm.to_string_lossy())) && p.is_file()
+    })
+}
+
+///
+/// split at the first file of the path that `is_container`, the rest is `/` joined
+pub fn split_container_path(path: &Path, is_container: impl Fn(&Path) -> bool) -> Option<(PathBuf, String)> {
+    let mut container = PathBuf::new();
+    let mut components = path.components();
+    while let Some(component) = components.next() {
+        container.push(component);
+        if is_container(&container) {
+            let inner = components
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect::<Vec<String>>()
+                .join("/");
+            return Some((container, inner));
+        }
+    }
+    None
+}
+
+fn normalize_nm(nm: &str) -> String {
+    let nm = nm.replace('\\', "/");
+    nm.trim_start_matches("./").trim_matches('/').to_string()
+}
+
+fn zip_time_to_sec(dt: zip::DateTime) -> u64 {
+    civil_to_sec(dt.year() as i64, dt.month() as i64, dt.day() as i64,
+                 dt.hour() as i64, dt.minute() as i64, dt.second() as i64).max(0) as u64
+}
+
+fn get_7z_time(file: &SevenZArchiveEntry) -> Option<u64> {
+    // 100ns ticks since 1601-01-01
+    let ticks: u64 = file.last_modified_date().into();
+    file.has_last_modified_date.then(|| (ticks / 10_000_000).saturating_sub(11_644_473_600))
+}
+
+fn open_tar(path: &Path, kind: ArchiveKind) -> Result<tar::Archive<Box<dyn Read>>> {
+    let reader = BufReader::new(File::open(path)?);
+    let reader: Box<dyn Read> = match kind {
+        ArchiveKind::TarGz => Box::new(GzDecoder::new(reader)),
+        _ => Box::new(reader),
+    };
+    Ok(tar::Archive::new(reader))
+}
+
+fn get_tar_entry(entry: &tar::Entry<Box<dyn Read>>) -> Result<ArchiveEntry> {
+    let header = entry.header();
+    Ok(ArchiveEntry {
+        nm: normalize_nm(&entry.path()?.to_string_lossy()),
+        dir: header.entry_type().is_dir(),
+        sz: header.size()?,
+        tm: header.mtime().ok(),
+    })
+}
+
+///
+/// all entries of the archive
+pub fn get_archive_entries(path: &Path) -> Result<Vec<ArchiveEntry>> {
+    let kind = get_kind(&path.to_string_lossy())
+        .ok_or_else(|| ApiError::Archive(format!("Err Kind: {:?}", path)))?;
+    let mut entries: Vec<ArchiveEntry> = vec![];
+    match kind {
+        ArchiveKind::Zip => {
+            let mut zip = ZipArchive::new(BufReader::new(File::open(path)?))?;
+            for i in 0..zip.len() {
+                let file = zip.by_index_raw(i)?;
+                entries.push(ArchiveEntry {
+                    nm: normalize_nm(file.name()),
+                    dir: file.is_dir(),
+                    sz: file.size(),
+                    tm: file.last_modified().map(zip_time_to_sec),
+                });
+            }
+        }
+        ArchiveKind::Tar | ArchiveKind::TarGz => {
+            let mut tar = open_tar(path, kind)?;
+            for entry in tar.entries()? {
+                entries.push(get_tar_entry(&entry?)?);
+            }
+        }
+        ArchiveKind::SevenZ => {
+            let reader = SevenZReader::open(path, Password::empty())?;
+            for file in &reader.archive().files {
+                entries.push(ArchiveEntry {
+                    nm: normalize_nm(file.name()),
+                    dir: file.is_directory(),
+                    sz: file.size(),
+                    tm: get_7z_time(file),
+                });
+            }
+        }
+    }
+    entries.retain(|entry| !entry.nm.is_empty());
+    Ok(entries)
+}
+
+pub fn is_archive_file(entries: &[ArchiveEntry], inner: &str) -> bool {
+    entries.iter().any(|entry| entry.nm == inner && !entry.dir)
+}
+
+///
+/// items of the folder `inner` in the entries of the archive. folders not stored in the archive are made from the file paths
+pub fn get_archive_items(path: &Path, entries: &[ArchiveEntry], inner: &str, meta_types: &Vec<MetaType>) -> Result<Vec<Item>> {
+    let prefix = if inner.is_empty() { String::new() } else { format!("{}/", inner) };
+    let mut items: Vec<Item> = vec![];
+    let mut idx: HashMap<String, usize> = HashMap::new();
+    let mut found = inner.is_empty();
+    for entry in entries {
+        if entry.nm == inner && entry.dir {
+            found = true;
+        }
+        let Some(rest) = entry.nm.strip_prefix(&prefix) else {
+            continue;
+        };
+        found = true;
+        let (nm, dir, is_self) = match rest.split_once('/') {
+            Some((nm, _)) => (nm.to_string(), true, false),
+            None => (rest.to_string(), entry.dir, true),
+        };
+        let item = Item {
+            nm: nm.clone(),
+            dir,
+            sz: if is_self && !dir && meta_types.contains(&MetaType::Sz) { Some(entry.sz) } else { None },
+            tm: if is_self && meta_types.contains(&MetaType::Tm) { entry.tm } else { None },
+            ..Item::default()
+        };
+        match idx.get(&nm) {
+            Some(&i) if is_self => items[i] = item,
+            Some(_) => {}
+            None => {
+                idx.insert(nm, items.len());
+                items.push(item);
+            }
+        }
+    }
+    if !found {
+        return Err(ApiError::Archive(format!("Err Not Found: {:?} {}", path, inner)));
+    }
+    Ok(items)
+}
+
+///
+/// read the file `inner` of the archive up to `max_len` bytes
+pub fn read_archive_file(path: &Path, inner: &str, max_len: u64) -> Result<(ArchiveEntry, Vec<u8>)> {
+    let kind = get_kind(&path.to_string_lossy())
+        .ok_or_else(|| ApiError::Archive(format!("Err Kind: {:?}", path)))?;
+    let not_found = || ApiError::Archive(format!("Err Not Found: {:?} {}", path, inner));
+    let mut buffer: Vec<u8> = vec![];
+    match kind {
+        ArchiveKind::Zip => {
+            let mut zip = ZipArchive::new(BufReader::new(File::open(path)?))?;
+            let i = (0..zip.len())
+                .find(|i| zip.name_for_index(*i).is_some_and(|nm| normalize_nm(nm) == inner))
+                .ok_or_else(not_found)?;
+            let file = zip.by_index(i)?;
+            if file.is_dir() {
+                return Err(not_found());
+            }
+            let entry = ArchiveEntry {
+                nm: inner.to_string(),
+                dir: false,
+                sz: file.size(),
+                tm: file.last_modified().map(zip_time_to_sec),
+            };
+            file.take(max_len).read_to_end(&mut buffer)?;
+            Ok((entry, buffer))
+        }
+        ArchiveKind::Tar | ArchiveKind::TarGz => {
+            let mut tar = open_tar(path, kind)?;
+            for entry in tar.entries()? {
+                let entry_file = entry?;
+                let entry = get_tar_entry(&entry_file)?;
+                if entry.nm == inner && !entry.dir {
+                    entry_file.take(max_len).read_to_end(&mut buffer)?;
+                    return Ok((entry, buffer));
+                }
+            }
+            Err(not_found())
+        }
+        ArchiveKind::SevenZ => {
+            let mut reader = SevenZReader::open(path, Password::empty())?;
+            let mut opt_entry: Option<ArchiveEntry> = None;
+            reader.for_each_entries(|file, data| {
+                if opt_entry.is_some() {
+                    return Ok(false);
+                }
+                if normalize_nm(file.name()) != inner || file.is_directory() {
+                    // entries of a block share one stream, so skipped ones must be consumed
+                    std::io::copy(data, &mut std::io::sink())?;
+                    return Ok(true);
+                }
+                data.take(max_len).read_to_end(&mut buffer)?;
+                opt_entry = Some(ArchiveEntry {
+                    nm: inner.to_string(),
+                    dir: false,
+                    sz: file.size(),
+                    tm: get_7z_time(file),
+                });
+                Ok(false)
+            })?;
+            let entry = opt_entry.ok_or_else(not_found)?;
+            Ok((entry, buffer))
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    fn write_zip(path: &Path) {
+        let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+        let opts = SimpleFileOptions::default();
+        zip.start_file("manifest.json", opts).unwrap();
+        zip.write_all(b"{\"version\": 1}").unwrap();
+        zip.start_file("bin/app.exe", opts).unwrap();
+        zip.write_all(b"MZ").unwrap();
+        zip.add_directory("bin/lib/", opts).unwrap();
+        zip.start_file("bin/lib/a.dll", opts).unwrap();
+        zip.write_all(b"MZ..").unwrap();
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn test_zip() {
+        let tmp = temp_dir();
+        let path = tmp.path().join("zip.zip");
+        write_zip(&path);
+        let meta_types = vec![MetaType::Sz];
+
+        let (archive, inner) = split_archive_path(&path.join("bin").join("app.exe")).unwrap();
+        assert_eq!((archive, inner.as_str()), (path.clone(), "bin/app.exe"));
+        let entries = get_archive_entries(&path).unwrap();
+        assert!(is_archive_file(&entries, "bin/app.exe"));
+        assert!(!is_archive_file(&entries, "bin"));
+
+        let items = get_archive_items(&path, &entries, "", &meta_types).unwrap();
+        let nms: Vec<(&str, bool)> = items.iter().map(|item| (item.nm.as_str(), item.dir)).collect();
+        assert_eq!(nms, vec![("manifest.json", false), ("bin", true)]);
+        let items = get_archive_items(&path, &entries, "bin", &meta_types).unwrap();
+        let nms: Vec<(&str, bool, Option<u64>)> = items.iter().map(|item| (item.nm.as_str(), item.dir, item.sz)).collect();
+        assert_eq!(nms, vec![("app.exe", false, Some(2)), ("lib", true, None)]);
+        assert!(get_archive_items(&path, &entries, "nothing", &meta_types).is_err());
+
+        let (entry, buffer) = read_archive_file(&path, "manifest.json", 1024).unwrap();
+        assert_eq!(entry.sz, 14);
+        assert_eq!(buffer, b"{\"version\": 1}");
+        let (_, buffer) = read_archive_file(&path, "bin/lib/a.dll", 2).unwrap();
+        assert_eq!(buffer, b"MZ");
+    }
+
+    #[test]
+    fn test_tar_gz() {
+        let tmp = temp_dir();
+        let path = tmp.path().join("tar.tar.gz");
+        let gz = flate2::write::GzEncoder::new(File::create(&path).unwrap(), flate2::Compression::default());
+        let mut tar = tar::Builder::new(gz);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(5);
+        header.set_mtime(1700000000);
+        header.set_mode(0o644);
+        tar.append_data(&mut header, "./logs/a.log", &b"hello"[..]).unwrap();
+        tar.into_inner().unwrap().finish().unwrap();
+
+        let entries = get_archive_entries(&path).unwrap();
+        let items = get_archive_items(&path, &entries, "logs", &vec![MetaType::Sz, MetaType::Tm]).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!((items[0].nm.as_str(), items[0].sz, items[0].tm), ("a.log", Some(5), Some(1700000000)));
+        let (_, buffer) = read_archive_file(&path, "logs/a.log", 1024).unwrap();
+        assert_eq!(buffer, b"hello");
+    }
+}
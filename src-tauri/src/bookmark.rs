@@ -0,0 +1,227 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use serde::{Deserialize, Serialize};
+use crate::models::{ApiError, Bookmark};
+use crate::settings::write_atomic;
+use crate::system_time_ext::SystemTimeExt;
+
+type Result<T> = std::result::Result<T, ApiError>;
+
+pub const BOOKMARK_VERSION: u64 = 1;
+pub const BOOKMARK_MAX: usize = 1000;
+/// key of the `StateChanged` of the bookmarks, not kept in the settings
+pub const BOOKMARK_KEY: &str = "bookmark.items";
+
+pub fn get_bookmark_path() -> Option<PathBuf> {
+    dirs_next::config_dir().map(|dir| dir.join("tr-viewer").join("bookmarks.json"))
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct BookmarkDoc {
+    version: u64,
+    items: Vec<Bookmark>,
+}
+
+///
+/// bookmarks of a json file or an exported one. a bare array is also read
+pub fn parse_bookmarks(text: &str) -> Result<Vec<Bookmark>> {
+    let items = match serde_json::from_str::<Vec<Bookmark>>(text) {
+        Ok(items) => items,
+        Err(_) => {
+            let doc: BookmarkDoc = serde_json::from_str(text)?;
+            if doc.version > BOOKMARK_VERSION {
+                return Err(ApiError::Bookmark(format!("Err Newer Version: {}", doc.version)));
+            }
+            doc.items
+        }
+    };
+    Ok(items.into_iter().map(to_stored).collect())
+}
+
+///
+/// without the checked fields
+fn to_stored(item: Bookmark) -> Bookmark {
+    Bookmark { dir: None, exists: None, ..item }
+}
+
+pub fn to_bookmarks_json(items: &[Bookmark]) -> Result<Vec<u8>> {
+    let doc = BookmarkDoc {
+        version: BOOKMARK_VERSION,
+        items: items.iter().cloned().map(to_stored).collect(),
+    };
+    Ok(serde_json::to_vec_pretty(&doc)?)
+}
+
+fn get_default_nm(path: &str) -> String {
+    Path::new(path).file_name()
+        .map(|nm| nm.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
+
+///
+/// bookmarks in order, saved on every change. without a path they are kept in memory only
+#[derive(Debug, Default)]
+pub struct Bookmarks {
+    path: Option<PathBuf>,
+    items: Vec<Bookmark>,
+}
+
+impl Bookmarks {
+    ///
+    /// a broken file is kept as `bookmarks.json.bad`
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let items = match path.as_deref().map(std::fs::read_to_string) {
+            Some(Ok(text)) => parse_bookmarks(&text).unwrap_or_else(|_| {
+                if let Some(path) = &path {
+                    let _ = std::fs::rename(path, path.with_extension("json.bad"));
+                }
+                vec![]
+            }),
+            _ => vec![],
+        };
+        Bookmarks { path, items }
+    }
+
+    pub fn get_items(&self) -> Vec<Bookmark> {
+        self.items.clone()
+    }
+
+    fn new_id(&self) -> String {
+        let mut n = SystemTime::now().to_ms();
+        loop {
+            let id = format!("{:x}", n);
+            if !self.items.iter().any(|item| item.id == id) {
+                return id;
+            }
+            n += 1;
+        }
+    }
+
+    ///
+    /// the items are replaced after they are written
+    fn commit(&mut self, items: Vec<Bookmark>) -> Result<()> {
+        if let Some(path) = &self.path {
+            write_atomic(path, &to_bookmarks_json(&items)?)?;
+        }
+        self.items = items;
+        Ok(())
+    }
+
+    fn find(&self, id: &str) -> Result<usize> {
+        self.items.iter().position(|item| item.id == id)
+            .ok_or_else(|| ApiError::Bookmark(format!("Err Not Found: {}", id)))
+    }
+
+    pub fn add(&mut self, path: &str, nm: Option<String>, group: Option<String>) -> Result<Bookmark> {
+        if self.items.len() >= BOOKMARK_MAX {
+            return Err(ApiError::Bookmark(format!("Err Max: {}", BOOKMARK_MAX)));
+        }
+        let item = Bookmark {
+            id: self.new_id(),
+            nm: nm.filter(|nm| !nm.trim().is_empty()).unwrap_or_else(|| get_default_nm(path)),
+            path: path.to_string(),
+            group,
+            ..Bookmark::default()
+        };
+        let mut items = self.items.clone();
+        items.push(item.clone());
+        self.commit(items)?;
+        Ok(item)
+    }
+
+    pub fn remove(&mut self, id: &str) -> Result<Bookmark> {
+        let mut items = self.items.clone();
+        let item = items.remove(self.find(id)?);
+        self.commit(items)?;
+        Ok(item)
+    }
+
+    ///
+    /// changes the bookmark of the id by `f`
+    pub fn update(&mut self, id: &str, f: impl FnOnce(&mut Bookmark)) -> Result<Bookmark> {
+        let idx = self.find(id)?;
+        let mut items = self.items.clone();
+        f(&mut items[idx]);
+        let item = items[idx].clone();
+        self.commit(items)?;
+        Ok(item)
+    }
+
+    ///
+    /// bookmarks of the ids first in the order, the others after them as they were
+    pub fn reorder(&mut self, ids: &[String]) -> Result<Vec<Bookmark>> {
+        let mut rest = self.items.clone();
+        let mut items: Vec<Bookmark> = Vec::with_capacity(rest.len());
+        for id in ids {
+            if let Some(idx) = rest.iter().position(|item| &item.id == id) {
+                items.push(rest.remove(idx));
+            }
+        }
+        items.extend(rest);
+        self.commit(items)?;
+        Ok(self.get_items())
+    }
+
+    ///
+    /// bookmarks of other paths are added, or all are replaced. (added count)
+    pub fn import(&mut self, imported: Vec<Bookmark>, replace: bool) -> Result<usize> {
+        let mut items = if replace { vec![] } else { self.items.clone() };
+        let mut cnt = 0;
+        for mut item in imported {
+            if items.len() >= BOOKMARK_MAX {
+                break;
+            }
+            if items.iter().any(|cur| cur.path == item.path) {
+                continue;
+            }
+            if item.id.is_empty() || items.iter().any(|cur| cur.id == item.id) {
+                item.id = format!("{}-{}", self.new_id(), cnt);
+            }
+            if item.nm.trim().is_empty() {
+                item.nm = get_default_nm(&item.path);
+            }
+            items.push(item);
+            cnt += 1;
+        }
+        self.commit(items)?;
+        Ok(cnt)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn test_bookmarks() {
+        let tmp = temp_dir();
+        let path = tmp.path().join("tr-viewer").join("bookmarks.json");
+
+        let mut bookmarks = Bookmarks::load(Some(path.clone()));
+        let a = bookmarks.add("/data/project", None, None).unwrap();
+        let b = bookmarks.add("/mnt/share", Some(String::from("Share")), Some(String::from("net"))).unwrap();
+        assert_eq!((a.nm.as_str(), b.nm.as_str()), ("project", "Share"));
+        assert_ne!(a.id, b.id);
+        bookmarks.update(&a.id, |item| item.nm = String::from("Project")).unwrap();
+        let ids: Vec<String> = bookmarks.reorder(std::slice::from_ref(&b.id)).unwrap().into_iter().map(|item| item.id).collect();
+        assert_eq!(ids, vec![b.id.clone(), a.id.clone()]);
+        assert!(bookmarks.remove("none").is_err());
+
+        let mut loaded = Bookmarks::load(Some(path.clone()));
+        assert_eq!(loaded.get_items(), bookmarks.get_items());
+        assert_eq!(loaded.get_items()[1].nm, "Project");
+
+        let json = String::from_utf8(to_bookmarks_json(&loaded.get_items()).unwrap()).unwrap();
+        loaded.remove(&b.id).unwrap();
+        assert_eq!(loaded.import(parse_bookmarks(&json).unwrap(), false).unwrap(), 1);
+        assert_eq!(loaded.get_items().len(), 2);
+        let arr = r#"[{"id": "", "nm": "", "path": "/tmp/a"}]"#;
+        assert_eq!(loaded.import(parse_bookmarks(arr).unwrap(), true).unwrap(), 1);
+        let items = loaded.get_items();
+        assert_eq!((items.len(), items[0].nm.as_str()), (1, "a"));
+        assert!(!items[0].id.is_empty());
+        assert!(parse_bookmarks(r#"{"version": 99, "items": []}"#).is_err());
+    }
+}
@@ -0,0 +1,133 @@
+use std::io::{BufRead, Read};
+use bzip2::read::MultiBzDecoder;
+use flate2::read::MultiGzDecoder;
+use liblzma::read::XzDecoder;
+use crate::models::ApiError;
+
+type Result<T> = std::result::Result<T, ApiError>;
+
+// (infer extension, codec, file extension)
+const CODECS: &[(&str, &str, &str)] = &[
+    ("gz", "gzip", ".gz"),
+    ("bz2", "bzip2", ".bz2"),
+    ("xz", "xz", ".xz"),
+    ("zst", "zstd", ".zst"),
+];
+
+///
+/// codec of single-file compressed data, `None` if not compressed
+pub fn get_codec(sample: &[u8]) -> Option<&'static str> {
+    let ext = infer::get(sample)?.extension();
+    CODECS.iter().find(|(infer_ext, _, _)| *infer_ext == ext).map(|(_, codec, _)| *codec)
+}
+
+///
+/// `app.log.gz` -> `app.log`
+pub fn strip_codec_ext<'a>(nm: &'a str, codec: &str) -> &'a str {
+    CODECS.iter()
+        .find(|(_, c, _)| *c == codec)
+        .and_then(|(_, _, ext)| {
+            let idx = nm.len().checked_sub(ext.len())?;
+            nm.get(idx..).filter(|tail| tail.eq_ignore_ascii_case(ext)).map(|_| &nm[..idx])
+        })
+        .unwrap_or(nm)
+}
+
+///
+/// codec of the file extension such as `app.log.gz`
+pub fn get_codec_by_ext(nm: &str) -> Option<&'static str> {
+    CODECS.iter()
+        .find(|(_, _, ext)| {
+            nm.len().checked_sub(ext.len())
+                .and_then(|idx| nm.get(idx..))
+                .is_some_and(|tail| tail.eq_ignore_ascii_case(ext))
+        })
+        .map(|(_, codec, _)| *codec)
+}
+
+fn get_decoder<R: BufRead + 'static>(reader: R, codec: &str) -> Result<Box<dyn Read>> {
+    Ok(match codec {
+        "gzip" => Box::new(MultiGzDecoder::new(reader)),
+        "bzip2" => Box::new(MultiBzDecoder::new(reader)),
+        "xz" => Box::new(XzDecoder::new_multi_decoder(reader)),
+        "zstd" => Box::new(zstd::stream::read::Decoder::with_buffer(reader)?),
+        _ => return Err(ApiError::Compress(format!("Err Codec: {}", codec))),
+    })
+}
+
+///
+/// decompress as a stream. `None` if the result is larger than `max_len`
+pub fn decompress<R: BufRead + 'static>(reader: R, codec: &str, max_len: u64) -> Result<Option<Vec<u8>>> {
+    let decoder = get_decoder(reader, codec)?;
+    let mut buffer: Vec<u8> = vec![];
+    decoder.take(max_len + 1).read_to_end(&mut buffer)
+        .map_err(|e| ApiError::Compress(format!("{}: {}", codec, e)))?;
+    if buffer.len() as u64 > max_len {
+        return Ok(None);
+    }
+    Ok(Some(buffer))
+}
+
+///
+/// head of the decompressed data up to `max_len`. data cut in the middle is decompressed as far as it goes
+pub fn decompress_head<R: BufRead + 'static>(reader: R, codec: &str, max_len: u64) -> Result<Vec<u8>> {
+    let decoder = get_decoder(reader, codec)?;
+    let mut buffer: Vec<u8> = vec![];
+    // bytes read before an error are kept in the buffer
+    if let Err(e) = decoder.take(max_len).read_to_end(&mut buffer) {
+        if buffer.is_empty() {
+            return Err(ApiError::Compress(format!("{}: {}", codec, e)));
+        }
+    }
+    Ok(buffer)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+
+    #[test]
+    fn test_decompress() {
+        let text = b"line 1\nline 2\n".repeat(100);
+        let mut gz = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+        gz.write_all(&text).unwrap();
+        let gz = gz.finish().unwrap();
+        let zst = zstd::encode_all(Cursor::new(&text), 3).unwrap();
+
+        assert_eq!(get_codec(&gz), Some("gzip"));
+        assert_eq!(get_codec(&zst), Some("zstd"));
+        assert_eq!(get_codec(&text), None);
+        assert_eq!(decompress(Cursor::new(gz.clone()), "gzip", 10_000).unwrap(), Some(text.clone()));
+        assert_eq!(decompress(Cursor::new(zst), "zstd", 10_000).unwrap(), Some(text.clone()));
+        assert_eq!(decompress(Cursor::new(gz), "gzip", 100).unwrap(), None);
+        assert!(decompress(Cursor::new(text), "gzip", 10_000).is_err());
+    }
+
+    #[test]
+    fn test_decompress_head() {
+        let text = b"line 1\nline 2\n".repeat(1000);
+        let mut gz = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+        gz.write_all(&text).unwrap();
+        let compressed = gz.finish().unwrap();
+        assert_eq!(decompress_head(Cursor::new(compressed.clone()), "gzip", 100).unwrap(), text[..100].to_vec());
+        let cut = compressed[..compressed.len() / 2].to_vec();
+        let head = decompress_head(Cursor::new(cut), "gzip", text.len() as u64).unwrap();
+        assert!(!head.is_empty() && text.starts_with(&head));
+    }
+
+    #[test]
+    fn test_strip_codec_ext() {
+        assert_eq!(strip_codec_ext("app.log.GZ", "gzip"), "app.log");
+        assert_eq!(strip_codec_ext("app.log.zst", "zstd"), "app.log");
+        assert_eq!(strip_codec_ext("app.log", "xz"), "app.log");
+    }
+
+    #[test]
+    fn test_get_codec_by_ext() {
+        assert_eq!(get_codec_by_ext("app.log.Gz"), Some("gzip"));
+        assert_eq!(get_codec_by_ext("/a/b.tar.zst"), Some("zstd"));
+        assert_eq!(get_codec_by_ext("app.log"), None);
+    }
+}
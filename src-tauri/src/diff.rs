@@ -0,0 +1,198 @@
+use std::borrow::Cow;
+use std::time::{Duration, Instant};
+use similar::{capture_diff_slices_deadline, group_diff_ops, Algorithm, ChangeTag, DiffOp, TextDiff};
+use crate::models::{DiffContent, DiffHunk, DiffKind, DiffLine, DiffOpts, DiffWord};
+
+pub const DIFF_CTX_N: usize = 3;
+/// lines of the hunks, later hunks are cut
+pub const DIFF_LINES_MAX: usize = 100_000;
+/// a larger diff is less minimal rather than slow
+const DIFF_TIMEOUT: Duration = Duration::from_secs(2);
+/// longer lines have no word diff
+const WORDS_LINE_MAX: usize = 2000;
+
+fn strip_eol(line: &str) -> &str {
+    match line.strip_suffix('\n') {
+        Some(line) => line.strip_suffix('\r').unwrap_or(line),
+        None => line,
+    }
+}
+
+///
+/// the line as compared, with the line ending unless ignored
+fn to_key<'a>(line: &'a str, opts: &DiffOpts) -> Cow<'a, str> {
+    if opts.ignore_ws.unwrap_or(false) {
+        Cow::Owned(line.chars().filter(|c| !c.is_whitespace()).collect())
+    } else if opts.ignore_eol.unwrap_or(false) {
+        Cow::Borrowed(strip_eol(line))
+    } else {
+        Cow::Borrowed(line)
+    }
+}
+
+fn push_word(words: &mut Vec<DiffWord>, kind: DiffKind, text: &str) {
+    match words.last_mut() {
+        Some(last) if last.kind == kind => last.text.push_str(text),
+        _ => words.push(DiffWord { kind, text: text.to_string() }),
+    }
+}
+
+///
+/// (words of the old line, words of the new line)
+fn diff_words(old: &str, new: &str) -> (Vec<DiffWord>, Vec<DiffWord>) {
+    let diff = TextDiff::configure().timeout(DIFF_TIMEOUT).diff_words(old, new);
+    let (mut old_words, mut new_words) = (vec![], vec![]);
+    for change in diff.iter_all_changes() {
+        match change.tag() {
+            ChangeTag::Equal => {
+                push_word(&mut old_words, DiffKind::Equal, change.value());
+                push_word(&mut new_words, DiffKind::Equal, change.value());
+            }
+            ChangeTag::Delete => push_word(&mut old_words, DiffKind::Delete, change.value()),
+            ChangeTag::Insert => push_word(&mut new_words, DiffKind::Insert, change.value()),
+        }
+    }
+    (old_words, new_words)
+}
+
+///
+/// `-3,2` of the unified diff, the line before for an empty range
+fn to_range(start: usize, len: usize) -> String {
+    match len {
+        0 => format!("{},0", start),
+        1 => format!("{}", start + 1),
+        len => format!("{},{}", start + 1, len),
+    }
+}
+
+fn push_unified(unified: &mut String, prefix: char, line: &str) {
+    unified.push(prefix);
+    unified.push_str(line);
+    if !line.ends_with('\n') {
+        unified.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+fn to_hunk(ops: &[DiffOp], old_lines: &[&str], new_lines: &[&str], words: bool, unified: &mut String) -> DiffHunk {
+    let (first, last) = (ops[0].as_tag_tuple(), ops[ops.len() - 1].as_tag_tuple());
+    let (old_start, new_start) = (first.1.start, first.2.start);
+    let (old_len, new_len) = (last.1.end - old_start, last.2.end - new_start);
+    unified.push_str(&format!("@@ -{} +{} @@\n", to_range(old_start, old_len), to_range(new_start, new_len)));
+
+    let mut lines: Vec<DiffLine> = vec![];
+    for op in ops {
+        let (_, old_range, new_range) = op.as_tag_tuple();
+        if let DiffOp::Equal { .. } = op {
+            for (i, j) in old_range.zip(new_range) {
+                push_unified(unified, ' ', old_lines[i]);
+                lines.push(DiffLine { kind: DiffKind::Equal, old_no: Some(i + 1), new_no: Some(j + 1), text: strip_eol(old_lines[i]).to_string(), words: None });
+            }
+            continue;
+        }
+        // a replaced line is paired with the inserted line of the same offset for the word diff
+        let pair_n = if words { old_range.len().min(new_range.len()) } else { 0 };
+        let mut new_words: Vec<Option<Vec<DiffWord>>> = vec![None; new_range.len()];
+        for (k, i) in old_range.clone().enumerate() {
+            push_unified(unified, '-', old_lines[i]);
+            let text = strip_eol(old_lines[i]);
+            let mut old_words = None;
+            if k < pair_n {
+                let new_text = strip_eol(new_lines[new_range.start + k]);
+                if text.len() <= WORDS_LINE_MAX && new_text.len() <= WORDS_LINE_MAX {
+                    let (o, n) = diff_words(text, new_text);
+                    (old_words, new_words[k]) = (Some(o), Some(n));
+                }
+            }
+            lines.push(DiffLine { kind: DiffKind::Delete, old_no: Some(i + 1), new_no: None, text: text.to_string(), words: old_words });
+        }
+        for (k, j) in new_range.enumerate() {
+            push_unified(unified, '+', new_lines[j]);
+            lines.push(DiffLine { kind: DiffKind::Insert, old_no: None, new_no: Some(j + 1), text: strip_eol(new_lines[j]).to_string(), words: new_words[k].take() });
+        }
+    }
+    DiffHunk {
+        old_start: if old_len == 0 { old_start } else { old_start + 1 },
+        old_len,
+        new_start: if new_len == 0 { new_start } else { new_start + 1 },
+        new_len,
+        lines,
+    }
+}
+
+///
+/// line diff with the hunks and the unified diff text.
+/// hunks after `DIFF_LINES_MAX` lines are cut and `is_over` is set
+pub fn diff_texts(left: &str, old: &str, right: &str, new: &str, opts: &DiffOpts) -> DiffContent {
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+    let old_keys: Vec<Cow<str>> = old_lines.iter().map(|line| to_key(line, opts)).collect();
+    let new_keys: Vec<Cow<str>> = new_lines.iter().map(|line| to_key(line, opts)).collect();
+    let ops = capture_diff_slices_deadline(Algorithm::Myers, &old_keys, &new_keys, Some(Instant::now() + DIFF_TIMEOUT));
+
+    let (mut add_cnt, mut del_cnt) = (0, 0);
+    for op in ops.iter() {
+        let (_, old_range, new_range) = op.as_tag_tuple();
+        if !matches!(op, DiffOp::Equal { .. }) {
+            del_cnt += old_range.len();
+            add_cnt += new_range.len();
+        }
+    }
+
+    let mut unified = String::new();
+    let mut hunks: Vec<DiffHunk> = vec![];
+    let mut line_n = 0;
+    let mut is_over = false;
+    let groups = group_diff_ops(ops, opts.ctx.unwrap_or(DIFF_CTX_N));
+    if !groups.is_empty() {
+        unified.push_str(&format!("--- {}\n+++ {}\n", left, right));
+    }
+    for group in groups {
+        if line_n >= DIFF_LINES_MAX {
+            is_over = true;
+            break;
+        }
+        let hunk = to_hunk(&group, &old_lines, &new_lines, opts.words.unwrap_or(false), &mut unified);
+        line_n += hunk.lines.len();
+        hunks.push(hunk);
+    }
+
+    DiffContent {
+        left: left.to_string(),
+        right: right.to_string(),
+        hunks,
+        unified,
+        add_cnt,
+        del_cnt,
+        is_over,
+        ..DiffContent::default()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_texts() {
+        let old = "a\nb = 1\nc\nd\n";
+        let new = "a\nb = 2\nc\nd\ne";
+        let opts = DiffOpts { words: Some(true), ..DiffOpts::default() };
+        let content = diff_texts("l.ini", old, "r.ini", new, &opts);
+        assert_eq!((content.add_cnt, content.del_cnt, content.hunks.len()), (2, 1, 1));
+        let hunk = &content.hunks[0];
+        assert_eq!((hunk.old_start, hunk.old_len, hunk.new_start, hunk.new_len), (1, 4, 1, 5));
+        let del = hunk.lines.iter().find(|line| line.kind == DiffKind::Delete).unwrap();
+        assert_eq!((del.old_no, del.text.as_str()), (Some(2), "b = 1"));
+        let words: Vec<(DiffKind, &str)> = del.words.as_ref().unwrap().iter().map(|w| (w.kind, w.text.as_str())).collect();
+        assert_eq!(words, vec![(DiffKind::Equal, "b = "), (DiffKind::Delete, "1")]);
+        assert_eq!(content.unified, "--- l.ini\n+++ r.ini\n@@ -1,4 +1,5 @@\n a\n-b = 1\n+b = 2\n c\n d\n+e\n\\ No newline at end of file\n");
+
+        let content = diff_texts("l", "a\r\nb\r\n", "r", "a\nb", &DiffOpts { ignore_eol: Some(true), ..DiffOpts::default() });
+        assert!(content.hunks.is_empty() && content.unified.is_empty());
+        let content = diff_texts("l", "if (a) {\n", "r", "if(a){\n", &DiffOpts { ignore_ws: Some(true), ..DiffOpts::default() });
+        assert_eq!(content.add_cnt, 0);
+        let content = diff_texts("l", "x\n", "r", "x\ny\n", &DiffOpts { ctx: Some(0), ..DiffOpts::default() });
+        assert_eq!(content.unified, "--- l\n+++ r\n@@ -1,0 +2 @@\n+y\n");
+    }
+}
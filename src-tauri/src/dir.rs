@@ -1,7 +1,7 @@
 use std::cmp::Ordering;
 use crate::models::{Item, MetaType, ApiError, OrderAsc, OrdItem, OrderBy};
 use crate::system_time_ext::SystemTimeExt;
-use std::path::{absolute, PathBuf};
+use std::path::{absolute, Path, PathBuf};
 use windows::{
     core::{
         PCWSTR
@@ -19,6 +19,11 @@ use windows::{
 use std::ffi::OsStr;
 use std::os::windows::ffi::{OsStrExt};
 use mime_guess::from_path;
+use crate::lang::get_lang_by_name;
+use crate::img::{is_img, get_img_meta};
+use crate::pdf::{is_pdf, get_pdf_pages};
+use crate::media::{is_media, get_media_meta};
+use crate::sqlite::{split_db_path, get_db_items};
 use windows::Win32::Storage::FileSystem::FILE_ATTRIBUTE_DIRECTORY;
 use windows::core::Error as WinError;
 
@@ -75,6 +80,16 @@ pub fn get_items_win32(p: &str, meta_types: &Vec<MetaType>) -> Result<Vec<Item>>
     Ok(result)
 }
 
+///
+/// items of the folder or tables of a database (`C:\data\app.db`).
+/// a folder in an archive is listed by `get_archive_items` of the cached entries
+pub fn read_items(p: &str, meta_types: &Vec<MetaType>) -> Result<Vec<Item>> {
+    match split_db_path(Path::new(p)) {
+        Some((db, inner)) => get_db_items(&db, &inner),
+        None => get_items_win32(p, meta_types),
+    }
+}
+
 #[allow(dead_code)]
 fn get_item_win32(p: &str, meta_types: &Vec<MetaType>) -> Result<Option<Item>> {
     let pattern: Vec<u16> = OsStr::new(&format!("{}/*", p))
@@ -268,7 +283,9 @@ fn get_mime_type(nm: &str) -> Option<String> {
     Some(from_path(&nm).first_or_octet_stream().to_string())
 }
 
-pub fn update_items(items: &mut Vec<Item>, meta_types: &Vec<MetaType>) {
+///
+/// meta by the name. the meta read from the file is by `update_items_by_file`
+pub fn update_items(items: &mut [Item], meta_types: &[MetaType]) {
     for item in items.iter_mut() {
         if meta_types.contains(&MetaType::Ext) {
             item.ext = get_ext(&item.nm)
@@ -276,9 +293,40 @@ pub fn update_items(items: &mut Vec<Item>, meta_types: &Vec<MetaType>) {
         if meta_types.contains(&MetaType::Mt) {
             item.mt = get_mime_type(&item.nm);
         }
+        if meta_types.contains(&MetaType::Lang) && !item.dir {
+            item.lang = get_lang_by_name(&item.nm).map(|lang| lang.to_string());
+        }
+    }
+}
+
+///
+/// exif, pdf pages and media tags read from the files, blocking.
+/// only for a page of items or for the fields of the ordering, items having the meta are skipped
+pub fn update_items_by_file(base_dir: &str, items: &mut [Item], meta_types: &[MetaType]) {
+    for item in items.iter_mut().filter(|item| !item.dir) {
+        if meta_types.contains(&MetaType::Img) && item.img.is_none() && is_img(&item.nm) {
+            item.img = get_img_meta(&PathBuf::from(base_dir).join(&item.nm));
+        }
+        if meta_types.contains(&MetaType::Pages) && item.pages.is_none() && is_pdf(&item.nm) {
+            item.pages = get_pdf_pages(&PathBuf::from(base_dir).join(&item.nm));
+        }
+        if meta_types.contains(&MetaType::Media) && item.media.is_none() && is_media(&item.nm) {
+            item.media = get_media_meta(&PathBuf::from(base_dir).join(&item.nm));
+        }
     }
 }
 
+///
+/// meta types read from the files that the ordering compares, of the requested ones
+pub fn get_sort_meta_types(meta_types: &[MetaType], ordering: &[OrdItem]) -> Vec<MetaType> {
+    let needed: Vec<MetaType> = ordering.iter().filter_map(|ord| match ord.nm {
+        OrderBy::Dt => Some(MetaType::Img),
+        OrderBy::Dur | OrderBy::Artist | OrderBy::Album => Some(MetaType::Media),
+        _ => None,
+    }).collect();
+    meta_types.iter().filter(|meta_type| needed.contains(meta_type)).cloned().collect()
+}
+
 
 fn cmp_item<T: Ord>(a: &T, b: &T, asc: &OrderAsc) -> Option<Ordering> {
     if a.ne(&b) {
@@ -297,32 +345,44 @@ fn cmp_str_item(a: &String, b: &String, asc: &OrderAsc) -> Option<Ordering> {
     cmp_item(&a, &b, asc)
 }
 
-fn cmp_opt_str_item(a: &Option<String>, b: &Option<String>, asc: &OrderAsc) -> Option<Ordering> {
+///
+/// `None` is last in both orders, so undecided only if both are `None`
+fn cmp_opt_str_item(a: Option<&str>, b: Option<&str>, asc: &OrderAsc) -> Option<Ordering> {
     match (a, b) {
-        (Some(a), Some(b)) => cmp_str_item(a, b, asc),
-        _ => None
+        (Some(a), Some(b)) => cmp_item(&a.to_lowercase(), &b.to_lowercase(), asc),
+        (Some(_), None) => Some(Ordering::Less),
+        (None, Some(_)) => Some(Ordering::Greater),
+        (None, None) => None,
     }
 }
 
+///
+/// `None` is last in both orders, so undecided only if both are `None`
 fn cmp_opt_item<T: Ord>(a: &Option<T>, b: &Option<T>, asc: &OrderAsc) -> Option<Ordering> {
     match (a, b) {
         (Some(a), Some(b)) => cmp_item(a, b, asc),
-        _ => None
+        (Some(_), None) => Some(Ordering::Less),
+        (None, Some(_)) => Some(Ordering::Greater),
+        (None, None) => None,
     }
 }
 
 
 
-pub fn sort_items(items: &mut Vec<Item>, ordering: &Vec<OrdItem>) {
+pub fn sort_items(items: &mut [Item], ordering: &[OrdItem]) {
     items.sort_by(|a, b| {
         for ord in ordering.iter() {
             let res = match ord.nm {
                 OrderBy::Dir => cmp_item(&b.dir, &a.dir, &ord.asc),
                 OrderBy::Nm => cmp_str_item(&a.nm, &b.nm, &ord.asc),
-                OrderBy::Ext if !a.dir => cmp_opt_str_item(&a.ext, &b.ext, &ord.asc),
-                OrderBy::Mt if !a.dir => cmp_opt_str_item(&a.mt, &b.mt, &ord.asc),
+                OrderBy::Ext if !a.dir => cmp_opt_str_item(a.ext.as_deref(), b.ext.as_deref(), &ord.asc),
+                OrderBy::Mt if !a.dir => cmp_opt_str_item(a.mt.as_deref(), b.mt.as_deref(), &ord.asc),
                 OrderBy::Sz if a.sz.ne(&b.sz)  => cmp_opt_item(&a.sz, &b.sz, &ord.asc),
                 OrderBy::Tm if a.tm.ne(&b.tm)  => cmp_opt_item(&a.tm, &b.tm, &ord.asc),
+                OrderBy::Dt => cmp_opt_item(&a.img.as_ref().and_then(|m| m.dt), &b.img.as_ref().and_then(|m| m.dt), &ord.asc),
+                OrderBy::Dur => cmp_opt_item(&a.media.as_ref().and_then(|m| m.dur_ms), &b.media.as_ref().and_then(|m| m.dur_ms), &ord.asc),
+                OrderBy::Artist => cmp_opt_str_item(a.media.as_ref().and_then(|m| m.artist.as_deref()), b.media.as_ref().and_then(|m| m.artist.as_deref()), &ord.asc),
+                OrderBy::Album => cmp_opt_str_item(a.media.as_ref().and_then(|m| m.album.as_deref()), b.media.as_ref().and_then(|m| m.album.as_deref()), &ord.asc),
                 _ => None,
             };
             if let Some(ord) = res {
@@ -356,6 +416,7 @@ pub fn get_arg_path() -> Option<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::{ImgMeta, MediaMeta};
     #[test]
     fn test_speed() {
         // let base_dir= r"C:\Windows\WinSxS";
@@ -370,6 +431,48 @@ mod tests {
         sort_items(&mut items, &ordering);
     }
 
+    #[test]
+    fn test_get_sort_meta_types() {
+        let meta_types = vec![MetaType::Sz, MetaType::Img, MetaType::Pages, MetaType::Media];
+        let ordering = vec![OrdItem{nm: OrderBy::Dur, asc: OrderAsc::Asc}, OrdItem{nm: OrderBy::Nm, asc: OrderAsc::Asc}];
+        assert_eq!(get_sort_meta_types(&meta_types, &ordering), vec![MetaType::Media]);
+        assert!(get_sort_meta_types(&[MetaType::Sz], &ordering).is_empty());
+    }
+
+    #[test]
+    fn test_sort_items_dt() {
+        let item = |nm: &str, dt: Option<u64>| Item {
+            nm: nm.to_string(),
+            img: Some(ImgMeta { dt, ..ImgMeta::default() }),
+            ..Item::default()
+        };
+        let mut items: Vec<Item> = (0..200).map(|i| item(&format!("{:03}", i), if i % 3 == 0 { None } else { Some(i % 7) })).collect();
+        for asc in [OrderAsc::Asc, OrderAsc::Desc] {
+            sort_items(&mut items, &[OrdItem { nm: OrderBy::Dt, asc: asc.clone() }]);
+            let dts: Vec<Option<u64>> = items.iter().map(|item| item.img.as_ref().and_then(|m| m.dt)).collect();
+            let n = dts.iter().filter(|dt| dt.is_some()).count();
+            assert!(dts[n..].iter().all(|dt| dt.is_none()));
+            assert!(dts[..n].windows(2).all(|w| if asc == OrderAsc::Asc { w[0] <= w[1] } else { w[0] >= w[1] }));
+        }
+    }
+
+    #[test]
+    fn test_sort_items_media() {
+        let item = |nm: &str, artist: Option<&str>, dur_ms: Option<u64>| Item {
+            nm: nm.to_string(),
+            media: Some(MediaMeta { artist: artist.map(String::from), dur_ms, ..MediaMeta::default() }),
+            ..Item::default()
+        };
+        let mut items: Vec<Item> = (0..200).map(|i| {
+            let artist = ["b", "A", "c"].get(i % 5).copied();
+            item(&format!("{:03}", i), artist, if i % 4 == 0 { None } else { Some((i % 9) as u64) })
+        }).collect();
+        sort_items(&mut items, &[OrdItem { nm: OrderBy::Artist, asc: OrderAsc::Desc }, OrdItem { nm: OrderBy::Dur, asc: OrderAsc::Asc }]);
+        let artists: Vec<Option<&str>> = items.iter().map(|item| item.media.as_ref().and_then(|m| m.artist.as_deref())).collect();
+        assert_eq!((artists[0], artists[199]), (Some("c"), None));
+        assert!(artists.windows(2).all(|w| w[1].is_none() || w[0].map(str::to_lowercase) >= w[1].map(str::to_lowercase)));
+    }
+
 
     #[test]
     fn test_has_children_win32() {
@@ -0,0 +1,218 @@
+use std::io::Read;
+use std::io::{Seek, SeekFrom};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use crate::models::{ApiError, HexRow, HexSearch, MagicInfo};
+
+type Result<T> = std::result::Result<T, ApiError>;
+
+pub const HEX_ROW_LEN: usize = 16;
+pub const HEX_MAX_LEN: u64 = 64 * 1024;
+pub const HEX_CHUNK_LEN: usize = 1024 * 1024;
+
+// (signature, offset, description)
+const SIGNATURES: &[(&[u8], usize, &str)] = &[
+    (b"\x89PNG\r\n\x1a\n", 0, "PNG image"),
+    (b"\xff\xd8\xff", 0, "JPEG image"),
+    (b"GIF87a", 0, "GIF image (87a)"),
+    (b"GIF89a", 0, "GIF image (89a)"),
+    (b"BM", 0, "BMP image"),
+    (b"RIFF", 0, "RIFF container"),
+    (b"%PDF-", 0, "PDF document"),
+    (b"PK\x03\x04", 0, "ZIP archive"),
+    (b"PK\x05\x06", 0, "ZIP archive (empty)"),
+    (b"\x1f\x8b", 0, "GZIP compressed"),
+    (b"BZh", 0, "BZIP2 compressed"),
+    (b"\xfd7zXZ\x00", 0, "XZ compressed"),
+    (b"\x28\xb5\x2f\xfd", 0, "Zstandard compressed"),
+    (b"7z\xbc\xaf\x27\x1c", 0, "7-Zip archive"),
+    (b"Rar!\x1a\x07", 0, "RAR archive"),
+    (b"ustar", 257, "TAR archive"),
+    (b"MZ", 0, "DOS/Windows executable"),
+    (b"\x7fELF", 0, "ELF executable"),
+    (b"\xca\xfe\xba\xbe", 0, "Mach-O fat binary / Java class"),
+    (b"\xcf\xfa\xed\xfe", 0, "Mach-O 64-bit executable"),
+    (b"\x00asm", 0, "WebAssembly binary"),
+    (b"SQLite format 3\x00", 0, "SQLite database"),
+    (b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1", 0, "OLE2 compound document"),
+    (b"OggS", 0, "Ogg container"),
+    (b"fLaC", 0, "FLAC audio"),
+    (b"ID3", 0, "MP3 audio (ID3 tag)"),
+    (b"\x1a\x45\xdf\xa3", 0, "Matroska/WebM container"),
+    (b"ftyp", 4, "ISO base media (MP4/MOV/HEIF)"),
+    (b"\xef\xbb\xbf", 0, "UTF-8 text (BOM)"),
+    (b"\xff\xfe", 0, "UTF-16LE text (BOM)"),
+    (b"\xfe\xff", 0, "UTF-16BE text (BOM)"),
+];
+
+pub fn to_rows(buf: &[u8], offset: u64) -> Vec<HexRow> {
+    buf.chunks(HEX_ROW_LEN).enumerate().map(|(i, chunk)| {
+        let hex = chunk.iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<Vec<String>>()
+            .join(" ");
+        let ascii = chunk.iter()
+            .map(|&b| if (0x20..0x7f).contains(&b) { b as char } else { '.' })
+            .collect::<String>();
+        HexRow {
+            offset: offset + (i * HEX_ROW_LEN) as u64,
+            hex,
+            ascii,
+        }
+    }).collect()
+}
+
+pub fn get_magic(sample: &[u8]) -> Option<MagicInfo> {
+    let desc = SIGNATURES.iter()
+        .find(|(sig, pos, _)| sample.len() >= pos + sig.len() && &sample[*pos..pos + sig.len()] == *sig)
+        .map(|(_, _, desc)| desc.to_string());
+    match infer::get(sample) {
+        Some(infer_type) => Some(MagicInfo {
+            mimetype: infer_type.mime_type().to_string(),
+            ext: infer_type.extension().to_string(),
+            kind: format!("{:?}", infer_type.matcher_type()),
+            desc,
+        }),
+        None => desc.map(|desc| MagicInfo {
+            desc: Some(desc),
+            ..MagicInfo::default()
+        }),
+    }
+}
+
+///
+/// parse byte pattern
+///
+/// - `"89 50 4e 47"`, `"89504E47"`, `"0x89 0x50"`: hex bytes
+/// - `"\"IHDR\""`: quoted text is matched as its UTF-8 bytes
+pub fn parse_pattern(pattern: &str) -> Result<Vec<u8>> {
+    let trimmed = pattern.trim();
+    if trimmed.len() >= 2 && trimmed.starts_with('"') && trimmed.ends_with('"') {
+        let text = &trimmed[1..trimmed.len() - 1];
+        if text.is_empty() {
+            return Err(ApiError::Hex(String::from("Err Empty Pattern")));
+        }
+        return Ok(text.as_bytes().to_vec());
+    }
+    let digits: String = trimmed
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .map(|s| s.trim_start_matches("0x").trim_start_matches("0X"))
+        .collect();
+    if digits.is_empty() {
+        return Err(ApiError::Hex(String::from("Err Empty Pattern")));
+    }
+    // `from_str_radix` takes a sign such as `+f`
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) || digits.len() % 2 != 0 {
+        return Err(ApiError::Hex(format!("Err Pattern: {}", pattern)));
+    }
+    (0..digits.len()).step_by(2).map(|i| {
+        u8::from_str_radix(&digits[i..i + 2], 16)
+            .map_err(|_| ApiError::Hex(format!("Err Pattern: {}", pattern)))
+    }).collect()
+}
+
+pub fn find_all(buf: &[u8], needle: &[u8]) -> Vec<usize> {
+    if needle.is_empty() || buf.len() < needle.len() {
+        return vec![];
+    }
+    buf.windows(needle.len())
+        .enumerate()
+        .filter(|(_, w)| *w == needle)
+        .map(|(i, _)| i)
+        .collect()
+}
+
+///
+/// offsets of `needle` from `offset`, read by `HEX_CHUNK_LEN`.
+/// stops after `take_n` matches, and checks `cancel` between chunks
+pub fn search_bytes(path: &Path, needle: &[u8], offset: u64, take_n: Option<usize>, cancel: &AtomicBool) -> Result<HexSearch> {
+    let mut file = std::fs::File::open(path)?;
+    file.seek(SeekFrom::Start(offset))?;
+
+    let mut res = HexSearch::default();
+    let mut buffer: Vec<u8> = Vec::with_capacity(HEX_CHUNK_LEN + needle.len());
+    let mut chunk = vec![0u8; HEX_CHUNK_LEN];
+    let mut base = offset;
+    'read: loop {
+        if cancel.load(Ordering::Relaxed) {
+            res.is_cancelled = true;
+            res.next_offset = Some(base);
+            break;
+        }
+        let n = file.read(&mut chunk)?;
+        if n == 0 {
+            break;
+        }
+        buffer.extend_from_slice(&chunk[..n]);
+        for pos in find_all(&buffer, needle) {
+            if take_n.is_some_and(|take| res.offsets.len() >= take) {
+                res.next_offset = Some(base + pos as u64);
+                break 'read;
+            }
+            res.offsets.push(base + pos as u64);
+        }
+        // keep the tail so that matches across chunk boundaries are found
+        let keep = needle.len().saturating_sub(1).min(buffer.len());
+        let drained = buffer.len() - keep;
+        buffer.drain(..drained);
+        base += drained as u64;
+    }
+    Ok(res)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn test_to_rows() {
+        let buf: Vec<u8> = (0x40u8..0x62).collect();
+        let rows = to_rows(&buf, 0x100);
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[1].offset, 0x110);
+        assert_eq!(rows[0].hex, "40 41 42 43 44 45 46 47 48 49 4a 4b 4c 4d 4e 4f");
+        assert_eq!(rows[0].ascii, "@ABCDEFGHIJKLMNO");
+        assert_eq!(rows[2].ascii, "`a");
+    }
+
+    #[test]
+    fn test_get_magic() {
+        let png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR";
+        let magic = get_magic(png).unwrap();
+        assert_eq!(magic.mimetype, "image/png");
+        assert_eq!(magic.desc, Some(String::from("PNG image")));
+        assert!(get_magic(b"plain text").is_none());
+    }
+
+    #[test]
+    fn test_parse_pattern() {
+        assert_eq!(parse_pattern("89 50 4e 47").unwrap(), vec![0x89, 0x50, 0x4e, 0x47]);
+        assert_eq!(parse_pattern("0x89,0x50").unwrap(), vec![0x89, 0x50]);
+        assert_eq!(parse_pattern("\"IHDR\"").unwrap(), b"IHDR".to_vec());
+        assert!(parse_pattern("895").is_err());
+        assert!(parse_pattern("zz").is_err());
+        assert!(parse_pattern("+f+f").is_err());
+        assert!(parse_pattern("").is_err());
+    }
+
+    #[test]
+    fn test_find_all() {
+        assert_eq!(find_all(b"abcabca", b"ca"), vec![2, 5]);
+        assert_eq!(find_all(b"ab", b"abc"), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_search_bytes() {
+        let tmp = temp_dir();
+        let path = tmp.path().join("search_bytes.bin");
+        std::fs::write(&path, b"..PNG..PNG..").unwrap();
+        let cancel = AtomicBool::new(false);
+        let res = search_bytes(&path, b"PNG", 3, None, &cancel).unwrap();
+        assert_eq!((res.offsets, res.next_offset, res.is_cancelled), (vec![7], None, false));
+        cancel.store(true, Ordering::Relaxed);
+        let res = search_bytes(&path, b"PNG", 3, None, &cancel).unwrap();
+        assert_eq!((res.offsets, res.next_offset, res.is_cancelled), (vec![], Some(3), true));
+    }
+}
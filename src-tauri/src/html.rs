@@ -0,0 +1,255 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use crate::models::RemovedItem;
+
+pub const HTML_MAX_LEN: u64 = 16 * 1024 * 1024;
+
+/// `convertFileSrc(path, 'stream')` of tauri on windows
+pub const STREAM_URL: &str = "http://stream.localhost/";
+
+/// `encodeURIComponent`
+const URI_COMPONENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-').remove(b'_').remove(b'.').remove(b'!').remove(b'~')
+    .remove(b'*').remove(b'\'').remove(b'(').remove(b')');
+
+pub fn to_stream_url(path: &Path) -> String {
+    format!("{}{}", STREAM_URL, utf8_percent_encode(&path.to_string_lossy(), URI_COMPONENT))
+}
+
+fn has_scheme(url: &str) -> bool {
+    // `C:/a.png` is a path
+    match url.split_once(':') {
+        Some((scheme, _)) => scheme.len() > 1
+            && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')),
+        None => false,
+    }
+}
+
+///
+/// `a/../b` -> `b` without touching the file system
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            c => normalized.push(c),
+        }
+    }
+    normalized
+}
+
+///
+/// local file of a relative or absolute reference, `None` for urls, `#id` and `//host`
+///
+/// `img/a%20b.png?v=1` in `C:\doc` -> `C:\doc\img\a b.png`
+pub fn resolve_local_ref(base_dir: &Path, url: &str) -> Option<PathBuf> {
+    let url = url.trim();
+    if url.is_empty() || url.starts_with('#') || url.starts_with("//") || url.starts_with("\\\\") || has_scheme(url) {
+        return None;
+    }
+    let url = url.split(['?', '#']).next().unwrap_or_default();
+    let decoded = percent_encoding::percent_decode_str(url).decode_utf8().ok()?;
+    let path = Path::new(decoded.as_ref());
+    Some(normalize(&if path.is_absolute() { path.to_path_buf() } else { base_dir.join(path) }))
+}
+
+// style properties that can not load anything
+const SAFE_STYLES: &[&str] = &[
+    "color", "background-color", "text-align", "text-decoration", "vertical-align", "white-space",
+    "font-family", "font-size", "font-style", "font-weight", "line-height",
+    "margin", "padding", "border", "width", "height", "display",
+];
+
+// elements whose text is not markup
+const RAW_TEXT_TAGS: &[&str] = &["script", "style", "textarea", "title"];
+
+///
+/// count of start tags and of attributes by name, lowercase. a rough scan only for the report
+fn scan_markup(html: &str) -> (HashMap<String, usize>, HashMap<String, usize>) {
+    let html = html.to_ascii_lowercase();
+    let mut tags: HashMap<String, usize> = HashMap::new();
+    let mut attrs: HashMap<String, usize> = HashMap::new();
+    let skip_to = |from: usize, pat: &str| html[from..].find(pat).map(|idx| from + idx + pat.len()).unwrap_or(html.len());
+    let mut i = 0;
+    while let Some(idx) = html[i..].find('<') {
+        i += idx;
+        let rest = &html[i..];
+        if rest.starts_with("<!--") {
+            i = skip_to(i, "-->");
+            continue;
+        }
+        if rest.starts_with("<!") || rest.starts_with("<?") || rest.starts_with("</") {
+            i = skip_to(i, ">");
+            continue;
+        }
+        let nm_len = rest[1..].find(|c: char| !(c.is_ascii_alphanumeric() || c == '-')).unwrap_or(rest.len() - 1);
+        if nm_len == 0 {
+            i += 1;
+            continue;
+        }
+        let nm = &rest[1..1 + nm_len];
+        *tags.entry(nm.to_string()).or_default() += 1;
+
+        let bytes = html.as_bytes();
+        let mut j = i + 1 + nm_len;
+        loop {
+            while j < bytes.len() && (bytes[j].is_ascii_whitespace() || bytes[j] == b'/') {
+                j += 1;
+            }
+            if j >= bytes.len() || bytes[j] == b'>' {
+                break;
+            }
+            let attr_start = j;
+            while j < bytes.len() && !bytes[j].is_ascii_whitespace() && !matches!(bytes[j], b'=' | b'>' | b'/') {
+                j += 1;
+            }
+            *attrs.entry(html[attr_start..j].to_string()).or_default() += 1;
+            while j < bytes.len() && bytes[j].is_ascii_whitespace() {
+                j += 1;
+            }
+            if j < bytes.len() && bytes[j] == b'=' {
+                j += 1;
+                while j < bytes.len() && bytes[j].is_ascii_whitespace() {
+                    j += 1;
+                }
+                match bytes.get(j) {
+                    Some(&q) if q == b'"' || q == b'\'' => {
+                        j = html[j + 1..].find(q as char).map(|idx| j + 1 + idx + 1).unwrap_or(bytes.len());
+                    }
+                    _ => {
+                        while j < bytes.len() && !bytes[j].is_ascii_whitespace() && bytes[j] != b'>' {
+                            j += 1;
+                        }
+                    }
+                }
+            }
+        }
+        i = (j + 1).min(html.len());
+        if RAW_TEXT_TAGS.contains(&nm) {
+            let nm = nm.to_string();
+            i = html[i..].find(&format!("</{}", nm)).map(|idx| i + idx).unwrap_or(html.len());
+        }
+    }
+    (tags, attrs)
+}
+
+///
+/// counts of the input not in the output, by name
+fn get_removed(input: &HashMap<String, usize>, output: &HashMap<String, usize>) -> Vec<RemovedItem> {
+    let mut removed: Vec<RemovedItem> = input.iter()
+        .filter_map(|(nm, cnt)| {
+            let cnt = cnt.saturating_sub(output.get(nm).copied().unwrap_or(0));
+            (cnt > 0).then(|| RemovedItem { nm: nm.clone(), cnt })
+        })
+        .collect();
+    removed.sort_by(|a, b| a.nm.cmp(&b.nm));
+    removed
+}
+
+///
+/// sanitized html and what was removed
+///
+/// - removed_tags, removed_attrs: scripts, event handlers, forms, frames, `javascript:` urls, ...
+/// - remote_urls: images from other hosts, never loaded
+/// - local_paths: files of the rewritten images, to be opened for the stream protocol
+pub struct SafeHtml {
+    pub html: String,
+    pub removed_tags: Vec<RemovedItem>,
+    pub removed_attrs: Vec<RemovedItem>,
+    pub remote_urls: Vec<String>,
+    pub local_paths: Vec<PathBuf>,
+}
+
+///
+/// whitelist of ammonia, and `style` with properties that load nothing.
+/// local images are rewritten to stream urls of `base_dir`, remote images are removed
+///
+/// - base_dir: folder of the file. `None` keeps relative images as they are
+pub fn sanitize_html(html: &str, opt_base_dir: Option<PathBuf>) -> SafeHtml {
+    let remote_urls: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(vec![]));
+    let filter_remote_urls = remote_urls.clone();
+    let local_paths: Arc<Mutex<Vec<PathBuf>>> = Arc::new(Mutex::new(vec![]));
+    let filter_local_paths = local_paths.clone();
+    let mut builder = ammonia::Builder::default();
+    builder
+        .add_generic_attributes(["style"])
+        .filter_style_properties(SAFE_STYLES.iter().copied().collect::<HashSet<_>>())
+        .attribute_filter(move |elem, attr, value| {
+            if elem != "img" || attr != "src" {
+                return Some(Cow::Borrowed(value));
+            }
+            let trimmed = value.trim();
+            if trimmed.starts_with("//") || has_scheme(trimmed) {
+                if let Ok(mut urls) = filter_remote_urls.lock() {
+                    urls.push(value.to_string());
+                }
+                return None;
+            }
+            match opt_base_dir.as_ref().and_then(|base_dir| resolve_local_ref(base_dir, value)) {
+                Some(path) => {
+                    let url = to_stream_url(&path);
+                    if let Ok(mut paths) = filter_local_paths.lock() {
+                        paths.push(path);
+                    }
+                    Some(Cow::Owned(url))
+                }
+                None => Some(Cow::Borrowed(value)),
+            }
+        });
+    let safe = builder.clean(html).to_string();
+
+    let (tags_in, attrs_in) = scan_markup(html);
+    let (tags_out, attrs_out) = scan_markup(&safe);
+    let remote_urls = remote_urls.lock().map(|urls| urls.clone()).unwrap_or_default();
+    let local_paths = local_paths.lock().map(|paths| paths.clone()).unwrap_or_default();
+    SafeHtml {
+        html: safe,
+        removed_tags: get_removed(&tags_in, &tags_out),
+        removed_attrs: get_removed(&attrs_in, &attrs_out),
+        remote_urls,
+        local_paths,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_html() {
+        let html = "<html><head><script src=\"x.js\"></script><style>body{}</style></head><body>\
+            <p onclick=\"alert(1)\" style=\"color: red; background-image: url(http://x.org/t)\">Hi</p>\
+            <img src=\"img/a.png\"><img src=\"https://x.org/t.gif\"><a href=\"javascript:alert(1)\">x</a>\
+            <iframe src=\"https://x.org\"></iframe><script>document.write('<b onload=x>')</script></body></html>";
+        let safe = sanitize_html(html, Some(PathBuf::from("/doc")));
+        assert!(!safe.html.contains("script") && !safe.html.contains("onclick") && !safe.html.contains("iframe"));
+        assert!(safe.html.contains("<p style=\"color:red\">Hi</p>"));
+        assert!(safe.html.contains(&format!("src=\"{}\"", to_stream_url(Path::new("/doc/img/a.png")))));
+        assert_eq!(safe.local_paths, vec![PathBuf::from("/doc/img/a.png")]);
+        assert_eq!(safe.remote_urls, vec!["https://x.org/t.gif"]);
+        let cnt = |items: &[RemovedItem], nm: &str| items.iter().find(|item| item.nm == nm).map(|item| item.cnt);
+        assert_eq!(cnt(&safe.removed_tags, "script"), Some(2));
+        assert_eq!(cnt(&safe.removed_tags, "iframe"), Some(1));
+        assert_eq!(cnt(&safe.removed_tags, "b"), None);
+        assert_eq!(cnt(&safe.removed_attrs, "onclick"), Some(1));
+        assert_eq!(cnt(&safe.removed_attrs, "href"), Some(1));
+    }
+
+    #[test]
+    fn test_resolve_local_ref() {
+        let base_dir = Path::new("/doc/sub");
+        assert_eq!(resolve_local_ref(base_dir, "../img/a.png?v=1"), Some(PathBuf::from("/doc/img/a.png")));
+        assert_eq!(resolve_local_ref(base_dir, "https://x.org/a.png"), None);
+        assert_eq!(resolve_local_ref(base_dir, "data:image/png;base64,AA"), None);
+        assert_eq!(resolve_local_ref(base_dir, "#top"), None);
+        assert_eq!(to_stream_url(Path::new(r"C:\a b.png")), "http://stream.localhost/C%3A%5Ca%20b.png");
+    }
+}
@@ -0,0 +1,191 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use image::{AnimationDecoder, ImageDecoder, ImageFormat, ImageReader};
+use image::codecs::gif::GifDecoder;
+use image::codecs::png::PngDecoder;
+use image::codecs::webp::WebPDecoder;
+use exif::{DateTime, Exif, In, Tag, Value};
+use crate::models::{ApiError, ExifInfo, ExifTag, ImgInfo, ImgMeta};
+use crate::system_time_ext::civil_to_sec;
+
+type Result<T> = std::result::Result<T, ApiError>;
+
+pub fn is_img(nm: &str) -> bool {
+    ImageFormat::from_path(nm).is_ok()
+}
+
+///
+/// image info with frame count and exif
+pub fn get_img_info(path: &Path) -> Result<ImgInfo> {
+    let reader = ImageReader::open(path)?.with_guessed_format()?;
+    let format = reader.format()
+        .ok_or_else(|| ApiError::Img(format!("Err Format: {:?}", path)))?;
+    let decoder = reader.into_decoder()?;
+    let (w, h) = decoder.dimensions();
+    let color = format!("{:?}", decoder.original_color_type());
+    drop(decoder);
+
+    Ok(ImgInfo {
+        path: path.to_string_lossy().to_string(),
+        format: format!("{:?}", format),
+        mimetype: format.to_mime_type().to_string(),
+        w,
+        h,
+        color,
+        frames: get_frame_count(path, format),
+        exif: get_exif_info(path),
+    })
+}
+
+///
+/// dimensions from the header and capture date, for listing
+pub fn get_img_meta(path: &Path) -> Option<ImgMeta> {
+    let (w, h) = ImageReader::open(path).ok()?
+        .with_guessed_format().ok()?
+        .into_dimensions().ok()?;
+    let dt = read_exif(path).and_then(|exif| get_dt(&exif)).map(|dt| to_sec(&dt));
+    Some(ImgMeta { w, h, dt })
+}
+
+fn get_frame_count(path: &Path, format: ImageFormat) -> Option<usize> {
+    let reader = BufReader::new(File::open(path).ok()?);
+    match format {
+        ImageFormat::Gif => Some(GifDecoder::new(reader).ok()?.into_frames().count()),
+        ImageFormat::Png => {
+            let decoder = PngDecoder::new(reader).ok()?;
+            if decoder.is_apng().ok()? {
+                Some(decoder.apng().ok()?.into_frames().count())
+            } else {
+                Some(1)
+            }
+        }
+        ImageFormat::WebP => {
+            let decoder = WebPDecoder::new(reader).ok()?;
+            if decoder.has_animation() {
+                Some(decoder.into_frames().count())
+            } else {
+                Some(1)
+            }
+        }
+        _ => Some(1),
+    }
+}
+
+fn read_exif(path: &Path) -> Option<Exif> {
+    let mut reader = BufReader::new(File::open(path).ok()?);
+    exif::Reader::new().read_from_container(&mut reader).ok()
+}
+
+pub fn get_exif_orientation(path: &Path) -> Option<u32> {
+    read_exif(path)?.get_field(Tag::Orientation, In::PRIMARY)?.value.get_uint(0)
+}
+
+pub fn get_exif_info(path: &Path) -> Option<ExifInfo> {
+    let exif = read_exif(path)?;
+    let dt = get_dt(&exif);
+    let lat = get_gps(&exif, Tag::GPSLatitude, Tag::GPSLatitudeRef, b'S');
+    let lon = get_gps(&exif, Tag::GPSLongitude, Tag::GPSLongitudeRef, b'W');
+    let alt = get_rational(&exif, Tag::GPSAltitude).map(|alt| {
+        match exif.get_field(Tag::GPSAltitudeRef, In::PRIMARY).and_then(|f| f.value.get_uint(0)) {
+            Some(1) => -alt,
+            _ => alt,
+        }
+    });
+    let tags = exif.fields().map(|field| ExifTag {
+        ifd: field.ifd_num.0,
+        tag: field.tag.to_string(),
+        val: field.display_value().with_unit(&exif).to_string(),
+    }).collect();
+
+    Some(ExifInfo {
+        make: get_ascii(&exif, Tag::Make),
+        model: get_ascii(&exif, Tag::Model),
+        lens: get_ascii(&exif, Tag::LensModel),
+        dt: dt.as_ref().map(|dt| dt.to_string()),
+        dt_sec: dt.as_ref().map(to_sec),
+        orientation: exif.get_field(Tag::Orientation, In::PRIMARY).and_then(|f| f.value.get_uint(0)),
+        exposure: exif.get_field(Tag::ExposureTime, In::PRIMARY).map(|f| f.display_value().to_string()),
+        f_number: get_rational(&exif, Tag::FNumber),
+        iso: exif.get_field(Tag::PhotographicSensitivity, In::PRIMARY).and_then(|f| f.value.get_uint(0)),
+        focal_len: get_rational(&exif, Tag::FocalLength),
+        lat,
+        lon,
+        alt,
+        tags,
+    })
+}
+
+fn get_ascii(exif: &Exif, tag: Tag) -> Option<String> {
+    match &exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Ascii(values) => values.first()
+            .map(|v| String::from_utf8_lossy(v).trim().to_string())
+            .filter(|s| !s.is_empty()),
+        _ => None,
+    }
+}
+
+fn get_rational(exif: &Exif, tag: Tag) -> Option<f64> {
+    match &exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Rational(values) if !values.is_empty() && values[0].denom != 0 => Some(values[0].to_f64()),
+        _ => None,
+    }
+}
+
+fn get_gps(exif: &Exif, tag: Tag, ref_tag: Tag, negative: u8) -> Option<f64> {
+    let dms = match &exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Rational(values) if values.len() >= 3 && values.iter().all(|v| v.denom != 0) => {
+            values[0].to_f64() + values[1].to_f64() / 60.0 + values[2].to_f64() / 3600.0
+        }
+        _ => return None,
+    };
+    match &exif.get_field(ref_tag, In::PRIMARY)?.value {
+        Value::Ascii(values) if values.first().and_then(|v| v.first()) == Some(&negative) => Some(-dms),
+        _ => Some(dms),
+    }
+}
+
+fn get_dt(exif: &Exif) -> Option<DateTime> {
+    [Tag::DateTimeOriginal, Tag::DateTimeDigitized, Tag::DateTime].iter()
+        .filter_map(|tag| exif.get_field(*tag, In::PRIMARY))
+        .find_map(|field| match &field.value {
+            Value::Ascii(values) => values.first().and_then(|v| DateTime::from_ascii(v).ok()),
+            _ => None,
+        })
+}
+
+///
+/// exif date time to unix time. local time without offset is taken as UTC.
+fn to_sec(dt: &DateTime) -> u64 {
+    let sec = civil_to_sec(dt.year as i64, dt.month as i64, dt.day as i64,
+                           dt.hour as i64, dt.minute as i64, dt.second as i64)
+        - dt.offset.unwrap_or(0) as i64 * 60;
+    sec.max(0) as u64
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn test_to_sec() {
+        let dt = DateTime::from_ascii(b"2024:02:29 12:34:56").unwrap();
+        assert_eq!(to_sec(&dt), 1709210096);
+        let dt = DateTime::from_ascii(b"1970:01:01 00:00:00").unwrap();
+        assert_eq!(to_sec(&dt), 0);
+    }
+
+    #[test]
+    fn test_get_img_info() {
+        let tmp = temp_dir();
+        let path = tmp.path().join("get_img_info.png");
+        image::RgbImage::new(3, 2).save(&path).unwrap();
+        let info = get_img_info(&path).unwrap();
+        assert_eq!((info.w, info.h, info.frames), (3, 2, Some(1)));
+        assert_eq!(info.mimetype, "image/png");
+        assert!(info.exif.is_none());
+        assert_eq!(get_img_meta(&path).map(|meta| (meta.w, meta.h)), Some((3, 2)));
+    }
+}
@@ -0,0 +1,194 @@
+use std::path::Path;
+
+pub const LANG_DEFAULT: &str = "plaintext";
+
+// language ids follow monaco-editor. languages monaco does not have are mapped to the closest one.
+const FILE_NAMES: &[(&str, &str)] = &[
+    ("dockerfile", "dockerfile"),
+    ("containerfile", "dockerfile"),
+    ("makefile", "shell"),
+    ("gnumakefile", "shell"),
+    ("cmakelists.txt", "shell"),
+    ("jenkinsfile", "java"),
+    ("vagrantfile", "ruby"),
+    ("gemfile", "ruby"),
+    ("rakefile", "ruby"),
+    ("podfile", "ruby"),
+    ("procfile", "yaml"),
+    ("cargo.lock", "ini"),
+    ("pipfile", "ini"),
+    (".bashrc", "shell"),
+    (".bash_profile", "shell"),
+    (".zshrc", "shell"),
+    (".profile", "shell"),
+    (".gitconfig", "ini"),
+    (".editorconfig", "ini"),
+    (".gitignore", "plaintext"),
+    (".env", "ini"),
+    (".babelrc", "json"),
+    (".eslintrc", "json"),
+    (".prettierrc", "json"),
+];
+
+const EXTENSIONS: &[(&str, &str)] = &[
+    ("txt", "plaintext"), ("log", "plaintext"),
+    ("js", "javascript"), ("mjs", "javascript"), ("cjs", "javascript"), ("jsx", "javascript"),
+    ("ts", "typescript"), ("mts", "typescript"), ("cts", "typescript"), ("tsx", "typescript"),
+    ("json", "json"), ("jsonc", "json"), ("json5", "json"), ("webmanifest", "json"),
+    ("html", "html"), ("htm", "html"), ("xhtml", "html"),
+    ("xml", "xml"), ("xsd", "xml"), ("xsl", "xml"), ("svg", "xml"), ("csproj", "xml"), ("plist", "xml"),
+    ("css", "css"), ("scss", "scss"), ("less", "less"),
+    ("md", "markdown"), ("markdown", "markdown"), ("mdx", "mdx"), ("rst", "restructuredtext"),
+    ("yaml", "yaml"), ("yml", "yaml"),
+    ("toml", "ini"), ("ini", "ini"), ("cfg", "ini"), ("conf", "ini"), ("properties", "ini"),
+    ("py", "python"), ("pyw", "python"), ("pyi", "python"),
+    ("rs", "rust"), ("go", "go"), ("java", "java"), ("kt", "kotlin"), ("kts", "kotlin"),
+    ("scala", "scala"), ("swift", "swift"), ("dart", "dart"),
+    ("c", "cpp"), ("h", "cpp"), ("cc", "cpp"), ("cpp", "cpp"), ("cxx", "cpp"), ("hpp", "cpp"), ("hh", "cpp"),
+    ("cs", "csharp"), ("fs", "fsharp"), ("vb", "vb"),
+    ("m", "objective-c"), ("mm", "objective-c"),
+    ("sh", "shell"), ("bash", "shell"), ("zsh", "shell"), ("ksh", "shell"),
+    ("ps1", "powershell"), ("psm1", "powershell"), ("psd1", "powershell"),
+    ("bat", "bat"), ("cmd", "bat"),
+    ("rb", "ruby"), ("php", "php"), ("pl", "perl"), ("pm", "perl"), ("lua", "lua"), ("r", "r"),
+    ("sql", "sql"), ("graphql", "graphql"), ("gql", "graphql"), ("proto", "protobuf"),
+    ("dockerfile", "dockerfile"), ("tf", "hcl"), ("hcl", "hcl"),
+    ("ex", "elixir"), ("exs", "elixir"), ("clj", "clojure"), ("coffee", "coffeescript"),
+    ("pas", "pascal"), ("jl", "julia"), ("tcl", "tcl"), ("sol", "solidity"), ("wgsl", "wgsl"),
+    ("hbs", "handlebars"), ("twig", "twig"), ("pug", "pug"), ("cshtml", "razor"),
+];
+
+// shebang interpreters, modeline names
+const ALIASES: &[(&str, &str)] = &[
+    ("python", "python"), ("python2", "python"), ("python3", "python"),
+    ("node", "javascript"), ("nodejs", "javascript"), ("deno", "typescript"), ("ts-node", "typescript"),
+    ("sh", "shell"), ("bash", "shell"), ("zsh", "shell"), ("ksh", "shell"), ("dash", "shell"),
+    ("make", "shell"),
+    ("pwsh", "powershell"), ("powershell", "powershell"),
+    ("perl", "perl"), ("ruby", "ruby"), ("php", "php"), ("lua", "lua"), ("rscript", "r"),
+    ("javascript", "javascript"), ("js", "javascript"), ("typescript", "typescript"),
+    ("c++", "cpp"), ("cpp", "cpp"), ("c", "cpp"), ("rust", "rust"), ("go", "go"), ("java", "java"),
+    ("yaml", "yaml"), ("json", "json"), ("xml", "xml"), ("html", "html"), ("markdown", "markdown"),
+    ("dosini", "ini"), ("conf", "ini"), ("toml", "ini"), ("sql", "sql"),
+];
+
+fn find(table: &[(&str, &'static str)], key: &str) -> Option<&'static str> {
+    table.iter().find(|(k, _)| *k == key).map(|(_, lang)| *lang)
+}
+
+///
+/// language by file name, then by extension
+pub fn get_lang_by_name(nm: &str) -> Option<&'static str> {
+    let nm = Path::new(nm).file_name()?.to_string_lossy().to_lowercase();
+    if let Some(lang) = find(FILE_NAMES, &nm) {
+        return Some(lang);
+    }
+    if nm.starts_with("dockerfile.") || nm.ends_with(".dockerfile") {
+        return Some("dockerfile");
+    }
+    let (_, ext) = nm.rsplit_once('.')?;
+    find(EXTENSIONS, ext)
+}
+
+fn get_lang_by_alias(nm: &str) -> Option<&'static str> {
+    let nm = nm.trim().to_lowercase();
+    find(ALIASES, &nm).or_else(|| find(EXTENSIONS, &nm))
+}
+
+///
+/// `#!/usr/bin/env python3`, `#!/bin/bash -e`
+pub fn get_lang_by_shebang(line: &str) -> Option<&'static str> {
+    let cmd = line.strip_prefix("#!")?;
+    let mut words = cmd.split_whitespace();
+    let mut prog = words.next()?.rsplit('/').next()?;
+    if prog == "env" {
+        prog = words.find(|w| !w.starts_with('-'))?;
+    }
+    // python3.11 -> python3
+    let prog = prog.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    get_lang_by_alias(prog).or_else(|| get_lang_by_alias(&format!("{}3", prog)))
+}
+
+///
+/// vim `vim: set ft=python:`, `vi: filetype=sh`, emacs `-*- mode: python -*-`, `-*- python -*-`
+pub fn get_lang_by_modeline(line: &str) -> Option<&'static str> {
+    if let Some(start) = line.find("-*-") {
+        let rest = &line[start + 3..];
+        let end = rest.find("-*-")?;
+        let vars = &rest[..end];
+        if !vars.contains(':') {
+            return get_lang_by_alias(vars);
+        }
+        return vars.split(';')
+            .filter_map(|var| var.split_once(':'))
+            .find(|(k, _)| k.trim().eq_ignore_ascii_case("mode"))
+            .and_then(|(_, v)| get_lang_by_alias(v));
+    }
+    let idx = ["vim:", "vi:", "ex:"].iter().find_map(|tag| line.find(tag).map(|i| i + tag.len()))?;
+    line[idx..]
+        .split(|c: char| c == ':' || c.is_whitespace())
+        .filter_map(|opt| opt.split_once('='))
+        .find(|(k, _)| *k == "ft" || *k == "filetype" || *k == "syntax")
+        .and_then(|(_, v)| get_lang_by_alias(v))
+}
+
+pub fn get_lang_by_content(text: &str) -> Option<&'static str> {
+    let head = text.trim_start_matches('\u{feff}').trim_start();
+    let head_lower = head.chars().take(64).collect::<String>().to_lowercase();
+    if head_lower.starts_with("<?xml") {
+        Some("xml")
+    } else if head_lower.starts_with("<!doctype html") || head_lower.starts_with("<html") {
+        Some("html")
+    } else if head_lower.starts_with("<?php") {
+        Some("php")
+    } else if (head.starts_with('{') || head.starts_with('['))
+        && serde_json::from_str::<serde_json::Value>(head).is_ok() {
+        Some("json")
+    } else if head.starts_with("---\n") || head.starts_with("---\r\n") || head.starts_with("%YAML") {
+        Some("yaml")
+    } else {
+        None
+    }
+}
+
+///
+/// detect language of text
+///
+/// modeline > file name > extension > shebang > content
+pub fn detect_lang(nm: &str, text: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let modeline_lines = lines.iter().take(5).chain(lines.iter().rev().take(5));
+    modeline_lines.filter_map(|line| get_lang_by_modeline(line)).next()
+        .or_else(|| get_lang_by_name(nm).filter(|lang| *lang != LANG_DEFAULT))
+        .or_else(|| lines.first().and_then(|line| get_lang_by_shebang(line)))
+        .or_else(|| get_lang_by_content(text))
+        .unwrap_or(LANG_DEFAULT)
+        .to_string()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_lang_by_name() {
+        assert_eq!(get_lang_by_name("src/Dockerfile"), Some("dockerfile"));
+        assert_eq!(get_lang_by_name("Makefile"), Some("shell"));
+        assert_eq!(get_lang_by_name("main.RS"), Some("rust"));
+        assert_eq!(get_lang_by_name("a.unknown"), None);
+        assert_eq!(get_lang_by_name("README"), None);
+    }
+
+    #[test]
+    fn test_detect_lang() {
+        assert_eq!(detect_lang("run", "#!/usr/bin/env python3.11\nprint(1)"), "python");
+        assert_eq!(detect_lang("run", "#!/bin/bash -e\nls"), "shell");
+        assert_eq!(detect_lang("a.txt", "# -*- mode: yaml -*-\na: 1"), "yaml");
+        assert_eq!(detect_lang("a.conf", "x\n# vim: set ft=sh ts=4:"), "shell");
+        assert_eq!(detect_lang("a.cfg", "a=1"), "ini");
+        assert_eq!(detect_lang("data", "  {\"a\": [1, 2]}"), "json");
+        assert_eq!(detect_lang("page", "<!DOCTYPE html><html></html>"), "html");
+        assert_eq!(detect_lang("notes", "hello"), "plaintext");
+    }
+}
@@ -2,24 +2,61 @@
 // use specta::Type;
 
 
-use tauri_specta::{collect_commands, Builder};
+use tauri::{Emitter, EventTarget, RunEvent, WindowEvent};
+use tauri_specta::{collect_commands, collect_events, Builder, Event};
+use tokio::sync::broadcast::error::RecvError;
 mod api;
+mod archive;
+mod bookmark;
+mod compress;
+mod diff;
 mod dir;
+mod hex;
+mod html;
+mod img;
+mod lang;
+mod markdown;
+mod media;
+mod text;
 mod models;
+mod office;
 mod path_ext;
+mod pdf;
+mod policy;
+mod protocol;
+mod search;
+mod settings;
+mod sqlite;
+mod stream;
+mod structured;
 mod system_time_ext;
+mod table;
+mod thumb;
+#[cfg(test)]
+mod test_util;
 
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use serde_json::Value;
 // use serde::{Serialize, Deserialize};
 use crate::api::get_instance;
-use crate::models::{OrdItem, OrderAsc, OrderBy, MetaType, OptParams, Params, ApiError, TextContent, Folder, HomeType, DiskInfo};
+use crate::thumb::{get_thumb_dir, prune_thumb_cache, THUMB_CACHE_MAX_AGE, THUMB_CACHE_MAX_LEN, THUMB_SCHEME};
+use crate::stream::STREAM_SCHEME;
+use crate::protocol::{get_stream_response, get_thumb_response};
+use crate::models::{OrdItem, OrderAsc, OrderBy, MetaType, OptParams, Params, ApiError, TextContent, Folder, HomeType, DiskInfo, HexContent, HexSearch, WriteOpts, WriteInfo, ImgInfo, StructContent, TableOpts, TableContent, MarkdownContent, HtmlContent, DbContent, OfficeContent, PdfContent, MediaInfo, DiffOpts, DiffContent, SearchOpts, TextSearch, AccessPolicy, StateChanged, Bookmark};
 
 
+///
+/// read text
+///
+/// # arg
+/// - path_str
+/// - enc: encoding label (`"cp949"`, `"Shift_JIS"`, ...). If `None` then the remembered encoding of the file or detected one.
+///   The given encoding is remembered for the file.
 #[tauri::command]
 #[specta::specta]
-async fn read_text(path_str: String) -> Result<TextContent, ApiError> {
-    get_instance().read_txt(&path_str).await
+async fn read_text(path_str: String, enc: Option<String>) -> Result<TextContent, ApiError> {
+    get_instance().read_txt(&path_str, enc).await
     // Ok(TextContent{
     //     path: "abc".to_string(),
     //     mimetype: "".to_string(),
@@ -28,6 +65,228 @@ async fn read_text(path_str: String) -> Result<TextContent, ApiError> {
     // })
 }
 
+///
+/// write text
+///
+/// writes a temp file and renames it over the original. files in an archive and compressed files are read only.
+///
+/// # arg
+/// - path_str
+/// - text
+/// - opts: pass `enc`, `bom`, `eol`, `sz`, `tm_ms` of `read_text` to keep the encoding and line endings and to detect conflicts
+#[tauri::command]
+#[specta::specta]
+async fn write_text(path_str: String, text: String, opts: WriteOpts) -> Result<WriteInfo, ApiError> {
+    get_instance().write_txt(&path_str, &text, opts).await
+}
+
+///
+/// set remembered encoding of the file
+///
+/// # arg
+/// - path_str
+/// - enc: encoding label. if `None` then forget and detect again. kept in the settings by `text.encodings`
+#[tauri::command]
+#[specta::specta]
+async fn set_encoding(path_str: String, enc: Option<String>) -> Result<Option<String>, ApiError> {
+    get_instance().set_encoding(&path_str, enc).await
+}
+
+#[tauri::command]
+#[specta::specta]
+fn list_encodings() -> Vec<String> {
+    text::list_encodings()
+}
+
+///
+/// image info
+///
+/// dimensions, color type, frame count of animated images and exif
+#[tauri::command]
+#[specta::specta]
+async fn image_info(path_str: String) -> Result<ImgInfo, ApiError> {
+    get_instance().image_info(&path_str).await
+}
+
+///
+/// read JSON, YAML, TOML or XML as a tree
+///
+/// returns the node at `path_expr` and its children. the parsed document is cached,
+/// so call again with the `path` of a child to expand it.
+///
+/// # arg
+/// - path_str
+/// - path_expr: json pointer (`/servers/0`). If `None` then the root
+/// - skip_n, take_n: paging of the children (default 1000)
+#[tauri::command]
+#[specta::specta]
+async fn read_structured(path_str: String, path_expr: Option<String>, skip_n: Option<usize>, take_n: Option<usize>) -> Result<StructContent, ApiError> {
+    get_instance().read_structured(&path_str, path_expr, skip_n, take_n).await
+}
+
+///
+/// read a page of CSV/TSV rows
+///
+/// delimiter, header and encoding are sniffed unless given. row offsets, and row orders
+/// of sorts and filters are cached per file.
+///
+/// # arg
+/// - path_str
+/// - opts: `sorts`, `filters`, `skip_n`, `take_n` (default 100, max 1000)
+#[tauri::command]
+#[specta::specta]
+async fn read_table(path_str: String, opts: TableOpts) -> Result<TableContent, ApiError> {
+    get_instance().read_table(&path_str, opts).await
+}
+
+///
+/// read a page of rows of a table or view in a SQLite database
+///
+/// the database is opened read only. tables are listed by `read_folder` of the database file.
+///
+/// # arg
+/// - path_str: path of the table (`C:\data\app.db\users`)
+/// - opts: `sorts`, `filters`, `skip_n`, `take_n` (default 100, max 1000). others are ignored
+#[tauri::command]
+#[specta::specta]
+async fn read_db(path_str: String, opts: TableOpts) -> Result<DbContent, ApiError> {
+    get_instance().read_db(&path_str, opts).await
+}
+
+///
+/// render markdown to sanitized html with heading ids and a table of contents
+///
+/// relative images are rewritten to stream urls of the folder of the file.
+///
+/// # arg
+/// - path_str
+#[tauri::command]
+#[specta::specta]
+async fn render_markdown(path_str: String) -> Result<MarkdownContent, ApiError> {
+    get_instance().render_markdown(&path_str).await
+}
+
+///
+/// read a html file sanitized for the preview
+///
+/// scripts, event handlers and remote images are removed by a whitelist, local images
+/// are rewritten to stream urls. what was removed is counted by name.
+///
+/// # arg
+/// - path_str
+#[tauri::command]
+#[specta::specta]
+async fn read_html_safe(path_str: String) -> Result<HtmlContent, ApiError> {
+    get_instance().read_html_safe(&path_str).await
+}
+
+///
+/// extract text and properties of docx, xlsx, pptx, odt, ods and odp
+///
+/// paragraphs of documents, cell values of sheets (up to 1000 rows each) or paragraphs of slides.
+///
+/// # arg
+/// - path_str
+#[tauri::command]
+#[specta::specta]
+async fn read_office(path_str: String) -> Result<OfficeContent, ApiError> {
+    get_instance().read_office(&path_str).await
+}
+
+///
+/// read page count, document info, outline and text of pages of a pdf
+///
+/// # arg
+/// - path_str
+/// - pages: 1-based page numbers to extract text (max 100). If `None` then the first 10 pages
+#[tauri::command]
+#[specta::specta]
+async fn read_pdf(path_str: String, pages: Option<Vec<u32>>) -> Result<PdfContent, ApiError> {
+    get_instance().read_pdf(&path_str, pages).await
+}
+
+///
+/// read duration, tracks, tags and cover art of an audio or video file
+///
+/// the cover art is served by `thumb://localhost/{path}`
+///
+/// # arg
+/// - path_str
+#[tauri::command]
+#[specta::specta]
+async fn media_info(path_str: String) -> Result<MediaInfo, ApiError> {
+    get_instance().media_info(&path_str).await
+}
+
+///
+/// line and word diff of two text files, decoded like `read_text`
+///
+/// a file larger than 5MB is cut to its head and `is_over` is set
+///
+/// # arg
+/// - left, right: paths of the old and the new file
+/// - opts: ignore whitespace or line endings, context lines and word diff
+#[tauri::command]
+#[specta::specta]
+async fn diff_files(left: String, right: String, opts: DiffOpts) -> Result<DiffContent, ApiError> {
+    get_instance().diff_files(&left, &right, opts).await
+}
+
+///
+/// read hex dump
+///
+/// # arg
+/// - path_str
+/// - offset: start byte offset
+/// - len: byte length (max 64KB)
+#[tauri::command]
+#[specta::specta]
+async fn read_hex(path_str: String, offset: u64, len: u64) -> Result<HexContent, ApiError> {
+    get_instance().read_hex(&path_str, offset, len).await
+}
+
+///
+/// search byte pattern
+///
+/// # arg
+/// - path_str
+/// - pattern: hex bytes `"89 50 4e 47"` or quoted text `"\"IHDR\""`
+/// - offset: start byte offset
+/// - take_n: max match count. `next_offset` is set if more matches exist
+/// - id: to cancel by `cancel_search`
+#[tauri::command]
+#[specta::specta]
+async fn search_hex(path_str: String, pattern: String, offset: u64, take_n: Option<usize>, id: Option<String>) -> Result<HexSearch, ApiError> {
+    get_instance().search_hex(&path_str, &pattern, offset, take_n, id).await
+}
+
+///
+/// regex search of a text file of any size, streamed in its encoding
+///
+/// returns up to `take_n` matches (default 100, max 1000). pass `next_offset` and `next_line`
+/// as `offset` and `line` for the next page
+///
+/// # arg
+/// - path_str
+/// - pattern: regex, or plain text if `literal`
+/// - opts: encoding, case, whole word, context lines, start of the page and the id to cancel
+#[tauri::command]
+#[specta::specta]
+async fn search_in_file(path_str: String, pattern: String, opts: SearchOpts) -> Result<TextSearch, ApiError> {
+    get_instance().search_in_file(&path_str, &pattern, opts).await
+}
+
+///
+/// cancel a running `search_in_file` of `opts.id` or `search_hex` of `id`
+///
+/// # arg
+/// - id
+#[tauri::command]
+#[specta::specta]
+async fn cancel_search(id: String) -> bool {
+    get_instance().cancel_search(&id).await
+}
+
 #[tauri::command]
 #[specta::specta]
 async fn read_folder(params: OptParams) -> Result<Folder, ApiError> {
@@ -46,29 +305,54 @@ async fn read_folder(params: OptParams) -> Result<Folder, ApiError> {
 ///
 /// set state
 ///
+/// saved to `settings.json` in the config dir
+///
 /// # arg
-/// - key
-/// - opt_val: if `None` then delete cache
+/// - key: `namespace.name` such as `folder.listOrder`
+/// - val: json value. if `None` then delete it
 ///
 /// # Examples
 /// ```
 /// ```
 #[tauri::command]
 #[specta::specta]
-async fn set_state(key: String, val: Option<String>) -> Result<Option<String>, ApiError> {
-    get_instance().set_state(key, val).await
+async fn set_state(window: tauri::Window, key: String, val: Option<Value>) -> Result<Option<Value>, ApiError> {
+    get_instance().set_state(key, val, Some(window.label().to_string())).await
 }
 
 ///
 /// get state
 ///
 /// # arg
-/// - key
-/// - default_val: If the key does not exist in the cache, insert the default value and return it.
+/// - key: `namespace.name`
+/// - default_val: If the key does not exist, save the default value and return it.
+#[tauri::command]
+#[specta::specta]
+async fn get_state(window: tauri::Window, key: String, default_val: Option<Value>) -> Result<Option<Value>, ApiError> {
+    get_instance().get_state(&key, default_val, Some(window.label().to_string())).await
+}
+
+///
+/// states of the keys starting with the prefix
+///
+/// # arg
+/// - prefix: `"folder."` for a namespace
 #[tauri::command]
 #[specta::specta]
-async fn get_state(key: String, default_val: Option<String>) -> Result<Option<String>, ApiError> {
-    get_instance().get_state(&key, default_val).await
+async fn list_state(prefix: String) -> Result<BTreeMap<String, Value>, ApiError> {
+    get_instance().list_state(&prefix).await
+}
+
+///
+/// `StateChanged` events of the window are filtered by the prefix.
+/// Listen with the window (`events.stateChanged(getCurrentWebviewWindow())`), a global listener gets all of them.
+///
+/// # arg
+/// - prefix: `"folder."`. If `None` then clear the prefixes of the window
+#[tauri::command]
+#[specta::specta]
+async fn subscribe_state(window: tauri::Window, prefix: Option<String>) -> Result<Vec<String>, ApiError> {
+    get_instance().subscribe_state(window.label().to_string(), prefix).await
 }
 
 #[tauri::command]
@@ -89,18 +373,139 @@ async fn get_arg_path() -> Result<Option<String>, ApiError> {
     get_instance().get_arg_path().await
 }
 
+///
+/// access policy of the paths, read once at the start. A path outside of it is a `Policy` error
+#[tauri::command]
+#[specta::specta]
+async fn get_policy() -> Result<AccessPolicy, ApiError> {
+    get_instance().get_policy().await
+}
+
+///
+/// bookmarks in order, `dir` and `exists` are checked now
+/// changes of the bookmarks are notified as `StateChanged` of the key `bookmark.items`
+#[tauri::command]
+#[specta::specta]
+async fn get_bookmarks() -> Result<Vec<Bookmark>, ApiError> {
+    get_instance().get_bookmarks().await
+}
+
+///
+/// add a bookmark at the end
+///
+/// # arg
+/// - path_str: folder or file
+/// - nm: If `None` then the file name
+/// - group: If `None` then no group
+#[tauri::command]
+#[specta::specta]
+async fn add_bookmark(path_str: String, nm: Option<String>, group: Option<String>) -> Result<Bookmark, ApiError> {
+    get_instance().add_bookmark(&path_str, nm, group).await
+}
+
+///
+/// remove a bookmark
+///
+/// # arg
+/// - id
+#[tauri::command]
+#[specta::specta]
+async fn remove_bookmark(id: String) -> Result<Bookmark, ApiError> {
+    get_instance().remove_bookmark(&id).await
+}
+
+///
+/// rename a bookmark
+///
+/// # arg
+/// - id
+/// - nm
+#[tauri::command]
+#[specta::specta]
+async fn rename_bookmark(id: String, nm: String) -> Result<Bookmark, ApiError> {
+    get_instance().rename_bookmark(&id, nm).await
+}
+
+///
+/// move a bookmark to a group
+///
+/// # arg
+/// - id
+/// - group: If `None` then out of its group
+#[tauri::command]
+#[specta::specta]
+async fn set_bookmark_group(id: String, group: Option<String>) -> Result<Bookmark, ApiError> {
+    get_instance().set_bookmark_group(&id, group).await
+}
+
+///
+/// reorder bookmarks
+///
+/// # arg
+/// - ids: bookmarks in the new order, the others follow as they were
+#[tauri::command]
+#[specta::specta]
+async fn reorder_bookmarks(ids: Vec<String>) -> Result<Vec<Bookmark>, ApiError> {
+    get_instance().reorder_bookmarks(ids).await
+}
+
+///
+/// write bookmarks to a json file and return the count
+///
+/// # arg
+/// - path_str
+#[tauri::command]
+#[specta::specta]
+async fn export_bookmarks(path_str: String) -> Result<usize, ApiError> {
+    get_instance().export_bookmarks(&path_str).await
+}
+
+///
+/// read bookmarks of a json file and return the count added. Paths already bookmarked are skipped.
+///
+/// # arg
+/// - path_str
+/// - replace: remove the current bookmarks first
+#[tauri::command]
+#[specta::specta]
+async fn import_bookmarks(path_str: String, replace: bool) -> Result<usize, ApiError> {
+    get_instance().import_bookmarks(&path_str, replace).await
+}
+
 #[tauri::command]
 #[specta::specta]
 fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
+///
+/// state changes of the api as `StateChanged` events, except for the windows of other prefixes
+async fn emit_state_changes(handle: tauri::AppHandle) {
+    let mut rx = get_instance().watch_state();
+    loop {
+        let changed = match rx.recv().await {
+            Ok(changed) => changed,
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => break,
+        };
+        let excluded = get_instance().get_state_excluded(&changed.key).await;
+        let _ = handle.emit_filter(StateChanged::NAME, changed, |target| match target {
+            EventTarget::AnyLabel { label }
+            | EventTarget::Window { label }
+            | EventTarget::Webview { label }
+            | EventTarget::WebviewWindow { label } => !excluded.contains(label),
+            _ => true,
+        });
+    }
+}
+
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
 
     let builder = Builder::<tauri::Wry>::new()
-        .commands(collect_commands![greet, read_text, read_folder, set_state, get_state, get_home_dir, get_disks, get_arg_path]);
+        .commands(collect_commands![greet, read_text, write_text, set_encoding, list_encodings, image_info, read_structured, read_table, read_db, render_markdown, read_html_safe, read_office, read_pdf, media_info, diff_files, read_hex, search_hex, search_in_file, cancel_search, read_folder, set_state, get_state, list_state, subscribe_state, get_home_dir, get_disks, get_arg_path, get_policy, get_bookmarks, add_bookmark, remove_bookmark, rename_bookmark, set_bookmark_group, reorder_bookmarks, export_bookmarks, import_bookmarks])
+        .events(collect_events![StateChanged]);
 
     #[cfg(debug_assertions)] // <- Only export on non-release builds
     {
@@ -116,10 +521,30 @@ pub fn run() {
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .register_asynchronous_uri_scheme_protocol(THUMB_SCHEME, |_ctx, request, responder| {
+            tauri::async_runtime::spawn(async move {
+                responder.respond(get_thumb_response(request).await);
+            });
+        })
+        .register_asynchronous_uri_scheme_protocol(STREAM_SCHEME, |_ctx, request, responder| {
+            tauri::async_runtime::spawn(async move {
+                responder.respond(get_stream_response(request).await);
+            });
+        })
         // .invoke_handler(tauri::generate_handler![greet, read_text, read_folder, set_state, get_state, get_home_dir, get_disks])
         .invoke_handler(builder.invoke_handler())
+        .on_window_event(|window, event| {
+            if let WindowEvent::Destroyed = event {
+                let label = window.label().to_string();
+                tauri::async_runtime::spawn(async move {
+                    let _ = get_instance().subscribe_state(label, None).await;
+                });
+            }
+        })
         .setup(move |app| {
             builder.mount_events(app);
+            tauri::async_runtime::spawn(emit_state_changes(app.handle().clone()));
+            tauri::async_runtime::spawn_blocking(|| prune_thumb_cache(&get_thumb_dir(), THUMB_CACHE_MAX_LEN, THUMB_CACHE_MAX_AGE));
             // match app.get_window("main") {
             //     Some(window) => {
             //         match window.get_webview("main") {
@@ -134,6 +559,11 @@ pub fn run() {
             // }
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_app, event| {
+            if let RunEvent::Exit = event {
+                let _ = tauri::async_runtime::block_on(get_instance().flush_settings());
+            }
+        });
 }
@@ -0,0 +1,152 @@
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag, TagEnd};
+use crate::models::TocItem;
+use crate::html::{resolve_local_ref, to_stream_url};
+
+pub const MD_MAX_LEN: u64 = 16 * 1024 * 1024;
+
+///
+/// github style `Hello, World!` -> `hello-world`
+fn slugify(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+///
+/// `intro`, `intro-1`, `intro-2`
+fn unique_id(slug: String, used: &mut HashSet<String>) -> String {
+    let slug = if slug.is_empty() { String::from("section") } else { slug };
+    let mut id = slug.clone();
+    let mut n = 0;
+    while used.contains(&id) {
+        n += 1;
+        id = format!("{}-{}", slug, n);
+    }
+    used.insert(id.clone());
+    id
+}
+
+///
+/// sets the id of headings, `{#id}` of the heading is kept
+fn set_heading_ids(events: &mut [Event]) -> Vec<TocItem> {
+    let mut toc: Vec<TocItem> = vec![];
+    let mut used: HashSet<String> = HashSet::new();
+    let mut i = 0;
+    while i < events.len() {
+        if let Event::Start(Tag::Heading { level, id, .. }) = &events[i] {
+            let (lvl, opt_id) = (*level as u8, id.as_ref().map(|id| id.to_string()));
+            let mut text = String::new();
+            let mut j = i + 1;
+            while j < events.len() && !matches!(events[j], Event::End(TagEnd::Heading(_))) {
+                if let Event::Text(s) | Event::Code(s) = &events[j] {
+                    text.push_str(s);
+                }
+                j += 1;
+            }
+            let new_id = unique_id(opt_id.unwrap_or_else(|| slugify(&text)), &mut used);
+            if let Event::Start(Tag::Heading { id, .. }) = &mut events[i] {
+                *id = Some(CowStr::from(new_id.clone()));
+            }
+            toc.push(TocItem { lvl, id: new_id, text });
+            i = j;
+        }
+        i += 1;
+    }
+    toc
+}
+
+///
+/// whitelist of ammonia with ids of headings, task list checkboxes and table alignment.
+/// relative images are rewritten to stream urls of `base_dir`. (html, files of the images)
+fn sanitize(html: &str, opt_base_dir: Option<PathBuf>) -> (String, Vec<PathBuf>) {
+    let mut builder = ammonia::Builder::default();
+    builder
+        .add_tags(["input"])
+        .set_tag_attribute_value("input", "type", "checkbox")
+        .set_tag_attribute_value("input", "disabled", "")
+        .add_tag_attributes("input", ["checked"])
+        .add_tag_attributes("th", ["style"])
+        .add_tag_attributes("td", ["style"])
+        .filter_style_properties(["text-align"].into())
+        .add_tag_attributes("code", ["class"])
+        .add_tag_attributes("div", ["id", "class"])
+        .add_tag_attributes("sup", ["class"]);
+    for tag in ["h1", "h2", "h3", "h4", "h5", "h6"] {
+        builder.add_tag_attributes(tag, ["id"]);
+    }
+    let local_paths: Arc<Mutex<Vec<PathBuf>>> = Arc::new(Mutex::new(vec![]));
+    if let Some(base_dir) = opt_base_dir {
+        let filter_local_paths = local_paths.clone();
+        builder.attribute_filter(move |elem, attr, value| {
+            if elem == "img" && attr == "src" {
+                if let Some(path) = resolve_local_ref(&base_dir, value) {
+                    let url = to_stream_url(&path);
+                    if let Ok(mut paths) = filter_local_paths.lock() {
+                        paths.push(path);
+                    }
+                    return Some(Cow::Owned(url));
+                }
+            }
+            Some(Cow::Borrowed(value))
+        });
+    }
+    let safe = builder.clean(html).to_string();
+    let local_paths = local_paths.lock().map(|paths| paths.clone()).unwrap_or_default();
+    (safe, local_paths)
+}
+
+///
+/// CommonMark with GFM tables, task lists, strikethrough and footnotes to sanitized html.
+/// front matter is not rendered. (html, toc, files of the images)
+///
+/// - base_dir: folder of the file. `None` keeps relative images as they are
+pub fn render_markdown(text: &str, opt_base_dir: Option<PathBuf>) -> (String, Vec<TocItem>, Vec<PathBuf>) {
+    let opts = Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_HEADING_ATTRIBUTES
+        | Options::ENABLE_YAML_STYLE_METADATA_BLOCKS
+        | Options::ENABLE_GFM;
+    let mut events: Vec<Event> = Parser::new_ext(text, opts).collect();
+    let toc = set_heading_ids(&mut events);
+    let mut unsafe_html = String::with_capacity(text.len() * 3 / 2);
+    html::push_html(&mut unsafe_html, events.into_iter());
+    let (html, local_paths) = sanitize(&unsafe_html, opt_base_dir);
+    (html, toc, local_paths)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_render_markdown() {
+        let md = "---\ntitle: x\n---\n# Hello, World!\n## Intro\n## Intro\n## Usage {#use}\n\
+            ![a](img/a%20b.png) ![b](https://x.org/b.png)\n\n- [x] done\n\n\
+            <script>alert(1)</script><img src=\"c.png\" onerror=\"alert(1)\">\n";
+        let (html, toc, local_paths) = render_markdown(md, Some(PathBuf::from("/doc")));
+        assert_eq!(toc.iter().map(|t| t.id.as_str()).collect::<Vec<_>>(), vec!["hello-world", "intro", "intro-1", "use"]);
+        assert_eq!((toc[0].lvl, toc[0].text.as_str()), (1, "Hello, World!"));
+        assert!(html.contains("<h2 id=\"intro-1\">"));
+        assert!(!html.contains("title: x"));
+        assert!(html.contains(&format!("src=\"{}", to_stream_url(Path::new("/doc/img/a b.png")))));
+        assert!(html.contains("src=\"https://x.org/b.png\""));
+        assert!(html.contains(&format!("src=\"{}", to_stream_url(Path::new("/doc/c.png")))));
+        assert_eq!(local_paths, vec![PathBuf::from("/doc/img/a b.png"), PathBuf::from("/doc/c.png")]);
+        assert!(html.contains("type=\"checkbox\""));
+        assert!(!html.contains("script") && !html.contains("onerror"));
+    }
+}
@@ -0,0 +1,567 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use symphonia::core::codecs::{CODEC_TYPE_NULL, CODEC_TYPE_OPUS};
+use symphonia::core::formats::{FormatOptions, Track};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::{MetadataOptions, MetadataRevision, StandardTagKey, StandardVisualKey, Tag, Visual};
+use symphonia::core::probe::Hint;
+use symphonia::default::{get_codecs, get_probe};
+use crate::models::{ApiError, MediaCover, MediaInfo, MediaMeta, MediaTag, MediaTrack, TrackKind};
+
+type Result<T> = std::result::Result<T, ApiError>;
+
+const MEDIA_EXTS: &[&str] = &[
+    "mp3", "flac", "ogg", "oga", "opus", "wav", "aif", "aiff", "caf",
+    "m4a", "m4b", "mp4", "m4v", "mov", "mkv", "mka", "webm",
+];
+/// larger `moov` or `Tracks` are not read for the video tracks
+const HEADER_MAX_LEN: u64 = 64 * 1024 * 1024;
+
+fn get_ext(path: &Path) -> String {
+    path.extension().map(|ext| ext.to_string_lossy().to_lowercase()).unwrap_or_default()
+}
+
+pub fn is_media(nm: &str) -> bool {
+    MEDIA_EXTS.contains(&get_ext(Path::new(nm)).as_str())
+}
+
+fn get_format(ext: &str) -> String {
+    match ext {
+        "m4a" | "m4b" | "m4v" | "mov" => "mp4",
+        "mka" => "mkv",
+        "oga" | "opus" => "ogg",
+        "aif" => "aiff",
+        ext => ext,
+    }.to_string()
+}
+
+///
+/// tracks and the latest metadata of the probe and of the container
+struct Probed {
+    tracks: Vec<Track>,
+    tags: Vec<Tag>,
+    visuals: Vec<Visual>,
+}
+
+fn push_rev(rev: Option<&MetadataRevision>, tags: &mut Vec<Tag>, visuals: &mut Vec<Visual>) {
+    if let Some(rev) = rev {
+        tags.extend(rev.tags().iter().cloned());
+        visuals.extend(rev.visuals().iter().cloned());
+    }
+}
+
+fn probe(path: &Path) -> Result<Probed> {
+    let mss = MediaSourceStream::new(Box::new(File::open(path)?), Default::default());
+    let mut hint = Hint::new();
+    hint.with_extension(&get_ext(path));
+    let mut probed = get_probe().format(&hint, mss, &FormatOptions::default(), &MetadataOptions::default())?;
+    let mut tags: Vec<Tag> = vec![];
+    let mut visuals: Vec<Visual> = vec![];
+    // ID3v2 in front of the container is read by the probe
+    if let Some(mut meta) = probed.metadata.get() {
+        push_rev(meta.skip_to_latest(), &mut tags, &mut visuals);
+    }
+    push_rev(probed.format.metadata().skip_to_latest(), &mut tags, &mut visuals);
+    Ok(Probed {
+        tracks: probed.format.tracks().to_vec(),
+        tags,
+        visuals,
+    })
+}
+
+fn to_ms(secs: u64, frac: f64) -> u64 {
+    secs * 1000 + (frac * 1000.0).round() as u64
+}
+
+fn get_codec_nm(track: &Track) -> Option<String> {
+    match track.codec_params.codec {
+        CODEC_TYPE_NULL => None,
+        CODEC_TYPE_OPUS => Some(String::from("opus")),
+        codec => Some(get_codecs().get_codec(codec)
+            .map(|desc| desc.short_name.to_string())
+            .unwrap_or_else(|| codec.to_string())),
+    }
+}
+
+///
+/// video tracks are not parsed by symphonia, they have no codec and no sample rate
+fn to_audio_track(track: &Track) -> Option<MediaTrack> {
+    let params = &track.codec_params;
+    if params.codec == CODEC_TYPE_NULL && params.sample_rate.is_none() {
+        return None;
+    }
+    let dur_ms = match (params.time_base, params.n_frames) {
+        (Some(tb), Some(n)) => {
+            let t = tb.calc_time(n);
+            Some(to_ms(t.seconds, t.frac))
+        }
+        (None, Some(n)) => params.sample_rate.filter(|r| *r > 0).map(|r| n * 1000 / r as u64),
+        _ => None,
+    };
+    Some(MediaTrack {
+        kind: TrackKind::Audio,
+        codec: get_codec_nm(track),
+        sample_rate: params.sample_rate,
+        channels: params.channels.map(|c| c.count() as u32),
+        bits: params.bits_per_sample,
+        dur_ms,
+        lang: track.language.clone().filter(|lang| lang != "und"),
+        ..MediaTrack::default()
+    })
+}
+
+fn get_tag_key(tag: &Tag) -> String {
+    tag.std_key.map(|key| format!("{:?}", key)).unwrap_or_else(|| tag.key.clone())
+}
+
+///
+/// RIFF INFO values are terminated by NUL
+fn get_tag_val(tag: &Tag) -> String {
+    tag.value.to_string().trim_matches(|c: char| c == '\0' || c.is_whitespace()).to_string()
+}
+
+fn get_std_tag(tags: &[Tag], key: StandardTagKey) -> Option<String> {
+    tags.iter()
+        .find(|tag| tag.std_key == Some(key))
+        .map(get_tag_val)
+        .filter(|val| !val.is_empty())
+}
+
+fn to_media_meta(tags: &[Tag], dur_ms: Option<u64>) -> MediaMeta {
+    MediaMeta {
+        dur_ms,
+        title: get_std_tag(tags, StandardTagKey::TrackTitle),
+        artist: get_std_tag(tags, StandardTagKey::Artist).or_else(|| get_std_tag(tags, StandardTagKey::AlbumArtist)),
+        album: get_std_tag(tags, StandardTagKey::Album),
+    }
+}
+
+///
+/// front cover, the first picture if there is no front cover
+fn get_cover_visual(visuals: &[Visual]) -> Option<&Visual> {
+    visuals.iter()
+        .find(|v| v.usage == Some(StandardVisualKey::FrontCover))
+        .or_else(|| visuals.first())
+}
+
+fn video_codec_nm(id: &str) -> String {
+    match id {
+        "avc1" | "avc3" | "V_MPEG4/ISO/AVC" => "h264",
+        "hvc1" | "hev1" | "V_MPEGH/ISO/HEVC" => "hevc",
+        "av01" | "V_AV1" => "av1",
+        "vp09" | "V_VP9" => "vp9",
+        "vp08" | "V_VP8" => "vp8",
+        "mp4v" | "V_MPEG4/ISO/ASP" => "mpeg4",
+        id => return id.trim_start_matches("V_").trim().to_lowercase(),
+    }.to_string()
+}
+
+fn be_uint(buf: &[u8]) -> u64 {
+    buf.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64)
+}
+
+///
+/// (type, payload) of the boxes in `buf`
+fn mp4_boxes(buf: &[u8]) -> Vec<(&[u8], &[u8])> {
+    let mut boxes = vec![];
+    let mut pos = 0usize;
+    while pos + 8 <= buf.len() {
+        let (size, kind) = (be_uint(&buf[pos..pos + 4]) as usize, &buf[pos + 4..pos + 8]);
+        let (hdr_len, size) = match size {
+            0 => (8, buf.len() - pos),
+            1 if pos + 16 <= buf.len() => (16, be_uint(&buf[pos + 8..pos + 16]) as usize),
+            size => (8, size),
+        };
+        if size < hdr_len || size > buf.len() - pos {
+            break;
+        }
+        boxes.push((kind, &buf[pos + hdr_len..pos + size]));
+        pos += size;
+    }
+    boxes
+}
+
+fn mp4_child<'a>(buf: &'a [u8], path: &[&[u8]]) -> Option<&'a [u8]> {
+    path.iter().try_fold(buf, |buf, kind| {
+        mp4_boxes(buf).into_iter().find(|(k, _)| k == kind).map(|(_, payload)| payload)
+    })
+}
+
+///
+/// duration of `mvhd` or `mdhd` in milliseconds
+fn mp4_hd_dur(payload: &[u8]) -> Option<u64> {
+    let (scale, dur) = match payload.first()? {
+        1 => (be_uint(payload.get(20..24)?), be_uint(payload.get(24..32)?)),
+        _ => (be_uint(payload.get(12..16)?), be_uint(payload.get(16..20)?)),
+    };
+    (scale > 0 && dur != u32::MAX as u64 && dur != u64::MAX).then(|| dur.saturating_mul(1000) / scale)
+}
+
+fn read_mp4_moov(file: &mut File) -> Option<Vec<u8>> {
+    let len = file.metadata().ok()?.len();
+    let mut pos = 0u64;
+    while pos + 8 <= len {
+        file.seek(SeekFrom::Start(pos)).ok()?;
+        let mut hdr = [0u8; 16];
+        file.read_exact(&mut hdr[..8]).ok()?;
+        let (mut size, mut hdr_len) = (be_uint(&hdr[..4]), 8);
+        if size == 1 {
+            file.read_exact(&mut hdr[8..]).ok()?;
+            (size, hdr_len) = (be_uint(&hdr[8..]), 16);
+        } else if size == 0 {
+            size = len - pos;
+        }
+        if size < hdr_len || size > len - pos {
+            return None;
+        }
+        if &hdr[4..8] == b"moov" {
+            if size > HEADER_MAX_LEN {
+                return None;
+            }
+            let mut moov = vec![0u8; (size - hdr_len) as usize];
+            file.read_exact(&mut moov).ok()?;
+            return Some(moov);
+        }
+        pos += size;
+    }
+    None
+}
+
+///
+/// (duration, video tracks) of mp4 / mov from `moov`
+fn read_mp4_video(file: &mut File) -> (Option<u64>, Vec<MediaTrack>) {
+    let Some(moov) = read_mp4_moov(file) else {
+        return (None, vec![]);
+    };
+    let tracks = mp4_boxes(&moov).into_iter()
+        .filter(|(kind, _)| *kind == b"trak")
+        .filter(|(_, trak)| mp4_child(trak, &[b"mdia", b"hdlr"]).and_then(|hdlr| hdlr.get(8..12)) == Some(b"vide"))
+        .map(|(_, trak)| {
+            // width and height in 16.16 fixed point at the end of `tkhd`
+            let opt_wh = mp4_child(trak, &[b"tkhd"])
+                .filter(|tkhd| tkhd.len() >= 84)
+                .map(|tkhd| &tkhd[tkhd.len() - 8..])
+                .map(|wh| ((be_uint(&wh[..4]) >> 16) as u32, (be_uint(&wh[4..]) >> 16) as u32));
+            let codec = mp4_child(trak, &[b"mdia", b"minf", b"stbl", b"stsd"])
+                .and_then(|stsd| stsd.get(12..16))
+                .map(|fourcc| video_codec_nm(&String::from_utf8_lossy(fourcc)));
+            MediaTrack {
+                kind: TrackKind::Video,
+                codec,
+                w: opt_wh.map(|(w, _)| w),
+                h: opt_wh.map(|(_, h)| h),
+                dur_ms: mp4_child(trak, &[b"mdia", b"mdhd"]).and_then(mp4_hd_dur),
+                ..MediaTrack::default()
+            }
+        })
+        .collect();
+    (mp4_child(&moov, &[b"mvhd"]).and_then(mp4_hd_dur), tracks)
+}
+
+const EBML_HEADER: u64 = 0x1A45DFA3;
+const MKV_SEGMENT: u64 = 0x18538067;
+const MKV_INFO: u64 = 0x1549A966;
+const MKV_TIMECODE_SCALE: u64 = 0x2AD7B1;
+const MKV_DURATION: u64 = 0x4489;
+const MKV_TRACKS: u64 = 0x1654AE6B;
+const MKV_TRACK_ENTRY: u64 = 0xAE;
+const MKV_TRACK_TYPE: u64 = 0x83;
+const MKV_CODEC_ID: u64 = 0x86;
+const MKV_LANGUAGE: u64 = 0x22B59C;
+const MKV_VIDEO: u64 = 0xE0;
+const MKV_PIXEL_WIDTH: u64 = 0xB0;
+const MKV_PIXEL_HEIGHT: u64 = 0xBA;
+const MKV_CLUSTER: u64 = 0x1F43B675;
+
+///
+/// (value, len) of a variable size integer. the marker bit is kept for ids.
+/// size of all 1 bits is unknown, `u64::MAX`
+fn read_vint(buf: &[u8], is_id: bool) -> Option<(u64, usize)> {
+    let first = *buf.first()?;
+    let len = first.leading_zeros() as usize + 1;
+    if len > 8 || buf.len() < len {
+        return None;
+    }
+    let marker = if is_id { first } else { first & (0xFFu16 >> len) as u8 };
+    let val = buf[1..len].iter().fold(marker as u64, |acc, b| (acc << 8) | *b as u64);
+    let all_ones = (1u64 << (7 * len)) - 1;
+    Some((if !is_id && val == all_ones { u64::MAX } else { val }, len))
+}
+
+///
+/// (id, payload) of the elements in `buf`
+fn ebml_elems(buf: &[u8]) -> Vec<(u64, &[u8])> {
+    let mut elems = vec![];
+    let mut pos = 0usize;
+    while let Some((id, id_len)) = read_vint(&buf[pos..], true) {
+        let Some((size, size_len)) = read_vint(&buf[pos + id_len..], false) else {
+            break;
+        };
+        let start = pos + id_len + size_len;
+        let end = if size == u64::MAX { buf.len() } else { start.saturating_add(size as usize) };
+        if end > buf.len() {
+            break;
+        }
+        elems.push((id, &buf[start..end]));
+        pos = end;
+    }
+    elems
+}
+
+fn ebml_child(buf: &[u8], id: u64) -> Option<&[u8]> {
+    ebml_elems(buf).into_iter().find(|(i, _)| *i == id).map(|(_, payload)| payload)
+}
+
+fn ebml_float(buf: &[u8]) -> Option<f64> {
+    match buf.len() {
+        4 => Some(f32::from_be_bytes(buf.try_into().ok()?) as f64),
+        8 => Some(f64::from_be_bytes(buf.try_into().ok()?)),
+        _ => None,
+    }
+}
+
+///
+/// `Info` and `Tracks` of the segment, stops at the first cluster
+fn read_mkv_header(file: &mut File) -> (Option<Vec<u8>>, Option<Vec<u8>>) {
+    let (mut info, mut tracks) = (None, None);
+    let len = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+    let mut pos = 0u64;
+    while pos < len && (info.is_none() || tracks.is_none()) {
+        let mut hdr = [0u8; 12];
+        if file.seek(SeekFrom::Start(pos)).is_err() {
+            break;
+        }
+        let n = file.read(&mut hdr).unwrap_or(0);
+        let Some((id, id_len)) = read_vint(&hdr[..n], true) else { break };
+        let Some((size, size_len)) = read_vint(&hdr[id_len..n], false) else { break };
+        let start = pos + (id_len + size_len) as u64;
+        match id {
+            // children of the segment follow its header
+            MKV_SEGMENT => {
+                pos = start;
+                continue;
+            }
+            MKV_INFO | MKV_TRACKS if size <= HEADER_MAX_LEN => {
+                let mut buf = vec![0u8; size as usize];
+                if file.seek(SeekFrom::Start(start)).is_err() || file.read_exact(&mut buf).is_err() {
+                    break;
+                }
+                if id == MKV_INFO { info = Some(buf) } else { tracks = Some(buf) }
+            }
+            MKV_CLUSTER => break,
+            _ if size == u64::MAX => break,
+            _ => {}
+        }
+        pos = start.saturating_add(size);
+    }
+    (info, tracks)
+}
+
+///
+/// (duration, video tracks) of mkv / webm from `Info` and `Tracks`
+fn read_mkv_video(file: &mut File) -> (Option<u64>, Vec<MediaTrack>) {
+    let mut hdr = [0u8; 4];
+    if file.read_exact(&mut hdr).is_err() || be_uint(&hdr) != EBML_HEADER {
+        return (None, vec![]);
+    }
+    let (opt_info, opt_tracks) = read_mkv_header(file);
+    let dur_ms = opt_info.and_then(|info| {
+        // duration is in the unit of the timecode scale, 1ms by default
+        let scale = ebml_child(&info, MKV_TIMECODE_SCALE).map(be_uint).unwrap_or(1_000_000);
+        ebml_child(&info, MKV_DURATION).and_then(ebml_float).map(|dur| (dur * scale as f64 / 1_000_000.0).round() as u64)
+    });
+    let tracks = opt_tracks.map(|tracks| {
+        ebml_elems(&tracks).into_iter()
+            .filter(|(id, entry)| *id == MKV_TRACK_ENTRY && ebml_child(entry, MKV_TRACK_TYPE).map(be_uint) == Some(1))
+            .map(|(_, entry)| {
+                let opt_video = ebml_child(entry, MKV_VIDEO);
+                let get_px = |id: u64| opt_video.and_then(|video| ebml_child(video, id)).map(|px| be_uint(px) as u32);
+                MediaTrack {
+                    kind: TrackKind::Video,
+                    codec: ebml_child(entry, MKV_CODEC_ID).map(|id| video_codec_nm(String::from_utf8_lossy(id).trim_end_matches('\0'))),
+                    w: get_px(MKV_PIXEL_WIDTH),
+                    h: get_px(MKV_PIXEL_HEIGHT),
+                    lang: ebml_child(entry, MKV_LANGUAGE)
+                        .map(|lang| String::from_utf8_lossy(lang).trim_end_matches('\0').to_string())
+                        .filter(|lang| lang != "und"),
+                    ..MediaTrack::default()
+                }
+            })
+            .collect()
+    }).unwrap_or_default();
+    (dur_ms, tracks)
+}
+
+fn read_video(path: &Path, format: &str) -> (Option<u64>, Vec<MediaTrack>) {
+    let Ok(mut file) = File::open(path) else {
+        return (None, vec![]);
+    };
+    match format {
+        "mp4" => read_mp4_video(&mut file),
+        "mkv" | "webm" => read_mkv_video(&mut file),
+        _ => (None, vec![]),
+    }
+}
+
+///
+/// duration, title, artist and album for the listing
+pub fn get_media_meta(path: &Path) -> Option<MediaMeta> {
+    let format = get_format(&get_ext(path));
+    let (opt_dur, _) = read_video(path, &format);
+    match probe(path) {
+        Ok(probed) => {
+            let dur_ms = opt_dur.or_else(|| probed.tracks.iter().filter_map(to_audio_track).filter_map(|t| t.dur_ms).max());
+            Some(to_media_meta(&probed.tags, dur_ms))
+        }
+        Err(_) => opt_dur.map(|dur_ms| MediaMeta { dur_ms: Some(dur_ms), ..MediaMeta::default() }),
+    }
+}
+
+///
+/// container metadata of an audio or video file.
+/// audio tracks and tags by symphonia, video tracks from the `moov` of mp4 or the `Tracks` of mkv
+pub fn get_media_info(path: &Path) -> Result<MediaInfo> {
+    let sz = path.metadata()?.len();
+    let format = get_format(&get_ext(path));
+    let (opt_dur, video_tracks) = read_video(path, &format);
+    let probed = match probe(path) {
+        Ok(probed) => probed,
+        // video only file of a codec unknown to symphonia
+        Err(_) if !video_tracks.is_empty() => Probed { tracks: vec![], tags: vec![], visuals: vec![] },
+        Err(e) => return Err(e),
+    };
+
+    let mut tracks: Vec<MediaTrack> = video_tracks;
+    tracks.extend(probed.tracks.iter().filter_map(to_audio_track));
+    let dur_ms = opt_dur.or_else(|| tracks.iter().filter_map(|t| t.dur_ms).max());
+    let bitrate = dur_ms.filter(|dur| *dur > 0).map(|dur| sz * 8 * 1000 / dur);
+    let cover = get_cover_visual(&probed.visuals).map(|v| MediaCover {
+        mimetype: v.media_type.clone(),
+        w: v.dimensions.map(|d| d.width),
+        h: v.dimensions.map(|d| d.height),
+        sz: v.data.len() as u64,
+    });
+    Ok(MediaInfo {
+        path: path.to_string_lossy().to_string(),
+        format,
+        sz,
+        bitrate,
+        meta: to_media_meta(&probed.tags, dur_ms),
+        tracks,
+        tags: probed.tags.iter()
+            .map(|tag| MediaTag { key: get_tag_key(tag), val: get_tag_val(tag) })
+            .collect(),
+        cover,
+    })
+}
+
+///
+/// encoded image of the embedded cover art
+pub fn get_cover(path: &Path) -> Result<Vec<u8>> {
+    let probed = probe(path)?;
+    get_cover_visual(&probed.visuals)
+        .map(|v| v.data.to_vec())
+        .ok_or_else(|| ApiError::Media(format!("Err No Cover: {:?}", path)))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    fn chunk(id: &[u8], body: &[u8]) -> Vec<u8> {
+        let mut buf = id.to_vec();
+        buf.extend((body.len() as u32).to_le_bytes());
+        buf.extend(body);
+        if body.len() % 2 == 1 {
+            buf.push(0);
+        }
+        buf
+    }
+
+    fn mp4_box(kind: &[u8], body: &[u8]) -> Vec<u8> {
+        let mut buf = ((body.len() + 8) as u32).to_be_bytes().to_vec();
+        buf.extend(kind);
+        buf.extend(body);
+        buf
+    }
+
+    #[test]
+    fn test_wav_info() {
+        let tmp = temp_dir();
+        let path = tmp.path().join("media.wav");
+        // 1s of 8kHz 16bit mono silence
+        let mut fmt = vec![];
+        for v in [1u16, 1] { fmt.extend(v.to_le_bytes()); }
+        for v in [8000u32, 16000] { fmt.extend(v.to_le_bytes()); }
+        for v in [2u16, 16] { fmt.extend(v.to_le_bytes()); }
+        let mut info = b"INFO".to_vec();
+        info.extend(chunk(b"INAM", b"Song\0"));
+        info.extend(chunk(b"IART", b"Band\0"));
+        let mut body = b"WAVE".to_vec();
+        body.extend(chunk(b"fmt ", &fmt));
+        body.extend(chunk(b"LIST", &info));
+        body.extend(chunk(b"data", &vec![0u8; 16000]));
+        std::fs::write(&path, chunk(b"RIFF", &body)).unwrap();
+
+        assert!(is_media("a.WAV") && !is_media("a.txt"));
+        let info = get_media_info(&path).unwrap();
+        assert_eq!((info.format.as_str(), info.meta.dur_ms, info.bitrate.map(|b| b / 1000)), ("wav", Some(1000), Some(128)));
+        assert_eq!((info.meta.title.as_deref(), info.meta.artist.as_deref()), (Some("Song"), Some("Band")));
+        let track = &info.tracks[0];
+        assert_eq!((track.kind, track.sample_rate, track.channels, track.bits), (TrackKind::Audio, Some(8000), Some(1), Some(16)));
+        assert!(info.tags.iter().any(|tag| tag.key == "TrackTitle"));
+        assert_eq!(get_media_meta(&path).and_then(|m| m.dur_ms), Some(1000));
+        assert!(get_cover(&path).is_err());
+    }
+
+    #[test]
+    fn test_mp4_video() {
+        let tmp = temp_dir();
+        let path = tmp.path().join("media.mp4");
+        let mut mvhd = vec![0u8; 12];
+        mvhd.extend(1000u32.to_be_bytes());
+        mvhd.extend(2500u32.to_be_bytes());
+        mvhd.resize(100, 0);
+        let mut tkhd = vec![0u8; 76];
+        tkhd.extend((1920u32 << 16).to_be_bytes());
+        tkhd.extend((1080u32 << 16).to_be_bytes());
+        let mut hdlr = vec![0u8; 8];
+        hdlr.extend(b"vide");
+        hdlr.resize(25, 0);
+        let mut stsd = vec![0, 0, 0, 0, 0, 0, 0, 1];
+        stsd.extend(mp4_box(b"avc1", &[0u8; 78]));
+        let stbl = mp4_box(b"stbl", &mp4_box(b"stsd", &stsd));
+        let mut mdia = mp4_box(b"hdlr", &hdlr);
+        mdia.extend(mp4_box(b"minf", &stbl));
+        let mut trak = mp4_box(b"tkhd", &tkhd);
+        trak.extend(mp4_box(b"mdia", &mdia));
+        let mut moov = mp4_box(b"mvhd", &mvhd);
+        moov.extend(mp4_box(b"trak", &trak));
+        let mut buf = mp4_box(b"ftyp", b"isom\0\0\0\0isom");
+        buf.extend(mp4_box(b"mdat", &[0u8; 32]));
+        buf.extend(mp4_box(b"moov", &moov));
+        std::fs::write(&path, buf).unwrap();
+
+        let (dur_ms, tracks) = read_video(&path, "mp4");
+        assert_eq!(dur_ms, Some(2500));
+        assert_eq!(tracks.len(), 1);
+        assert_eq!((tracks[0].kind, tracks[0].codec.as_deref(), tracks[0].w, tracks[0].h), (TrackKind::Video, Some("h264"), Some(1920), Some(1080)));
+    }
+
+    #[test]
+    fn test_ebml() {
+        // TrackEntry { TrackType: 1, CodecID: V_VP9, Video { PixelWidth: 640, PixelHeight: 360 } }
+        let entry = [0x83, 0x81, 0x01, 0x86, 0x85, b'V', b'_', b'V', b'P', b'9',
+            0xE0, 0x88, 0xB0, 0x82, 0x02, 0x80, 0xBA, 0x82, 0x01, 0x68];
+        let elems = ebml_elems(&entry);
+        assert_eq!(elems.iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![MKV_TRACK_TYPE, MKV_CODEC_ID, MKV_VIDEO]);
+        let video = ebml_child(&entry, MKV_VIDEO).unwrap();
+        assert_eq!(ebml_child(video, MKV_PIXEL_HEIGHT).map(be_uint), Some(360));
+        assert_eq!(read_vint(&[0x1A, 0x45, 0xDF, 0xA3], true), Some((EBML_HEADER, 4)));
+        assert_eq!(read_vint(&[0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF], false), Some((u64::MAX, 8)));
+        assert_eq!(video_codec_nm("V_VP9"), "vp9");
+    }
+}
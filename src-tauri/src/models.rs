@@ -13,6 +13,10 @@ pub enum MetaType {
     Tm,
     Mt,
     Ext,
+    Lang,
+    Img,
+    Pages,
+    Media,
 }
 
 #[allow(dead_code)]
@@ -24,6 +28,10 @@ pub enum OrderBy {
     Tm,
     Mt,
     Ext,
+    Dt,
+    Dur,
+    Artist,
+    Album,
 }
 
 #[allow(dead_code)]
@@ -99,6 +107,13 @@ pub struct CacheVal {
     pub ordering: Vec<OrdItem>,
 }
 
+#[derive(Clone, Debug)]
+pub struct Thumb {
+    pub body: Vec<u8>,
+    pub mimetype: String,
+    pub etag: String,
+}
+
 #[allow(dead_code)]
 #[skip_serializing_none]
 #[derive(Type, Serialize, Deserialize, Debug, Default)]
@@ -125,9 +140,101 @@ pub struct Item {
     pub mt: Option<String>,
     pub sz: Option<u64>,  // u64
     pub tm: Option<u64>,  // u64
+    pub lang: Option<String>,
+    pub img: Option<ImgMeta>,
+    pub cnt: Option<u64>,  // rows of a table in a database, None over DB_COUNT_MAX
+    pub pages: Option<u64>,  // pages of a pdf
+    pub media: Option<MediaMeta>,
     pub items: Option<Vec<Item>>
 }
 
+///
+/// image meta of listing
+///
+/// - dt: capture date (unix time) from exif
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ImgMeta {
+    pub w: u32,
+    pub h: u32,
+    pub dt: Option<u64>,
+}
+
+///
+/// audio or video meta of listing
+///
+/// - dur_ms: duration in milliseconds
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct MediaMeta {
+    pub dur_ms: Option<u64>,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+}
+
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ImgInfo {
+    pub path: String,
+    pub format: String,
+    pub mimetype: String,
+    pub w: u32,
+    pub h: u32,
+    pub color: String,
+    pub frames: Option<usize>,
+    pub exif: Option<ExifInfo>,
+}
+
+///
+/// exif of the primary image
+///
+/// - dt: capture date `2024-01-31 12:34:56`, dt_sec: unix time of dt
+/// - orientation: 1 ~ 8
+/// - lat, lon: degrees, alt: meters
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ExifInfo {
+    pub make: Option<String>,
+    pub model: Option<String>,
+    pub lens: Option<String>,
+    pub dt: Option<String>,
+    pub dt_sec: Option<u64>,
+    pub orientation: Option<u32>,
+    pub exposure: Option<String>,
+    pub f_number: Option<f64>,
+    pub iso: Option<u32>,
+    pub focal_len: Option<f64>,
+    pub lat: Option<f64>,
+    pub lon: Option<f64>,
+    pub alt: Option<f64>,
+    pub tags: Vec<ExifTag>,
+}
+
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ExifTag {
+    pub ifd: u16,
+    pub tag: String,
+    pub val: String,
+}
+
+///
+/// text of the file
+///
+/// - detection: how the encoding was detected, `None` if it is given or remembered
+/// - sz, tm_ms: of the file
+/// - codec: `gzip`, `bzip2`, `xz` or `zstd` if the text is decompressed from the file
 #[allow(dead_code)]
 #[skip_serializing_none]
 #[serde_as]
@@ -137,6 +244,854 @@ pub struct TextContent {
     pub mimetype: String,
     pub enc: Option<String>,
     pub text: Option<String>,
+    pub language: Option<String>,
+    pub detection: Option<Detection>,
+    pub errs: Option<Vec<DecodeErr>>,
+    pub err_cnt: Option<usize>,
+    pub bom: Option<bool>,
+    pub eol: Option<EolInfo>,
+    pub final_newline: Option<bool>,
+    pub indent: Option<IndentInfo>,
+    pub sz: Option<u64>,
+    pub tm_ms: Option<u64>,
+    pub codec: Option<String>,
+}
+
+///
+/// write options
+///
+/// - enc, bom, eol: from `TextContent`. `eol` converts all line endings, `Mixed` or `None` keeps them
+/// - sz, tm_ms: from `TextContent`. if the file changed since then, `ApiError::Conflict`
+/// - force: write even if conflicted
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct WriteOpts {
+    pub enc: Option<String>,
+    pub bom: Option<bool>,
+    pub eol: Option<Eol>,
+    pub sz: Option<u64>,
+    pub tm_ms: Option<u64>,
+    pub force: Option<bool>,
+}
+
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct WriteInfo {
+    pub path: String,
+    pub enc: String,
+    pub sz: u64,
+    pub tm_ms: u64,
+}
+
+///
+/// how the encoding of a text is detected
+///
+/// - Bom: by the byte order mark
+/// - Ascii: ascii only, the same in any ascii compatible encoding
+/// - Utf8: valid UTF-8
+/// - Guess: guessed from the bytes
+/// - WeakGuess: guessed without a clear winner, likely to be wrong
+#[allow(dead_code)]
+#[derive(Type, Serialize, Deserialize, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Detection {
+    Bom,
+    Ascii,
+    Utf8,
+    Guess,
+    WeakGuess,
+}
+
+#[allow(dead_code)]
+#[derive(Type, Serialize, Deserialize, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub enum Eol {
+    #[default]
+    None,
+    Lf,
+    Crlf,
+    Cr,
+    Mixed,
+}
+
+///
+/// line ending counts
+///
+/// - eol: the only kind used, `Mixed` if more than one kind, `None` if single line
+/// - dominant: the most used kind
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct EolInfo {
+    pub eol: Eol,
+    pub dominant: Eol,
+    pub lf: usize,
+    pub crlf: usize,
+    pub cr: usize,
+}
+
+///
+/// indentation counts of lines
+///
+/// - mixed: lines indented with both tab and space
+/// - width: guessed space indent width
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct IndentInfo {
+    pub tab: usize,
+    pub space: usize,
+    pub mixed: usize,
+    pub width: Option<usize>,
+}
+
+///
+/// malformed byte sequence replaced with U+FFFD
+///
+/// - byte_pos: byte offset in the file
+/// - chr_pos: utf-16 offset in the text (javascript string index)
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct DecodeErr {
+    pub byte_pos: u64,
+    pub byte_len: u32,
+    pub chr_pos: usize,
+}
+
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct HexRow {
+    pub offset: u64,
+    pub hex: String,
+    pub ascii: String,
+}
+
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct MagicInfo {
+    pub mimetype: String,
+    pub ext: String,
+    pub kind: String,
+    pub desc: Option<String>,
+}
+
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct HexContent {
+    pub path: String,
+    pub mimetype: String,
+    pub magic: Option<MagicInfo>,
+    pub sz: u64,
+    pub offset: u64,
+    pub len: u64,
+    pub rows: Vec<HexRow>,
+}
+
+///
+/// - next_offset: of the next match over `take_n`, or to resume from if cancelled
+/// - is_cancelled: stopped by `cancel_search`, `offsets` are those found until then
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct HexSearch {
+    pub path: String,
+    pub pattern: String,
+    pub offsets: Vec<u64>,
+    pub next_offset: Option<u64>,
+    pub is_cancelled: bool,
+}
+
+///
+/// access policy of the paths, `policy.json` in the config dir or the file of `TR_VIEWER_POLICY`
+///
+/// - allow: roots that can be accessed. If empty then any path that is not denied
+/// - deny: roots that can not be accessed, even under an allowed root
+/// - read_only: files are not written
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct AccessPolicy {
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+    pub read_only: bool,
+}
+
+///
+/// event of a state set or removed by `set_state`, or saved as the default by `get_state`.
+/// a window of `subscribe_state` gets the keys of its prefixes only
+///
+/// - old, new: `None` if it did not exist or is removed
+/// - source: label of the window that changed it
+#[allow(dead_code)]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default, tauri_specta::Event)]
+pub struct StateChanged {
+    pub key: String,
+    pub old: Option<serde_json::Value>,
+    pub new: Option<serde_json::Value>,
+    pub source: Option<String>,
+}
+
+///
+/// pinned place of the left pane
+///
+/// - nm: shown name, the file name if not given
+/// - group: `None` for no group
+/// - dir, exists: checked by `get_bookmarks`, `None` in the file
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Bookmark {
+    pub id: String,
+    pub nm: String,
+    pub path: String,
+    pub group: Option<String>,
+    pub dir: Option<bool>,
+    pub exists: Option<bool>,
+}
+
+///
+/// text search options
+///
+/// - enc: encoding label. If `None` then the remembered encoding of the file or detected one
+/// - literal: the pattern is plain text, not a regex
+/// - ctx: lines of context before and after a match, 0 if `None`
+/// - offset, line: where to start, `next_offset` and `next_line` of the previous page
+/// - id: to cancel the search with `cancel_search`
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SearchOpts {
+    pub enc: Option<String>,
+    pub ignore_case: Option<bool>,
+    pub whole_word: Option<bool>,
+    pub literal: Option<bool>,
+    pub ctx: Option<usize>,
+    pub offset: Option<u64>,
+    pub line: Option<u64>,
+    pub take_n: Option<usize>,
+    pub id: Option<String>,
+}
+
+///
+/// match of a text search
+///
+/// - line, col: 1-based. col is utf-16 like Monaco
+/// - offset, byte_len: bytes in the file
+/// - text: the line, around the match if the line is long
+/// - pos, len: utf-16 offset of the match in `text`
+/// - before, after: context lines
+#[allow(dead_code)]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct TextMatch {
+    pub line: u64,
+    pub col: usize,
+    pub offset: u64,
+    pub byte_len: usize,
+    pub text: String,
+    pub pos: usize,
+    pub len: usize,
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+}
+
+///
+/// page of a text search
+///
+/// - next_offset, next_line: start of the next page, `None` if the end of the file is reached
+/// - scanned: bytes searched up to
+/// - is_cancelled: stopped by `cancel_search`, `matches` are those found until then
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct TextSearch {
+    pub path: String,
+    pub pattern: String,
+    pub enc: String,
+    pub sz: u64,
+    pub matches: Vec<TextMatch>,
+    pub next_offset: Option<u64>,
+    pub next_line: Option<u64>,
+    pub scanned: u64,
+    pub is_cancelled: bool,
+}
+
+#[allow(dead_code)]
+#[derive(Type, Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Hash, Debug, Default)]
+pub enum ColType {
+    #[default]
+    String,
+    Int,
+    Float,
+    Bool,
+    Date,
+}
+
+#[allow(dead_code)]
+#[derive(Type, Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum FilterOp {
+    Eq,
+    Ne,
+    Contains,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+#[allow(dead_code)]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct TableCol {
+    pub nm: String,
+    pub kind: ColType,
+}
+
+#[allow(dead_code)]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct TableSort {
+    pub col: usize,
+    pub asc: OrderAsc,
+}
+
+///
+/// `Contains` ignores case. `Gt`, `Ge`, `Lt`, `Le` compare numbers in `Int`, `Float` columns
+#[allow(dead_code)]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct TableFilter {
+    pub col: usize,
+    pub op: FilterOp,
+    pub val: String,
+}
+
+///
+/// diff options
+///
+/// - ignore_ws: ignore all whitespace in lines
+/// - ignore_eol: ignore `\r\n` vs `\n` and a missing final newline
+/// - ctx: lines of context around changes, 3 if `None`
+/// - words: word diff of changed lines
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct DiffOpts {
+    pub ignore_ws: Option<bool>,
+    pub ignore_eol: Option<bool>,
+    pub ctx: Option<usize>,
+    pub words: Option<bool>,
+}
+
+#[allow(dead_code)]
+#[derive(Type, Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Hash, Debug, Default)]
+pub enum DiffKind {
+    #[default]
+    Equal,
+    Delete,
+    Insert,
+}
+
+#[allow(dead_code)]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct DiffWord {
+    pub kind: DiffKind,
+    pub text: String,
+}
+
+///
+/// - old_no, new_no: 1-based line number in the left and the right
+/// - text: without the line ending
+/// - words: changed words of a replaced line
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct DiffLine {
+    pub kind: DiffKind,
+    pub old_no: Option<usize>,
+    pub new_no: Option<usize>,
+    pub text: String,
+    pub words: Option<Vec<DiffWord>>,
+}
+
+///
+/// - old_start, new_start: 1-based first line, `@@ -old_start,old_len +new_start,new_len @@`
+#[allow(dead_code)]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct DiffHunk {
+    pub old_start: usize,
+    pub old_len: usize,
+    pub new_start: usize,
+    pub new_len: usize,
+    pub lines: Vec<DiffLine>,
+}
+
+///
+/// diff of two text files
+///
+/// - left_enc, right_enc: encoding the files are decoded with
+/// - unified: unified diff text of the hunks
+/// - add_cnt, del_cnt: inserted and deleted lines
+/// - is_over: a file is cut to the max length or the hunks to the max lines
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct DiffContent {
+    pub left: String,
+    pub right: String,
+    pub left_enc: Option<String>,
+    pub right_enc: Option<String>,
+    pub hunks: Vec<DiffHunk>,
+    pub unified: String,
+    pub add_cnt: usize,
+    pub del_cnt: usize,
+    pub is_over: bool,
+}
+
+///
+/// table options
+///
+/// - enc, delimiter, has_header: sniffed if `None`. `delimiter` is one character (`"\t"`)
+/// - filters: rows matching all of them
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct TableOpts {
+    pub enc: Option<String>,
+    pub delimiter: Option<String>,
+    pub has_header: Option<bool>,
+    pub sorts: Option<Vec<TableSort>>,
+    pub filters: Option<Vec<TableFilter>>,
+    pub skip_n: Option<usize>,
+    pub take_n: Option<usize>,
+}
+
+///
+/// page of a table
+///
+/// - row_cnt: data rows of the file, tot: rows after filters
+/// - row_nos: 0-based data row numbers of `rows` in the file
+#[allow(dead_code)]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct TableContent {
+    pub path: String,
+    pub enc: String,
+    pub delimiter: String,
+    pub has_header: bool,
+    pub cols: Vec<TableCol>,
+    pub row_cnt: usize,
+    pub tot: usize,
+    pub skip_n: usize,
+    pub take_n: usize,
+    pub row_nos: Vec<usize>,
+    pub rows: Vec<Vec<String>>,
+}
+
+///
+/// column of a database table
+///
+/// - decl: declared type, `kind` is by its affinity
+/// - dflt: default value as sql
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct DbCol {
+    pub nm: String,
+    pub decl: String,
+    pub kind: ColType,
+    pub not_null: bool,
+    pub pk: bool,
+    pub dflt: Option<String>,
+}
+
+///
+/// page of a database table or view
+///
+/// - path: of the table, `C:\data\app.db\users`
+/// - kind: `table` or `view`, sql: `CREATE` statement
+/// - row_cnt: rows of the table, tot: rows after filters
+/// - rows: `NULL` is `None`, blobs are shown as `x'0102' (2 bytes)`
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct DbContent {
+    pub path: String,
+    pub kind: String,
+    pub sql: Option<String>,
+    pub cols: Vec<DbCol>,
+    pub row_cnt: u64,
+    pub tot: u64,
+    pub skip_n: usize,
+    pub take_n: usize,
+    pub rows: Vec<Vec<Option<String>>>,
+}
+
+#[allow(dead_code)]
+#[derive(Type, Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Hash, Debug, Default)]
+pub enum OfficeFormat {
+    #[default]
+    Docx,
+    Xlsx,
+    Pptx,
+    Odt,
+    Ods,
+    Odp,
+}
+
+///
+/// document properties
+///
+/// - author: creator of the document, modified_by: last modified by
+/// - created, modified: as stored, `2024-05-01T10:00:00Z`
+/// - pages: pages of docx, odt or slides of pptx
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct DocProps {
+    pub title: Option<String>,
+    pub subject: Option<String>,
+    pub author: Option<String>,
+    pub modified_by: Option<String>,
+    pub created: Option<String>,
+    pub modified: Option<String>,
+    pub app: Option<String>,
+    pub pages: Option<u64>,
+}
+
+///
+/// - row_cnt: rows of the sheet, `rows` are up to 1000
+#[allow(dead_code)]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct OfficeSheet {
+    pub nm: String,
+    pub rows: Vec<Vec<String>>,
+    pub row_cnt: usize,
+}
+
+///
+/// - no: 1-based in the order of the presentation
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct OfficeSlide {
+    pub no: usize,
+    pub nm: Option<String>,
+    pub paras: Vec<String>,
+}
+
+///
+/// text of an office document
+///
+/// - paras: paragraphs of docx, odt
+/// - sheets: xlsx, ods
+/// - slides: pptx, odp
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct OfficeContent {
+    pub path: String,
+    pub format: OfficeFormat,
+    pub props: DocProps,
+    pub paras: Option<Vec<String>>,
+    pub sheets: Option<Vec<OfficeSheet>>,
+    pub slides: Option<Vec<OfficeSlide>>,
+}
+
+///
+/// document info of a pdf
+///
+/// - created, modified: unix time
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct PdfInfo {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub subject: Option<String>,
+    pub keywords: Option<String>,
+    pub creator: Option<String>,
+    pub producer: Option<String>,
+    pub created: Option<u64>,
+    pub modified: Option<u64>,
+}
+
+///
+/// bookmark of a pdf
+///
+/// - lvl: 1-based depth, page: 1-based page number
+#[allow(dead_code)]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct PdfOutline {
+    pub lvl: usize,
+    pub title: String,
+    pub page: usize,
+}
+
+///
+/// - no: 1-based page number
+/// - err: set instead of text if the text can not be extracted
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct PdfPage {
+    pub no: u32,
+    pub text: Option<String>,
+    pub err: Option<String>,
+}
+
+///
+/// pdf document
+///
+/// - version: pdf version, `1.7`
+/// - pages: text of the requested pages
+#[allow(dead_code)]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct PdfContent {
+    pub path: String,
+    pub version: String,
+    pub page_cnt: u64,
+    pub info: PdfInfo,
+    pub outline: Vec<PdfOutline>,
+    pub pages: Vec<PdfPage>,
+}
+
+#[allow(dead_code)]
+#[derive(Type, Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Hash, Debug, Default)]
+pub enum TrackKind {
+    #[default]
+    Audio,
+    Video,
+}
+
+///
+/// audio or video track of a media file
+///
+/// - codec: `mp3`, `aac`, `flac`, `h264`, ...
+/// - bits: bits per sample
+/// - w, h: resolution of a video track
+/// - dur_ms: duration in milliseconds
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct MediaTrack {
+    pub kind: TrackKind,
+    pub codec: Option<String>,
+    pub sample_rate: Option<u32>,
+    pub channels: Option<u32>,
+    pub bits: Option<u32>,
+    pub w: Option<u32>,
+    pub h: Option<u32>,
+    pub dur_ms: Option<u64>,
+    pub lang: Option<String>,
+}
+
+///
+/// - key: standard key `TrackTitle`, `Artist`, ... or the key in the file
+#[allow(dead_code)]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct MediaTag {
+    pub key: String,
+    pub val: String,
+}
+
+///
+/// embedded cover art, served as a thumbnail of the media file
+///
+/// - mimetype: `image/jpeg`, ...
+/// - sz: bytes of the image
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct MediaCover {
+    pub mimetype: String,
+    pub w: Option<u32>,
+    pub h: Option<u32>,
+    pub sz: u64,
+}
+
+///
+/// container metadata of an audio or video file
+///
+/// - format: container, `mp3`, `mp4`, `mkv`, ...
+/// - bitrate: overall bits per second
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct MediaInfo {
+    pub path: String,
+    pub format: String,
+    pub sz: u64,
+    pub bitrate: Option<u64>,
+    pub meta: MediaMeta,
+    pub tracks: Vec<MediaTrack>,
+    pub tags: Vec<MediaTag>,
+    pub cover: Option<MediaCover>,
+}
+
+#[allow(dead_code)]
+#[derive(Type, Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum StructFormat {
+    Json,
+    Yaml,
+    Toml,
+    Xml,
+}
+
+#[allow(dead_code)]
+#[derive(Type, Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub enum NodeType {
+    Object,
+    Array,
+    String,
+    Number,
+    Bool,
+    #[default]
+    Null,
+}
+
+///
+/// node of a structured document
+///
+/// - path: json pointer of the node (`/servers/0/name`), `""` for the root
+/// - cnt: child count of object or array
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct StructNode {
+    pub key: String,
+    pub path: String,
+    pub kind: NodeType,
+    pub preview: String,
+    pub cnt: Option<usize>,
+}
+
+///
+/// parse error, line and col are 1-based. col counts utf-16 units like monaco
+#[allow(dead_code)]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ParseErr {
+    pub msg: String,
+    pub line: usize,
+    pub col: usize,
+}
+
+///
+/// node and its children of a structured document
+///
+/// - err: set instead of node and items if the document can not be parsed
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct StructContent {
+    pub path: String,
+    pub format: Option<StructFormat>,
+    pub node: Option<StructNode>,
+    pub items: Option<Vec<StructNode>>,
+    pub tot: Option<usize>,
+    pub skip_n: Option<usize>,
+    pub take_n: Option<usize>,
+    pub err: Option<ParseErr>,
+}
+
+///
+/// heading of a markdown document
+///
+/// - lvl: 1 to 6
+/// - id: anchor of the heading in html (`#intro`)
+#[allow(dead_code)]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct TocItem {
+    pub lvl: u8,
+    pub id: String,
+    pub text: String,
+}
+
+///
+/// sanitized html of a markdown document
+///
+/// - toc: headings in order
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct MarkdownContent {
+    pub path: String,
+    pub html: String,
+    pub toc: Vec<TocItem>,
+    pub sz: Option<u64>,
+    pub tm_ms: Option<u64>,
+}
+
+///
+/// removed tags or attributes of the same name
+#[allow(dead_code)]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct RemovedItem {
+    pub nm: String,
+    pub cnt: usize,
+}
+
+///
+/// sanitized html of a html file
+///
+/// - removed_tags, removed_attrs: scripts, event handlers, frames, forms, `javascript:` urls, ...
+/// - remote_urls: images from other hosts, not loaded
+#[allow(dead_code)]
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct HtmlContent {
+    pub path: String,
+    pub html: String,
+    pub removed_tags: Vec<RemovedItem>,
+    pub removed_attrs: Vec<RemovedItem>,
+    pub remote_urls: Vec<String>,
+    pub sz: Option<u64>,
+    pub tm_ms: Option<u64>,
 }
 
 
@@ -199,6 +1154,60 @@ pub enum ApiError {
     #[error("windows::core::Error: {0}")]
     DirApi(String),
 
+    #[error("Hex error: {0}")]
+    Hex(String),
+
+    #[error("Encoding error: {0}")]
+    Encoding(String),
+
+    #[error("Conflict error: {0}")]
+    Conflict(String),
+
+    #[error("Image error: {0}")]
+    Img(String),
+
+    #[error("Archive error: {0}")]
+    Archive(String),
+
+    #[error("Compress error: {0}")]
+    Compress(String),
+
+    #[error("Struct error: {0}")]
+    Struct(String),
+
+    #[error("Table error: {0}")]
+    Table(String),
+
+    #[error("Html error: {0}")]
+    Html(String),
+
+    #[error("Db error: {0}")]
+    Db(String),
+
+    #[error("Office error: {0}")]
+    Office(String),
+
+    #[error("Pdf error: {0}")]
+    Pdf(String),
+
+    #[error("Media error: {0}")]
+    Media(String),
+
+    #[error("Diff error: {0}")]
+    Diff(String),
+
+    #[error("Search error: {0}")]
+    Search(String),
+
+    #[error("Policy error: {0}")]
+    Policy(String),
+
+    #[error("Settings error: {0}")]
+    Settings(String),
+
+    #[error("Bookmark error: {0}")]
+    Bookmark(String),
+
 
 }
 
@@ -214,6 +1223,42 @@ impl From<serde_json::Error> for ApiError {
     }
 }
 
+impl From<image::ImageError> for ApiError {
+    fn from(e: image::ImageError) -> Self {
+        ApiError::Img(e.to_string())
+    }
+}
+
+impl From<csv::Error> for ApiError {
+    fn from(e: csv::Error) -> Self {
+        ApiError::Table(e.to_string())
+    }
+}
+
+impl From<rusqlite::Error> for ApiError {
+    fn from(e: rusqlite::Error) -> Self {
+        ApiError::Db(e.to_string())
+    }
+}
+
+impl From<symphonia::core::errors::Error> for ApiError {
+    fn from(e: symphonia::core::errors::Error) -> Self {
+        ApiError::Media(e.to_string())
+    }
+}
+
+impl From<zip::result::ZipError> for ApiError {
+    fn from(e: zip::result::ZipError) -> Self {
+        ApiError::Archive(e.to_string())
+    }
+}
+
+impl From<sevenz_rust::Error> for ApiError {
+    fn from(e: sevenz_rust::Error) -> Self {
+        ApiError::Archive(e.to_string())
+    }
+}
+
 impl From<windows::core::Error> for ApiError {
     fn from(e: windows::core::Error) -> Self {
         ApiError::DirApi(e.to_string())
@@ -0,0 +1,598 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use zip::ZipArchive;
+use crate::models::{ApiError, DocProps, OfficeContent, OfficeFormat, OfficeSheet, OfficeSlide};
+
+type Result<T> = std::result::Result<T, ApiError>;
+type Zip = ZipArchive<BufReader<File>>;
+
+pub const OFFICE_ROWS_MAX: usize = 1000;
+const OFFICE_COLS_MAX: usize = 256;
+const XML_MAX_LEN: u64 = 64 * 1024 * 1024;
+
+const OFFICE_EXTS: &[(&str, OfficeFormat)] = &[
+    (".docx", OfficeFormat::Docx),
+    (".docm", OfficeFormat::Docx),
+    (".xlsx", OfficeFormat::Xlsx),
+    (".xlsm", OfficeFormat::Xlsx),
+    (".pptx", OfficeFormat::Pptx),
+    (".pptm", OfficeFormat::Pptx),
+    (".odt", OfficeFormat::Odt),
+    (".ods", OfficeFormat::Ods),
+    (".odp", OfficeFormat::Odp),
+];
+
+pub fn get_office_format(nm: &str) -> Option<OfficeFormat> {
+    let nm = nm.to_lowercase();
+    OFFICE_EXTS.iter().find(|(ext, _)| nm.ends_with(ext)).map(|(_, format)| *format)
+}
+
+fn xml_err(e: impl std::fmt::Display) -> ApiError {
+    ApiError::Office(e.to_string())
+}
+
+///
+/// xml part of the package, `None` if not in it. a part over `XML_MAX_LEN` is an error, not cut
+fn read_part(zip: &mut Zip, nm: &str) -> Result<Option<String>> {
+    let file = match zip.by_name(nm) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let mut buffer: Vec<u8> = vec![];
+    file.take(XML_MAX_LEN + 1).read_to_end(&mut buffer)?;
+    if buffer.len() as u64 > XML_MAX_LEN {
+        return Err(ApiError::Office(format!("Err Too Large: {}", nm)));
+    }
+    Ok(Some(String::from_utf8_lossy(&buffer).to_string()))
+}
+
+///
+/// attribute by qualified name (`r:id`)
+fn get_attr(e: &BytesStart, qname: &str) -> Option<String> {
+    e.attributes()
+        .flatten()
+        .find(|attr| attr.key.as_ref() == qname.as_bytes())
+        .and_then(|attr| attr.unescape_value().ok().map(|v| v.to_string()))
+}
+
+fn local_name(e: &BytesStart) -> Vec<u8> {
+    e.local_name().as_ref().to_vec()
+}
+
+///
+/// `Id` -> part name of `_rels/*.rels`, targets are relative to `base_dir`
+fn read_rels(zip: &mut Zip, rels_nm: &str, base_dir: &str) -> Result<HashMap<String, String>> {
+    let mut rels: HashMap<String, String> = HashMap::new();
+    let Some(xml) = read_part(zip, rels_nm)? else {
+        return Ok(rels);
+    };
+    let mut reader = Reader::from_str(&xml);
+    loop {
+        match reader.read_event().map_err(xml_err)? {
+            Event::Start(e) | Event::Empty(e) if local_name(&e) == b"Relationship" => {
+                if let (Some(id), Some(target)) = (get_attr(&e, "Id"), get_attr(&e, "Target")) {
+                    let nm = match target.strip_prefix('/') {
+                        Some(abs) => abs.to_string(),
+                        None => format!("{}/{}", base_dir, target),
+                    };
+                    rels.insert(id, nm);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(rels)
+}
+
+///
+/// paragraphs of `w:p`, `a:p` or `text:p` / `text:h`. tabs and line breaks are kept
+fn read_paras(xml: &str, para_tags: &[&[u8]]) -> Result<Vec<String>> {
+    let mut paras: Vec<String> = vec![];
+    let mut cur: Option<String> = None;
+    let mut depth = 0;
+    let mut reader = Reader::from_str(xml);
+    loop {
+        match reader.read_event().map_err(xml_err)? {
+            // field codes and deleted runs of docx
+            Event::Start(e) if matches!(e.local_name().as_ref(), b"instrText" | b"delText") => {
+                reader.read_to_end(e.name()).map_err(xml_err)?;
+            }
+            Event::Start(e) if para_tags.contains(&e.local_name().as_ref()) => {
+                depth += 1;
+                cur.get_or_insert_with(String::new);
+            }
+            Event::End(e) if para_tags.contains(&e.local_name().as_ref()) => {
+                depth -= 1;
+                if depth == 0 {
+                    paras.extend(cur.take());
+                }
+            }
+            Event::Empty(e) => {
+                let nm = local_name(&e);
+                if para_tags.contains(&nm.as_slice()) && depth == 0 {
+                    paras.push(String::new());
+                } else if let Some(s) = cur.as_mut() {
+                    match nm.as_slice() {
+                        b"tab" => s.push('\t'),
+                        b"br" | b"cr" | b"line-break" => s.push('\n'),
+                        // odf `<text:s text:c="3"/>`
+                        b"s" => s.push_str(&" ".repeat(get_attr(&e, "text:c").and_then(|c| c.parse().ok()).unwrap_or(1))),
+                        _ => {}
+                    }
+                }
+            }
+            Event::Text(e) => {
+                if let Some(s) = cur.as_mut() {
+                    s.push_str(&e.unescape().map_err(xml_err)?);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(paras)
+}
+
+///
+/// `AB12` -> 27 (0-based column)
+fn col_of_ref(cell_ref: &str) -> Option<usize> {
+    let letters: String = cell_ref.chars().take_while(|c| c.is_ascii_alphabetic()).collect();
+    if letters.is_empty() {
+        return None;
+    }
+    Some(letters.to_ascii_uppercase().bytes().fold(0usize, |acc, b| acc * 26 + (b - b'A' + 1) as usize) - 1)
+}
+
+fn read_shared_strings(zip: &mut Zip) -> Result<Vec<String>> {
+    let mut strings: Vec<String> = vec![];
+    let Some(xml) = read_part(zip, "xl/sharedStrings.xml")? else {
+        return Ok(strings);
+    };
+    let mut reader = Reader::from_str(&xml);
+    let mut in_t = false;
+    loop {
+        match reader.read_event().map_err(xml_err)? {
+            Event::Start(e) if e.local_name().as_ref() == b"si" => strings.push(String::new()),
+            Event::Start(e) if e.local_name().as_ref() == b"t" => in_t = true,
+            Event::End(e) if e.local_name().as_ref() == b"t" => in_t = false,
+            // phonetic runs of `rPh` are not text of the cell
+            Event::Start(e) if e.local_name().as_ref() == b"rPh" => {
+                reader.read_to_end(e.name()).map_err(xml_err)?;
+            }
+            Event::Text(e) if in_t => {
+                if let Some(s) = strings.last_mut() {
+                    s.push_str(&e.unescape().map_err(xml_err)?);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(strings)
+}
+
+fn set_cell(rows: &mut [Vec<String>], col: usize, val: String) {
+    if let Some(row) = rows.last_mut() {
+        if col < OFFICE_COLS_MAX {
+            if row.len() <= col {
+                row.resize(col + 1, String::new());
+            }
+            row[col] = val;
+        }
+    }
+}
+
+///
+/// values of the cells as stored, numbers and dates are not formatted. formulas are not text
+///
+/// returns rows up to `OFFICE_ROWS_MAX` and the number of the last row
+fn read_xlsx_sheet(xml: &str, strings: &[String]) -> Result<(Vec<Vec<String>>, usize)> {
+    let mut rows: Vec<Vec<String>> = vec![];
+    let (mut row_no, mut col) = (0usize, 0usize);
+    let mut cell_type = String::new();
+    let mut val: Option<String> = None;
+    let mut in_val = false;
+    let mut reader = Reader::from_str(xml);
+    loop {
+        match reader.read_event().map_err(xml_err)? {
+            Event::Start(e) if e.local_name().as_ref() == b"row" => {
+                row_no = get_attr(&e, "r").and_then(|r| r.parse().ok()).unwrap_or(row_no + 1);
+                if row_no <= OFFICE_ROWS_MAX {
+                    // rows without cells are not stored in the sheet
+                    rows.resize(row_no, vec![]);
+                }
+                col = 0;
+            }
+            Event::Empty(e) if e.local_name().as_ref() == b"row" => {
+                row_no = get_attr(&e, "r").and_then(|r| r.parse().ok()).unwrap_or(row_no + 1);
+            }
+            Event::Start(e) if e.local_name().as_ref() == b"c" => {
+                col = get_attr(&e, "r").and_then(|r| col_of_ref(&r)).unwrap_or(col);
+                cell_type = get_attr(&e, "t").unwrap_or_default();
+                val = None;
+            }
+            Event::Empty(e) if e.local_name().as_ref() == b"c" => {
+                col = get_attr(&e, "r").and_then(|r| col_of_ref(&r)).unwrap_or(col) + 1;
+            }
+            Event::End(e) if e.local_name().as_ref() == b"c" => {
+                if let (Some(v), true) = (val.take(), row_no <= OFFICE_ROWS_MAX) {
+                    let v = match cell_type.as_str() {
+                        "s" => v.trim().parse::<usize>().ok().and_then(|i| strings.get(i)).cloned().unwrap_or_default(),
+                        "b" => String::from(if v.trim() == "1" { "TRUE" } else { "FALSE" }),
+                        _ => v,
+                    };
+                    set_cell(&mut rows, col, v);
+                }
+                col += 1;
+            }
+            // `v` of the cell, or `t` of an inline string
+            Event::Start(e) if matches!(e.local_name().as_ref(), b"v" | b"t") => in_val = true,
+            Event::End(e) if matches!(e.local_name().as_ref(), b"v" | b"t") => in_val = false,
+            Event::Text(e) if in_val => {
+                val.get_or_insert_with(String::new).push_str(&e.unescape().map_err(xml_err)?);
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok((rows, row_no))
+}
+
+fn read_docx(zip: &mut Zip, content: &mut OfficeContent) -> Result<()> {
+    let xml = read_part(zip, "word/document.xml")?
+        .ok_or_else(|| ApiError::Office(String::from("Err Not Found: word/document.xml")))?;
+    content.paras = Some(read_paras(&xml, &[b"p"])?);
+    Ok(())
+}
+
+fn read_xlsx(zip: &mut Zip, content: &mut OfficeContent) -> Result<()> {
+    let xml = read_part(zip, "xl/workbook.xml")?
+        .ok_or_else(|| ApiError::Office(String::from("Err Not Found: xl/workbook.xml")))?;
+    let rels = read_rels(zip, "xl/_rels/workbook.xml.rels", "xl")?;
+    let strings = read_shared_strings(zip)?;
+    let mut sheets: Vec<(String, String)> = vec![];
+    let mut reader = Reader::from_str(&xml);
+    loop {
+        match reader.read_event().map_err(xml_err)? {
+            Event::Start(e) | Event::Empty(e) if local_name(&e) == b"sheet" => {
+                let nm = get_attr(&e, "name").unwrap_or_default();
+                if let Some(part) = get_attr(&e, "r:id").and_then(|id| rels.get(&id)) {
+                    sheets.push((nm, part.clone()));
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    let mut office_sheets: Vec<OfficeSheet> = vec![];
+    for (nm, part) in sheets {
+        let (rows, row_cnt) = match read_part(zip, &part)? {
+            Some(xml) => read_xlsx_sheet(&xml, &strings)?,
+            None => (vec![], 0),
+        };
+        office_sheets.push(OfficeSheet { nm, rows, row_cnt });
+    }
+    content.sheets = Some(office_sheets);
+    Ok(())
+}
+
+fn read_pptx(zip: &mut Zip, content: &mut OfficeContent) -> Result<()> {
+    let xml = read_part(zip, "ppt/presentation.xml")?
+        .ok_or_else(|| ApiError::Office(String::from("Err Not Found: ppt/presentation.xml")))?;
+    let rels = read_rels(zip, "ppt/_rels/presentation.xml.rels", "ppt")?;
+    let mut parts: Vec<String> = vec![];
+    let mut reader = Reader::from_str(&xml);
+    loop {
+        match reader.read_event().map_err(xml_err)? {
+            Event::Start(e) | Event::Empty(e) if local_name(&e) == b"sldId" => {
+                if let Some(part) = get_attr(&e, "r:id").and_then(|id| rels.get(&id)) {
+                    parts.push(part.clone());
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    let mut slides: Vec<OfficeSlide> = vec![];
+    for (i, part) in parts.iter().enumerate() {
+        let paras = match read_part(zip, part)? {
+            Some(xml) => read_paras(&xml, &[b"p"])?,
+            None => vec![],
+        };
+        slides.push(OfficeSlide { no: i + 1, nm: None, paras });
+    }
+    content.slides = Some(slides);
+    Ok(())
+}
+
+fn get_repeat(e: &BytesStart, qname: &str) -> usize {
+    get_attr(e, qname).and_then(|n| n.parse().ok()).unwrap_or(1)
+}
+
+///
+/// text of `content.xml`, by the root element of the body
+///
+/// repeated empty rows and cells at the end of a sheet are dropped
+fn read_odf(zip: &mut Zip, content: &mut OfficeContent) -> Result<()> {
+    let xml = read_part(zip, "content.xml")?
+        .ok_or_else(|| ApiError::Office(String::from("Err Not Found: content.xml")))?;
+    match content.format {
+        OfficeFormat::Odt => content.paras = Some(read_paras(&xml, &[b"p", b"h"])?),
+        OfficeFormat::Odp => {
+            let mut slides: Vec<OfficeSlide> = vec![];
+            let mut reader = Reader::from_str(&xml);
+            loop {
+                match reader.read_event().map_err(xml_err)? {
+                    Event::Start(e) if e.local_name().as_ref() == b"page" => {
+                        let nm = get_attr(&e, "draw:name");
+                        let end = reader.read_to_end(e.name()).map_err(xml_err)?;
+                        let paras = read_paras(&xml[end.start as usize..end.end as usize], &[b"p", b"h"])?;
+                        slides.push(OfficeSlide { no: slides.len() + 1, nm, paras });
+                    }
+                    Event::Eof => break,
+                    _ => {}
+                }
+            }
+            content.slides = Some(slides);
+        }
+        _ => content.sheets = Some(read_ods(&xml)?),
+    }
+    Ok(())
+}
+
+///
+/// pending empty rows and cells are stored only if something follows,
+/// so thousands of repeated empty cells at the end are dropped
+fn read_ods(xml: &str) -> Result<Vec<OfficeSheet>> {
+    let mut sheets: Vec<OfficeSheet> = vec![];
+    let mut row: Vec<String> = vec![];
+    let (mut row_repeat, mut empty_rows, mut empty_cells) = (1usize, 0usize, 0usize);
+    let mut reader = Reader::from_str(xml);
+    loop {
+        let (text, repeat) = match reader.read_event().map_err(xml_err)? {
+            Event::Start(e) if e.local_name().as_ref() == b"table" => {
+                sheets.push(OfficeSheet { nm: get_attr(&e, "table:name").unwrap_or_default(), ..OfficeSheet::default() });
+                empty_rows = 0;
+                continue;
+            }
+            Event::Start(e) if e.local_name().as_ref() == b"table-row" => {
+                row_repeat = get_repeat(&e, "table:number-rows-repeated");
+                row = vec![];
+                empty_cells = 0;
+                continue;
+            }
+            Event::End(e) if e.local_name().as_ref() == b"table-row" => {
+                let Some(sheet) = sheets.last_mut() else {
+                    continue;
+                };
+                if row.is_empty() {
+                    empty_rows = empty_rows.saturating_add(row_repeat);
+                    continue;
+                }
+                let n = empty_rows.min(OFFICE_ROWS_MAX.saturating_sub(sheet.rows.len()));
+                sheet.rows.extend((0..n).map(|_| vec![]));
+                let n = row_repeat.min(OFFICE_ROWS_MAX.saturating_sub(sheet.rows.len()));
+                sheet.rows.extend((0..n).map(|_| row.clone()));
+                sheet.row_cnt = sheet.row_cnt.saturating_add(empty_rows.saturating_add(row_repeat));
+                empty_rows = 0;
+                continue;
+            }
+            Event::Start(e) if matches!(e.local_name().as_ref(), b"table-cell" | b"covered-table-cell") => {
+                let repeat = get_repeat(&e, "table:number-columns-repeated");
+                let span = reader.read_to_end(e.name()).map_err(xml_err)?;
+                let paras = read_paras(&xml[span.start as usize..span.end as usize], &[b"p", b"h"])?;
+                (paras.join("\n"), repeat)
+            }
+            Event::Empty(e) if matches!(e.local_name().as_ref(), b"table-cell" | b"covered-table-cell") => {
+                (String::new(), get_repeat(&e, "table:number-columns-repeated"))
+            }
+            Event::Eof => break,
+            _ => continue,
+        };
+        if text.is_empty() {
+            empty_cells = empty_cells.saturating_add(repeat);
+            continue;
+        }
+        let n = empty_cells.saturating_add(repeat).min(OFFICE_COLS_MAX.saturating_sub(row.len()));
+        row.extend((0..n).map(|i| if i < empty_cells { String::new() } else { text.clone() }));
+        empty_cells = 0;
+    }
+    Ok(sheets)
+}
+
+///
+/// text of leaf elements by qualified name (`dc:title`), and attributes of `meta:document-statistic`
+fn read_leaf_texts(xml: &str) -> Result<HashMap<String, String>> {
+    let mut texts: HashMap<String, String> = HashMap::new();
+    let mut cur: Option<String> = None;
+    let mut reader = Reader::from_str(xml);
+    loop {
+        match reader.read_event().map_err(xml_err)? {
+            Event::Start(e) => cur = Some(String::from_utf8_lossy(e.name().as_ref()).to_string()),
+            Event::End(_) => cur = None,
+            Event::Empty(e) if e.local_name().as_ref() == b"document-statistic" => {
+                for attr in e.attributes().flatten() {
+                    let key = String::from_utf8_lossy(attr.key.as_ref()).to_string();
+                    texts.insert(key, attr.unescape_value().map_err(xml_err)?.to_string());
+                }
+            }
+            Event::Text(e) => {
+                if let Some(nm) = &cur {
+                    texts.entry(nm.clone()).or_default().push_str(&e.unescape().map_err(xml_err)?);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(texts)
+}
+
+///
+/// `docProps/core.xml` and `docProps/app.xml` of OOXML, `meta.xml` of ODF
+fn read_props(zip: &mut Zip, format: OfficeFormat) -> Result<DocProps> {
+    let mut texts: HashMap<String, String> = HashMap::new();
+    let parts: &[&str] = match format {
+        OfficeFormat::Odt | OfficeFormat::Ods | OfficeFormat::Odp => &["meta.xml"],
+        _ => &["docProps/core.xml", "docProps/app.xml"],
+    };
+    for part in parts {
+        if let Some(xml) = read_part(zip, part)? {
+            texts.extend(read_leaf_texts(&xml)?);
+        }
+    }
+    let mut get = |keys: &[&str]| keys.iter()
+        .find_map(|key| texts.remove(*key))
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+    Ok(DocProps {
+        title: get(&["dc:title"]),
+        subject: get(&["dc:subject"]),
+        author: get(&["meta:initial-creator", "dc:creator"]),
+        modified_by: get(&["cp:lastModifiedBy", "dc:creator"]),
+        created: get(&["dcterms:created", "meta:creation-date"]),
+        modified: get(&["dcterms:modified", "dc:date"]),
+        app: get(&["Application", "meta:generator"]),
+        pages: get(&["Pages", "Slides", "meta:page-count"]).and_then(|v| v.parse().ok()),
+    })
+}
+
+///
+/// text of a docx, xlsx, pptx, odt, ods or odp with the document properties
+///
+/// - paras: docx, odt
+/// - sheets: xlsx, ods. up to `OFFICE_ROWS_MAX` rows each
+/// - slides: pptx, odp
+pub fn read_office(path: &Path) -> Result<OfficeContent> {
+    let format = get_office_format(&path.to_string_lossy())
+        .ok_or_else(|| ApiError::Office(format!("Err Format: {:?}", path)))?;
+    let mut zip = ZipArchive::new(BufReader::new(File::open(path)?))?;
+    let mut content = OfficeContent {
+        path: path.to_string_lossy().to_string(),
+        format,
+        props: read_props(&mut zip, format)?,
+        ..OfficeContent::default()
+    };
+    match format {
+        OfficeFormat::Docx => read_docx(&mut zip, &mut content)?,
+        OfficeFormat::Xlsx => read_xlsx(&mut zip, &mut content)?,
+        OfficeFormat::Pptx => read_pptx(&mut zip, &mut content)?,
+        OfficeFormat::Odt | OfficeFormat::Ods | OfficeFormat::Odp => read_odf(&mut zip, &mut content)?,
+    }
+    Ok(content)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    fn write_package(path: &Path, parts: &[(&str, &str)]) {
+        let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+        for (nm, xml) in parts {
+            zip.start_file(*nm, SimpleFileOptions::default()).unwrap();
+            zip.write_all(xml.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    const CORE: &str = r#"<cp:coreProperties xmlns:cp="cp" xmlns:dc="dc" xmlns:dcterms="dcterms">
+        <dc:title>Report</dc:title><dc:creator>kim</dc:creator><cp:lastModifiedBy>lee</cp:lastModifiedBy>
+        <dcterms:modified>2024-05-01T10:00:00Z</dcterms:modified></cp:coreProperties>"#;
+
+    #[test]
+    fn test_docx() {
+        let tmp = temp_dir();
+        let path = tmp.path().join("office.docx");
+        write_package(&path, &[
+            ("docProps/core.xml", CORE),
+            ("word/document.xml", r#"<w:document xmlns:w="w"><w:body>
+                <w:p><w:r><w:t xml:space="preserve">Hello </w:t></w:r><w:r><w:t>World</w:t><w:tab/><w:t>&amp;</w:t></w:r></w:p>
+                <w:p/><w:p><w:r><w:instrText> PAGE </w:instrText></w:r><w:r><w:t>2</w:t></w:r></w:p>
+            </w:body></w:document>"#),
+        ]);
+        let content = read_office(&path).unwrap();
+        assert_eq!(content.format, OfficeFormat::Docx);
+        assert_eq!(content.paras, Some(vec![String::from("Hello World\t&"), String::new(), String::from("2")]));
+        assert_eq!((content.props.title.as_deref(), content.props.author.as_deref()), (Some("Report"), Some("kim")));
+        assert_eq!((content.props.modified_by.as_deref(), content.props.modified.as_deref()), (Some("lee"), Some("2024-05-01T10:00:00Z")));
+    }
+
+    #[test]
+    fn test_xlsx() {
+        let tmp = temp_dir();
+        let path = tmp.path().join("office.xlsx");
+        write_package(&path, &[
+            ("xl/workbook.xml", r#"<workbook xmlns:r="r"><sheets><sheet name="Q1" sheetId="1" r:id="rId1"/></sheets></workbook>"#),
+            ("xl/_rels/workbook.xml.rels", r#"<Relationships><Relationship Id="rId1" Target="worksheets/sheet1.xml"/></Relationships>"#),
+            ("xl/sharedStrings.xml", r#"<sst><si><t>nm</t></si><si><r><t>ki</t></r><r><t>m</t></r></si></sst>"#),
+            ("xl/worksheets/sheet1.xml", r#"<worksheet><sheetData>
+                <row r="1"><c r="A1" t="s"><v>0</v></c><c r="C1" t="inlineStr"><is><t>ok</t></is></c></row>
+                <row r="3"><c r="A3" t="s"><v>1</v></c><c r="B3"><f>1+1</f><v>2</v></c><c r="C3" t="b"><v>1</v></c></row>
+            </sheetData></worksheet>"#),
+        ]);
+        let content = read_office(&path).unwrap();
+        let sheets = content.sheets.unwrap();
+        assert_eq!((sheets[0].nm.as_str(), sheets[0].row_cnt), ("Q1", 3));
+        assert_eq!(sheets[0].rows, vec![vec!["nm", "", "ok"], vec![], vec!["kim", "2", "TRUE"]]);
+    }
+
+    #[test]
+    fn test_pptx() {
+        let tmp = temp_dir();
+        let path = tmp.path().join("office.pptx");
+        write_package(&path, &[
+            ("docProps/app.xml", r#"<Properties><Application>PowerPoint</Application><Slides>2</Slides></Properties>"#),
+            ("ppt/presentation.xml", r#"<p:presentation xmlns:p="p" xmlns:r="r"><p:sldIdLst>
+                <p:sldId id="257" r:id="rId3"/><p:sldId id="256" r:id="rId2"/></p:sldIdLst></p:presentation>"#),
+            ("ppt/_rels/presentation.xml.rels", r#"<Relationships>
+                <Relationship Id="rId2" Target="slides/slide1.xml"/><Relationship Id="rId3" Target="/ppt/slides/slide2.xml"/></Relationships>"#),
+            ("ppt/slides/slide1.xml", r#"<p:sld xmlns:p="p" xmlns:a="a"><a:p><a:r><a:t>one</a:t></a:r></a:p></p:sld>"#),
+            ("ppt/slides/slide2.xml", r#"<p:sld xmlns:p="p" xmlns:a="a"><a:p><a:r><a:t>two</a:t></a:r><a:br/><a:r><a:t>lines</a:t></a:r></a:p></p:sld>"#),
+        ]);
+        let content = read_office(&path).unwrap();
+        let slides = content.slides.unwrap();
+        assert_eq!(slides.iter().map(|s| (s.no, s.paras.clone())).collect::<Vec<_>>(),
+                   vec![(1, vec![String::from("two\nlines")]), (2, vec![String::from("one")])]);
+        assert_eq!((content.props.app.as_deref(), content.props.pages), (Some("PowerPoint"), Some(2)));
+    }
+
+    #[test]
+    fn test_odf() {
+        let tmp = temp_dir();
+        let path = tmp.path().join("office.ods");
+        write_package(&path, &[
+            ("meta.xml", r#"<office:document-meta xmlns:office="o" xmlns:meta="m" xmlns:dc="dc"><office:meta>
+                <meta:initial-creator>kim</meta:initial-creator><dc:creator>lee</dc:creator>
+                <meta:document-statistic meta:table-count="1"/></office:meta></office:document-meta>"#),
+            ("content.xml", r#"<office:document-content xmlns:office="o" xmlns:table="t" xmlns:text="x"><office:body><office:spreadsheet>
+                <table:table table:name="S1">
+                <table:table-row><table:table-cell><text:p>a<text:s text:c="2"/>b</text:p></table:table-cell>
+                    <table:table-cell table:number-columns-repeated="2"/><table:table-cell><text:p>c</text:p></table:table-cell>
+                    <table:table-cell table:number-columns-repeated="16000"/></table:table-row>
+                <table:table-row table:number-rows-repeated="2"><table:table-cell><text:p>d</text:p></table:table-cell></table:table-row>
+                <table:table-row table:number-rows-repeated="1048000"><table:table-cell table:number-columns-repeated="1024"/></table:table-row>
+                </table:table></office:spreadsheet></office:body></office:document-content>"#),
+        ]);
+        let content = read_office(&path).unwrap();
+        let sheets = content.sheets.unwrap();
+        assert_eq!((sheets[0].nm.as_str(), sheets[0].row_cnt), ("S1", 3));
+        assert_eq!(sheets[0].rows, vec![vec!["a  b", "", "", "c"], vec!["d"], vec!["d"]]);
+        assert_eq!((content.props.author.as_deref(), content.props.modified_by.as_deref()), (Some("kim"), Some("lee")));
+
+        let sheets = read_ods(r#"<table:table table:name="S2">
+            <table:table-row table:number-rows-repeated="18446744073709551615"><table:table-cell/></table:table-row>
+            <table:table-row table:number-rows-repeated="18446744073709551615"><table:table-cell><text:p>e</text:p></table:table-cell></table:table-row>
+            </table:table>"#).unwrap();
+        assert_eq!((sheets[0].row_cnt, sheets[0].rows.len()), (usize::MAX, OFFICE_ROWS_MAX));
+    }
+}
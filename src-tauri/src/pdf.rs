@@ -0,0 +1,198 @@
+use std::path::Path;
+use lopdf::{decode_text_string, Dictionary, Document};
+use crate::models::{ApiError, PdfContent, PdfInfo, PdfOutline, PdfPage};
+use crate::system_time_ext::civil_to_sec;
+
+type Result<T> = std::result::Result<T, ApiError>;
+
+pub const PDF_TAKE_N: usize = 10;
+pub const PDF_TAKE_MAX: usize = 100;
+/// larger files are not opened for the page count of a listing
+const PDF_META_MAX_LEN: u64 = 64 * 1024 * 1024;
+
+pub fn is_pdf(nm: &str) -> bool {
+    nm.to_lowercase().ends_with(".pdf")
+}
+
+fn pdf_err(e: lopdf::Error) -> ApiError {
+    ApiError::Pdf(e.to_string())
+}
+
+///
+/// encrypted documents are opened with the empty user password
+fn load_pdf(path: &Path) -> Result<Document> {
+    let mut doc = Document::load(path).map_err(pdf_err)?;
+    if doc.is_encrypted() {
+        doc.decrypt("").map_err(|_| ApiError::Pdf(format!("Err Encrypted: {:?}", path)))?;
+    }
+    Ok(doc)
+}
+
+///
+/// `/Count` of the page tree, without walking the pages
+fn get_page_cnt(doc: &Document) -> Option<u64> {
+    let pages = doc.catalog().ok()?.get(b"Pages").ok()?;
+    let (_, pages) = doc.dereference(pages).ok()?;
+    pages.as_dict().ok()?.get(b"Count").ok()?.as_i64().ok().map(|cnt| cnt.max(0) as u64)
+}
+
+///
+/// page count for the listing
+pub fn get_pdf_pages(path: &Path) -> Option<u64> {
+    if path.metadata().ok()?.len() > PDF_META_MAX_LEN {
+        return None;
+    }
+    get_page_cnt(&load_pdf(path).ok()?)
+}
+
+///
+/// `D:20240501100000+09'00'` -> unix time
+fn parse_pdf_date(s: &str) -> Option<u64> {
+    let s = s.trim().strip_prefix("D:").unwrap_or(s.trim());
+    let digits: String = s.chars().take_while(|c| c.is_ascii_digit()).collect();
+    let num = |from: usize, len: usize, default: i64| digits.get(from..from + len).and_then(|d| d.parse::<i64>().ok()).unwrap_or(default);
+    if digits.len() < 4 {
+        return None;
+    }
+    let sec = civil_to_sec(num(0, 4, 0), num(4, 2, 1), num(6, 2, 1), num(8, 2, 0), num(10, 2, 0), num(12, 2, 0));
+    // `Z`, `+09'00'` or `-05'00'` after the digits, UTC if missing
+    let tz = &s[digits.len()..];
+    let offset = match tz.chars().next() {
+        Some(sign @ ('+' | '-')) => {
+            let tz_digits: String = tz.chars().filter(|c| c.is_ascii_digit()).collect();
+            let hh = tz_digits.get(0..2).and_then(|d| d.parse::<i64>().ok()).unwrap_or(0);
+            let mm = tz_digits.get(2..4).and_then(|d| d.parse::<i64>().ok()).unwrap_or(0);
+            if sign == '+' { hh * 3600 + mm * 60 } else { -(hh * 3600 + mm * 60) }
+        }
+        _ => 0,
+    };
+    u64::try_from(sec - offset).ok()
+}
+
+fn get_info(doc: &Document) -> PdfInfo {
+    let opt_dict: Option<&Dictionary> = doc.trailer.get(b"Info").ok()
+        .and_then(|info| doc.dereference(info).ok())
+        .and_then(|(_, info)| info.as_dict().ok());
+    let Some(dict) = opt_dict else {
+        return PdfInfo::default();
+    };
+    let get = |key: &[u8]| dict.get(key).ok()
+        .and_then(|obj| doc.dereference(obj).ok())
+        .and_then(|(_, obj)| decode_text_string(obj).ok())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty());
+    PdfInfo {
+        title: get(b"Title"),
+        author: get(b"Author"),
+        subject: get(b"Subject"),
+        keywords: get(b"Keywords"),
+        creator: get(b"Creator"),
+        producer: get(b"Producer"),
+        created: get(b"CreationDate").and_then(|s| parse_pdf_date(&s)),
+        modified: get(b"ModDate").and_then(|s| parse_pdf_date(&s)),
+    }
+}
+
+fn get_outline(doc: &Document) -> Vec<PdfOutline> {
+    // no outline is an error of lopdf
+    match doc.get_toc() {
+        Ok(toc) => toc.toc.into_iter()
+            .map(|item| PdfOutline { lvl: item.level, title: item.title, page: item.page })
+            .collect(),
+        Err(_) => vec![],
+    }
+}
+
+///
+/// page count, document info, outline and text of `page_nos` (1-based)
+///
+/// text of a page that can not be extracted is `None` with the error
+pub fn read_pdf(path: &Path, page_nos: &[u32]) -> Result<PdfContent> {
+    let doc = load_pdf(path)?;
+    let page_cnt = doc.get_pages().len() as u64;
+    let pages = page_nos.iter()
+        .filter(|no| **no >= 1 && **no as u64 <= page_cnt)
+        .map(|no| match doc.extract_text(&[*no]) {
+            Ok(text) => PdfPage { no: *no, text: Some(text), err: None },
+            Err(e) => PdfPage { no: *no, text: None, err: Some(e.to_string()) },
+        })
+        .collect();
+    Ok(PdfContent {
+        path: path.to_string_lossy().to_string(),
+        version: doc.version.clone(),
+        page_cnt,
+        info: get_info(&doc),
+        outline: get_outline(&doc),
+        pages,
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use lopdf::content::{Content, Operation};
+    use lopdf::{dictionary, Object, Stream};
+
+    fn write_pdf(path: &Path, texts: &[&str]) {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font", "Subtype" => "Type1", "BaseFont" => "Courier",
+        });
+        let resources_id = doc.add_object(dictionary! { "Font" => dictionary! { "F1" => font_id } });
+        let mut kids: Vec<Object> = vec![];
+        for text in texts {
+            let content = Content {
+                operations: vec![
+                    Operation::new("BT", vec![]),
+                    Operation::new("Tf", vec!["F1".into(), 24.into()]),
+                    Operation::new("Td", vec![100.into(), 600.into()]),
+                    Operation::new("Tj", vec![Object::string_literal(*text)]),
+                    Operation::new("ET", vec![]),
+                ],
+            };
+            let content_id = doc.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
+            let page_id = doc.add_object(dictionary! {
+                "Type" => "Page", "Parent" => pages_id, "Contents" => content_id,
+            });
+            kids.push(page_id.into());
+        }
+        doc.objects.insert(pages_id, Object::Dictionary(dictionary! {
+            "Type" => "Pages", "Count" => kids.len() as i64, "Kids" => kids,
+            "Resources" => resources_id, "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+        }));
+        let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        let info_id = doc.add_object(dictionary! {
+            "Title" => Object::string_literal("Report"),
+            "CreationDate" => Object::string_literal("D:20240501100000+09'00'"),
+        });
+        doc.trailer.set("Root", catalog_id);
+        doc.trailer.set("Info", info_id);
+        doc.save(path).unwrap();
+    }
+
+    #[test]
+    fn test_read_pdf() {
+        let tmp = temp_dir();
+        let path = tmp.path().join("pdf.pdf");
+        write_pdf(&path, &["Hello", "World"]);
+        assert_eq!(get_pdf_pages(&path), Some(2));
+        let content = read_pdf(&path, &[2, 3]).unwrap();
+        assert_eq!(content.page_cnt, 2);
+        assert_eq!(content.info.title.as_deref(), Some("Report"));
+        assert_eq!(content.info.created, Some(1714525200));
+        assert_eq!(content.pages.len(), 1);
+        assert_eq!((content.pages[0].no, content.pages[0].text.as_deref().map(|t| t.trim())), (2, Some("World")));
+        assert!(content.outline.is_empty());
+    }
+
+    #[test]
+    fn test_parse_pdf_date() {
+        assert_eq!(parse_pdf_date("D:20240501010000Z"), Some(1714525200));
+        assert_eq!(parse_pdf_date("D:20240430200000-05'00'"), Some(1714525200));
+        assert_eq!(parse_pdf_date("D:2024"), Some(1704067200));
+        assert_eq!(parse_pdf_date("x"), None);
+    }
+}
@@ -0,0 +1,216 @@
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use crate::models::{AccessPolicy, ApiError};
+use crate::stream::is_under_roots;
+
+type Result<T> = std::result::Result<T, ApiError>;
+
+pub const POLICY_ENV: &str = "TR_VIEWER_POLICY";
+
+pub fn get_policy_path() -> Option<PathBuf> {
+    match std::env::var_os(POLICY_ENV) {
+        Some(path) => Some(PathBuf::from(path)),
+        None => dirs_next::config_dir().map(|dir| dir.join("tr-viewer").join("policy.json")),
+    }
+}
+
+///
+/// `\\?\C:\a` -> `C:\a`, `\\?\UNC\host\a` -> `\\host\a` of `canonicalize` on windows
+fn strip_verbatim(path: PathBuf) -> PathBuf {
+    let path_str = path.to_string_lossy();
+    if let Some(rest) = path_str.strip_prefix(r"\\?\UNC\") {
+        return PathBuf::from(format!(r"\\{}", rest));
+    }
+    if let Some(rest) = path_str.strip_prefix(r"\\?\") {
+        return PathBuf::from(rest);
+    }
+    path
+}
+
+///
+/// absolute path with `..` and symlinks resolved.
+/// the existing part is canonicalized, the rest as written such as a new file or a file in an archive
+pub fn resolve_path(path: &Path) -> io::Result<PathBuf> {
+    let abs = std::path::absolute(path)?;
+    for base in abs.ancestors() {
+        let Ok(mut resolved) = std::fs::canonicalize(base) else {
+            continue;
+        };
+        let rest = abs.strip_prefix(base).map_err(io::Error::other)?;
+        for component in rest.components() {
+            match component {
+                Component::ParentDir => {
+                    resolved.pop();
+                }
+                Component::Normal(nm) => resolved.push(nm),
+                _ => {}
+            }
+        }
+        return Ok(strip_verbatim(resolved));
+    }
+    Err(io::Error::new(io::ErrorKind::NotFound, format!("Err Resolve: {}", abs.to_string_lossy())))
+}
+
+///
+/// absolute path with `..` removed as written, of a path not on any existing drive
+fn normalize_path(path: &Path) -> io::Result<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in std::path::absolute(path)?.components() {
+        match component {
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::CurDir => {}
+            component => normalized.push(component),
+        }
+    }
+    Ok(strip_verbatim(normalized))
+}
+
+///
+/// resolved roots of `AccessPolicy`. a path is checked before any IO
+#[derive(Debug, Default)]
+pub struct Policy {
+    conf: AccessPolicy,
+    allow: Vec<PathBuf>,
+    deny: Vec<PathBuf>,
+    // an invalid policy file denies everything rather than nothing
+    err: Option<String>,
+}
+
+impl Policy {
+    pub fn new(conf: AccessPolicy) -> Self {
+        // a root not resolved now such as of an unmounted drive is kept as written
+        let resolve = |roots: &[String]| -> Vec<PathBuf> {
+            roots.iter().filter_map(|root| {
+                let root = Path::new(root);
+                resolve_path(root).or_else(|_| normalize_path(root)).ok()
+            }).collect()
+        };
+        Policy {
+            allow: resolve(&conf.allow),
+            deny: resolve(&conf.deny),
+            conf,
+            err: None,
+        }
+    }
+
+    ///
+    /// policy of `get_policy_path`, no policy if the file is missing
+    pub fn load() -> Self {
+        let Some(path) = get_policy_path() else {
+            return Policy::default();
+        };
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Policy::default(),
+            Err(e) => return Policy::invalid(e.to_string()),
+        };
+        match serde_json::from_str::<AccessPolicy>(&text) {
+            Ok(conf) => Policy::new(conf),
+            Err(e) => Policy::invalid(e.to_string()),
+        }
+    }
+
+    fn invalid(err: String) -> Self {
+        Policy {
+            conf: AccessPolicy { read_only: true, ..AccessPolicy::default() },
+            err: Some(err),
+            ..Policy::default()
+        }
+    }
+
+    pub fn get_conf(&self) -> AccessPolicy {
+        self.conf.clone()
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.conf.read_only
+    }
+
+    ///
+    /// `path` is resolved
+    pub fn is_allowed(&self, path: &Path) -> bool {
+        if self.err.is_some() || is_under_roots(path, &self.deny) {
+            return false;
+        }
+        // an allow list of missing roots allows nothing
+        self.conf.allow.is_empty() || is_under_roots(path, &self.allow)
+    }
+
+    pub fn check_read(&self, path_str: &str) -> Result<PathBuf> {
+        if let Some(err) = &self.err {
+            return Err(ApiError::Policy(format!("Err Invalid Policy: {}", err)));
+        }
+        let path = resolve_path(Path::new(path_str))?;
+        if !self.is_allowed(&path) {
+            return Err(ApiError::Policy(format!("Err Not Allowed: {}", path_str)));
+        }
+        Ok(path)
+    }
+
+    pub fn check_write(&self, path_str: &str) -> Result<PathBuf> {
+        if self.is_read_only() {
+            return Err(ApiError::Policy(format!("Err Read Only: {}", path_str)));
+        }
+        self.check_read(path_str)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn test_policy() {
+        let tmp = temp_dir();
+        let base = tmp.path().to_path_buf();
+        std::fs::create_dir_all(base.join("pub").join("private")).unwrap();
+        std::fs::create_dir_all(base.join("secret")).unwrap();
+        std::fs::write(base.join("secret").join("a.txt"), "a").unwrap();
+        let path_str = |path: PathBuf| path.to_string_lossy().to_string();
+
+        let policy = Policy::new(AccessPolicy {
+            allow: vec![path_str(base.join("pub"))],
+            deny: vec![path_str(base.join("pub").join("private"))],
+            read_only: false,
+        });
+        assert!(policy.check_read(&path_str(base.join("pub").join("new.txt"))).is_ok());
+        assert!(policy.check_write(&path_str(base.join("pub").join("a.zip").join("b.txt"))).is_ok());
+        let err = policy.check_read(&path_str(base.join("pub").join("..").join("secret").join("a.txt")));
+        assert!(matches!(err, Err(ApiError::Policy(_))));
+        assert!(policy.check_read(&path_str(base.join("pub").join("private").join("b.txt"))).is_err());
+        assert!(policy.check_read(&path_str(base.join("pub2"))).is_err());
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(base.join("secret"), base.join("pub").join("link")).unwrap();
+            assert!(policy.check_read(&path_str(base.join("pub").join("link").join("a.txt"))).is_err());
+        }
+
+        let policy = Policy::new(AccessPolicy { read_only: true, ..AccessPolicy::default() });
+        assert!(policy.check_read(&path_str(base.join("secret").join("a.txt"))).is_ok());
+        assert!(policy.check_write(&path_str(base.join("secret").join("a.txt"))).is_err());
+        assert!(Policy::invalid(String::from("err")).check_read(&path_str(base)).is_err());
+    }
+
+    #[test]
+    fn test_strip_verbatim() {
+        assert_eq!(strip_verbatim(PathBuf::from(r"\\?\C:\a\b.txt")), PathBuf::from(r"C:\a\b.txt"));
+        assert_eq!(strip_verbatim(PathBuf::from(r"\\?\UNC\host\share\a")), PathBuf::from(r"\\host\share\a"));
+        assert_eq!(strip_verbatim(PathBuf::from("/data/a")), PathBuf::from("/data/a"));
+    }
+
+    #[test]
+    fn test_normalize_path() {
+        let abs = std::path::absolute("a").unwrap();
+        assert_eq!(normalize_path(Path::new("a/./b/../c")).unwrap(), abs.join("c"));
+        #[cfg(windows)]
+        assert_eq!(normalize_path(Path::new(r"Q:\secret\..\private")).unwrap(), PathBuf::from(r"Q:\private"));
+
+        let policy = Policy::new(AccessPolicy { deny: vec![String::from("missing/private")], ..AccessPolicy::default() });
+        assert!(!policy.is_allowed(&std::path::absolute("missing").unwrap().join("private").join("a.txt")));
+    }
+}
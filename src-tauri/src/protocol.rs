@@ -0,0 +1,127 @@
+use std::io::SeekFrom;
+use tauri::http::{header, Method, Request, Response, StatusCode};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use crate::api::get_instance;
+use crate::stream::{decode_uri_path, get_etag, get_stream_mimetype, parse_range, STREAM_CHUNK_LEN, STREAM_FULL_MAX};
+use crate::thumb::parse_thumb_uri;
+
+fn error_response(status: StatusCode, msg: String) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/plain")
+        .body(msg.into_bytes())
+        .unwrap_or_default()
+}
+
+///
+/// `thumb://localhost/{path}?size=256`
+///
+/// revalidated by ETag of path + mtime + size, `304 Not Modified` if unchanged
+pub async fn get_thumb_response(request: Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let (path_str, size) = match parse_thumb_uri(request.uri().path(), request.uri().query()) {
+        Some(v) => v,
+        None => return error_response(StatusCode::BAD_REQUEST, format!("Err Uri: {}", request.uri())),
+    };
+    let etag = match get_instance().get_thumb_key(&path_str, size).await {
+        Ok(key) => format!("\"{}\"", key),
+        Err(e) => return error_response(StatusCode::NOT_FOUND, e.to_string()),
+    };
+    let if_none_match = request.headers().get(header::IF_NONE_MATCH).and_then(|v| v.to_str().ok());
+    if if_none_match == Some(etag.as_str()) {
+        return Response::builder()
+            .status(StatusCode::NOT_MODIFIED)
+            .header(header::ETAG, &etag)
+            .header(header::CACHE_CONTROL, "no-cache")
+            .body(vec![])
+            .unwrap_or_default();
+    }
+    match get_instance().get_thumb(&path_str, size).await {
+        Ok(thumb) => Response::builder()
+            .header(header::CONTENT_TYPE, thumb.mimetype)
+            .header(header::ETAG, &etag)
+            .header(header::CACHE_CONTROL, "no-cache")
+            .body(thumb.body)
+            .unwrap_or_default(),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+async fn read_range(path: &std::path::Path, start: u64, len: u64) -> std::io::Result<Vec<u8>> {
+    let mut file = tokio::fs::File::open(path).await?;
+    file.seek(SeekFrom::Start(start)).await?;
+    let mut body = Vec::with_capacity(len as usize);
+    file.take(len).read_to_end(&mut body).await?;
+    Ok(body)
+}
+
+///
+/// `stream://localhost/{path}`
+///
+/// only files directly in the folders listed by `read_folder`, drive roots too, and images of
+/// rendered documents. `206 Partial Content` for `Range` up to `STREAM_CHUNK_LEN`.
+/// without a range `200 OK` with the whole file, or over `STREAM_FULL_MAX` `206 Partial Content`
+/// with the first chunk, as a media element asks for the rest by `Content-Range`.
+/// revalidated by ETag of mtime + size
+pub async fn get_stream_response(request: Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let Some(path_str) = decode_uri_path(request.uri().path()) else {
+        return error_response(StatusCode::BAD_REQUEST, format!("Err Uri: {}", request.uri()));
+    };
+    let Some(path) = get_instance().get_opened_file(&path_str).await else {
+        return error_response(StatusCode::FORBIDDEN, format!("Err Not Opened: {}", path_str));
+    };
+    let meta = match path.metadata() {
+        Ok(meta) => meta,
+        Err(e) => return error_response(StatusCode::NOT_FOUND, e.to_string()),
+    };
+    let sz = meta.len();
+    let etag = get_etag(&meta);
+    let get_header = |nm: header::HeaderName| request.headers().get(nm).and_then(|v| v.to_str().ok());
+    if get_header(header::IF_NONE_MATCH) == Some(etag.as_str()) {
+        return Response::builder()
+            .status(StatusCode::NOT_MODIFIED)
+            .header(header::ETAG, &etag)
+            .body(vec![])
+            .unwrap_or_default();
+    }
+    // a range of a changed file is not resumed
+    let opt_range = match get_header(header::IF_RANGE) {
+        Some(if_range) if if_range != etag => None,
+        _ => parse_range(get_header(header::RANGE), sz),
+    };
+    let (status, start, end) = match opt_range {
+        Some(Ok((start, end))) => (StatusCode::PARTIAL_CONTENT, start, end),
+        Some(Err(())) => return Response::builder()
+            .status(StatusCode::RANGE_NOT_SATISFIABLE)
+            .header(header::CONTENT_RANGE, format!("bytes */{}", sz))
+            .body(vec![])
+            .unwrap_or_default(),
+        // a large file is not loaded for a client that does not range
+        None if sz > STREAM_FULL_MAX => (StatusCode::PARTIAL_CONTENT, 0, STREAM_CHUNK_LEN - 1),
+        None => (StatusCode::OK, 0, sz.saturating_sub(1)),
+    };
+    let len = if sz == 0 { 0 } else { end - start + 1 };
+    let sample = match read_range(&path, 0, 512.min(sz)).await {
+        Ok(sample) => sample,
+        Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+    let body = if request.method() == Method::HEAD {
+        vec![]
+    } else {
+        match read_range(&path, start, len).await {
+            Ok(body) => body,
+            Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        }
+    };
+
+    let mut builder = Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, get_stream_mimetype(&path, &sample))
+        .header(header::CONTENT_LENGTH, len)
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::ETAG, &etag)
+        .header(header::CACHE_CONTROL, "no-cache");
+    if status == StatusCode::PARTIAL_CONTENT {
+        builder = builder.header(header::CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, sz));
+    }
+    builder.body(body).unwrap_or_default()
+}
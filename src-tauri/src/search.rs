@@ -0,0 +1,257 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+use regex::{Regex, RegexBuilder};
+use crate::models::{ApiError, SearchOpts, TextMatch, TextSearch};
+
+type Result<T> = std::result::Result<T, ApiError>;
+
+pub const SEARCH_TAKE_N: usize = 100;
+pub const SEARCH_TAKE_MAX: usize = 1000;
+pub const SEARCH_CTX_MAX: usize = 10;
+/// longer lines are split, a match across the split is not found
+const SEARCH_LINE_MAX: usize = 1024 * 1024;
+/// chars of a line in `text` and the context
+const PREVIEW_LEN: usize = 500;
+const PREVIEW_BEFORE: usize = 100;
+
+fn build_regex(pattern: &str, opts: &SearchOpts) -> Result<Regex> {
+    let mut pattern = if opts.literal.unwrap_or(false) { regex::escape(pattern) } else { pattern.to_string() };
+    if opts.whole_word.unwrap_or(false) {
+        pattern = format!(r"\b(?:{})\b", pattern);
+    }
+    RegexBuilder::new(&pattern)
+        .case_insensitive(opts.ignore_case.unwrap_or(false))
+        .build()
+        .map_err(|e| ApiError::Search(e.to_string()))
+}
+
+///
+/// reads a line with its line ending up to `SEARCH_LINE_MAX` bytes.
+/// utf-16 is read by code units so that `\n` is not found inside a character
+fn read_line(reader: &mut impl BufRead, encoding: &'static Encoding, buf: &mut Vec<u8>) -> std::io::Result<usize> {
+    buf.clear();
+    let nl: [u8; 2] = if encoding == UTF_16LE {
+        [b'\n', 0]
+    } else if encoding == UTF_16BE {
+        [0, b'\n']
+    } else {
+        return reader.take(SEARCH_LINE_MAX as u64).read_until(b'\n', buf);
+    };
+    let mut unit = [0u8; 2];
+    while buf.len() < SEARCH_LINE_MAX {
+        match reader.read(&mut unit[..1])? {
+            0 => break,
+            _ => buf.push(unit[0]),
+        }
+        if reader.read(&mut unit[1..])? == 0 {
+            break;
+        }
+        buf.push(unit[1]);
+        if unit == nl {
+            break;
+        }
+    }
+    Ok(buf.len())
+}
+
+///
+/// bytes of `text` in the encoding of the file
+fn byte_len(text: &str, encoding: &'static Encoding) -> usize {
+    if encoding == UTF_8 {
+        text.len()
+    } else if encoding == UTF_16LE || encoding == UTF_16BE {
+        text.encode_utf16().count() * 2
+    } else {
+        encoding.encode(text).0.len()
+    }
+}
+
+fn utf16_len(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+
+fn cut_preview(line: &str) -> String {
+    line.chars().take(PREVIEW_LEN).collect()
+}
+
+///
+/// (text, pos, len) of the line around the match at `start..end`, in utf-16
+fn to_preview(line: &str, start: usize, end: usize) -> (String, usize, usize) {
+    if line.chars().count() <= PREVIEW_LEN {
+        return (line.to_string(), utf16_len(&line[..start]), utf16_len(&line[start..end]));
+    }
+    let from = line[..start].char_indices().rev().nth(PREVIEW_BEFORE - 1).map(|(i, _)| i).unwrap_or(0);
+    let text: String = line[from..].chars().take(PREVIEW_LEN).collect();
+    let len = utf16_len(&line[start..end.min(from + text.len())]);
+    (text, utf16_len(&line[from..start]), len)
+}
+
+///
+/// regex search of a file by lines, decoded in `encoding`.
+/// stops after `take_n` matches at the end of the line, and checks `cancel` between lines
+pub fn search_file(path: &Path, pattern: &str, encoding: &'static Encoding, opts: &SearchOpts,
+                   cancel: &AtomicBool) -> Result<TextSearch> {
+    let re = build_regex(pattern, opts)?;
+    let sz = path.metadata()?.len();
+    let take_n = opts.take_n.unwrap_or(SEARCH_TAKE_N).min(SEARCH_TAKE_MAX);
+    let ctx = opts.ctx.unwrap_or(0).min(SEARCH_CTX_MAX);
+    let mut offset = opts.offset.unwrap_or(0);
+    let mut line_no = opts.line.unwrap_or(1);
+    let mut file = File::open(path)?;
+    if offset == 0 {
+        let mut bom = [0u8; 3];
+        let n = file.read(&mut bom)?;
+        offset = Encoding::for_bom(&bom[..n]).map(|(_, len)| len as u64).unwrap_or(0);
+    }
+    file.seek(SeekFrom::Start(offset))?;
+    let mut reader = BufReader::with_capacity(1024 * 1024, file);
+
+    let mut matches: Vec<TextMatch> = vec![];
+    let mut before: VecDeque<String> = VecDeque::with_capacity(ctx);
+    // matches waiting for the lines after them
+    let mut pending = 0;
+    let mut next: Option<(u64, u64)> = None;
+    let mut is_cancelled = false;
+    let mut buf: Vec<u8> = Vec::new();
+    loop {
+        if cancel.load(Ordering::Relaxed) {
+            is_cancelled = true;
+            next = Some((offset, line_no));
+            break;
+        }
+        let n = read_line(&mut reader, encoding, &mut buf)?;
+        if n == 0 {
+            break;
+        }
+        let (decoded, _) = encoding.decode_without_bom_handling(&buf);
+        let line = decoded.trim_end_matches(['\n', '\r']);
+
+        for m in matches[pending..].iter_mut() {
+            if m.after.len() < ctx {
+                m.after.push(cut_preview(line));
+            }
+        }
+        while pending < matches.len() && matches[pending].after.len() >= ctx {
+            pending += 1;
+        }
+        if next.is_some() {
+            // only the context after the last page
+            if pending >= matches.len() {
+                break;
+            }
+            continue;
+        }
+
+        for found in re.find_iter(line) {
+            if found.is_empty() {
+                continue;
+            }
+            let (text, pos, len) = to_preview(line, found.start(), found.end());
+            matches.push(TextMatch {
+                line: line_no,
+                col: utf16_len(&line[..found.start()]) + 1,
+                offset: offset + byte_len(&line[..found.start()], encoding) as u64,
+                byte_len: byte_len(found.as_str(), encoding),
+                text,
+                pos,
+                len,
+                before: before.iter().cloned().collect(),
+                after: vec![],
+            });
+        }
+        if ctx > 0 {
+            if before.len() == ctx {
+                before.pop_front();
+            }
+            before.push_back(cut_preview(line));
+        }
+        offset += n as u64;
+        // a part of a long line has no line ending
+        if decoded.ends_with('\n') {
+            line_no += 1;
+        }
+        if matches.len() >= take_n {
+            next = Some((offset, line_no));
+            if ctx == 0 {
+                break;
+            }
+        }
+    }
+    // no more lines after the page
+    if next.is_some_and(|(offset, _)| offset >= sz) {
+        next = None;
+    }
+
+    Ok(TextSearch {
+        path: path.to_string_lossy().to_string(),
+        pattern: pattern.to_string(),
+        enc: encoding.name().to_string(),
+        sz,
+        matches,
+        next_offset: next.map(|(offset, _)| offset),
+        next_line: next.map(|(_, line)| line),
+        scanned: next.map(|(offset, _)| offset).unwrap_or(sz),
+        is_cancelled,
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn test_search_file() {
+        let tmp = temp_dir();
+        let path = tmp.path().join("search.log");
+        let text = "\u{feff}INFO start\r\nERROR disk 한글 full\nINFO ok\nerror net\nINFO end\n";
+        std::fs::write(&path, text).unwrap();
+        let cancel = AtomicBool::new(false);
+
+        let opts = SearchOpts { ignore_case: Some(true), ctx: Some(1), take_n: Some(1), ..SearchOpts::default() };
+        let res = search_file(&path, "error", UTF_8, &opts, &cancel).unwrap();
+        assert_eq!(res.matches.len(), 1);
+        let m = &res.matches[0];
+        assert_eq!((m.line, m.col, m.offset, m.byte_len), (2, 1, 15, 5));
+        assert_eq!((m.before.clone(), m.after.clone()), (vec![String::from("INFO start")], vec![String::from("INFO ok")]));
+        assert_eq!((res.next_offset, res.next_line), (Some(38), Some(3)));
+
+        let opts = SearchOpts { offset: res.next_offset, line: res.next_line, ..opts };
+        let res = search_file(&path, "error", UTF_8, &opts, &cancel).unwrap();
+        assert_eq!((res.matches[0].line, res.matches[0].offset, res.next_offset), (4, 46, Some(56)));
+
+        let opts = SearchOpts { literal: Some(true), whole_word: Some(true), ..SearchOpts::default() };
+        let m = &search_file(&path, "full", UTF_8, &opts, &cancel).unwrap().matches[0];
+        assert_eq!((m.col, m.offset, m.pos), (15, 15 + 18, 14));
+        assert!(build_regex("(", &SearchOpts::default()).is_err());
+
+        cancel.store(true, Ordering::Relaxed);
+        let res = search_file(&path, "full", UTF_8, &SearchOpts::default(), &cancel).unwrap();
+        assert!(res.is_cancelled && res.matches.is_empty());
+    }
+
+    #[test]
+    fn test_search_utf16() {
+        let tmp = temp_dir();
+        let path = tmp.path().join("search_utf16.txt");
+        let mut buf = vec![0xFF, 0xFE];
+        buf.extend("a\n\u{0A0A}b\nfoo b\n".encode_utf16().flat_map(|u| u.to_le_bytes()));
+        std::fs::write(&path, buf).unwrap();
+        let res = search_file(&path, "b", UTF_16LE, &SearchOpts::default(), &AtomicBool::new(false)).unwrap();
+        assert_eq!(res.matches.iter().map(|m| (m.line, m.col, m.offset)).collect::<Vec<_>>(), vec![(2, 2, 8), (3, 5, 20)]);
+    }
+
+    #[test]
+    fn test_to_preview() {
+        let line = format!("{}needle{}", "가".repeat(300), "x".repeat(300));
+        let start = line.find("needle").unwrap();
+        let (text, pos, len) = to_preview(&line, start, start + 6);
+        assert_eq!((text.chars().count(), pos, len), (PREVIEW_BEFORE + 6 + 300, PREVIEW_BEFORE, 6));
+        assert!(text[text.char_indices().nth(pos).unwrap().0..].starts_with("needle"));
+    }
+}
@@ -0,0 +1,216 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::models::ApiError;
+
+type Result<T> = std::result::Result<T, ApiError>;
+
+pub const SETTINGS_VERSION: u64 = 1;
+
+pub fn get_settings_path() -> Option<PathBuf> {
+    dirs_next::config_dir().map(|dir| dir.join("tr-viewer").join("settings.json"))
+}
+
+///
+/// `namespace.name` such as `folder.listOrder`, ascii letters, digits, `_` and `-`
+pub fn check_key(key: &str) -> Result<()> {
+    let is_valid = match key.split_once('.') {
+        Some((ns, nm)) => !ns.is_empty() && !nm.is_empty() && key.split('.').all(|part| {
+            !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        }),
+        None => false,
+    };
+    if !is_valid {
+        return Err(ApiError::Settings(format!("Err Key: {}", key)));
+    }
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct SettingsDoc {
+    // 0 for a file written before the versions
+    #[serde(default)]
+    version: u64,
+    values: BTreeMap<String, Value>,
+}
+
+fn get_version(doc: &Value) -> u64 {
+    doc.get("version").and_then(|v| v.as_u64()).unwrap_or(0)
+}
+
+///
+/// the document of version `from` brought to `SETTINGS_VERSION` one version at a time.
+///
+/// - 0: without `version`, the values are kept as they are
+fn migrate(mut doc: Value, from: u64) -> Result<Value> {
+    for version in from..SETTINGS_VERSION {
+        doc = match version {
+            0 => doc,
+            _ => return Err(ApiError::Settings(format!("Err Version: {}", version))),
+        };
+    }
+    let Value::Object(map) = &mut doc else {
+        return Err(ApiError::Settings(String::from("Err Not Object")));
+    };
+    map.insert(String::from("version"), Value::from(SETTINGS_VERSION));
+    Ok(doc)
+}
+
+///
+/// settings of a json file, changed in memory and written by `take_snapshot`.
+/// without a path they are kept in memory only
+#[derive(Debug, Default)]
+pub struct Settings {
+    path: Option<PathBuf>,
+    doc: SettingsDoc,
+    is_dirty: bool,
+}
+
+impl Settings {
+    fn new(path: Option<PathBuf>) -> Self {
+        Settings {
+            path,
+            doc: SettingsDoc { version: SETTINGS_VERSION, ..SettingsDoc::default() },
+            is_dirty: false,
+        }
+    }
+
+    ///
+    /// a broken file is kept as `settings.json.bad` and the settings start empty.
+    /// a file of an older version is migrated and written on the next save,
+    /// a file of a newer version is read as it is and not written
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let Some(text) = path.as_deref().and_then(|path| std::fs::read_to_string(path).ok()) else {
+            return Settings::new(path);
+        };
+        let parsed = serde_json::from_str::<Value>(&text).map_err(ApiError::from);
+        let from = parsed.as_ref().map(get_version).unwrap_or(SETTINGS_VERSION);
+        if from > SETTINGS_VERSION {
+            let doc = parsed.ok().and_then(|doc| serde_json::from_value(doc).ok()).unwrap_or_default();
+            return Settings { path: None, doc, is_dirty: false };
+        }
+        let migrated = parsed.and_then(|doc| migrate(doc, from))
+            .and_then(|doc| serde_json::from_value::<SettingsDoc>(doc).map_err(ApiError::from));
+        match migrated {
+            Ok(doc) => Settings { path, doc, is_dirty: from < SETTINGS_VERSION },
+            Err(_) => {
+                if let Some(path) = &path {
+                    let _ = std::fs::rename(path, path.with_extension("json.bad"));
+                }
+                Settings::new(path)
+            }
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<Value> {
+        self.doc.values.get(key).cloned()
+    }
+
+    ///
+    /// values of the keys starting with `prefix`
+    pub fn list(&self, prefix: &str) -> BTreeMap<String, Value> {
+        self.doc.values.range(prefix.to_string()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .map(|(key, val)| (key.clone(), val.clone()))
+            .collect()
+    }
+
+    ///
+    /// sets or removes the value and returns the old one
+    pub fn set(&mut self, key: &str, opt_val: Option<Value>) -> Result<Option<Value>> {
+        check_key(key)?;
+        let old = match opt_val {
+            Some(val) => self.doc.values.insert(key.to_string(), val),
+            None => self.doc.values.remove(key),
+        };
+        self.is_dirty = true;
+        Ok(old)
+    }
+
+    ///
+    /// the path and the content to write if changed since the last snapshot
+    pub fn take_snapshot(&mut self) -> Result<Option<(PathBuf, Vec<u8>)>> {
+        let Some(path) = self.path.clone().filter(|_| self.is_dirty) else {
+            return Ok(None);
+        };
+        let buf = serde_json::to_vec_pretty(&self.doc)?;
+        self.is_dirty = false;
+        Ok(Some((path, buf)))
+    }
+
+    ///
+    /// marks the settings to be written again after a failed write
+    pub fn set_dirty(&mut self) {
+        self.is_dirty = true;
+    }
+}
+
+///
+/// writes a temp file and renames it over the original
+pub fn write_atomic(path: &Path, buf: &[u8]) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let file_name = path.file_name().map(|nm| nm.to_string_lossy().to_string()).unwrap_or_default();
+    let tmp_path = path.with_file_name(format!(".{}.{}.tmp", file_name, std::process::id()));
+    let res = (|| {
+        let mut file = std::fs::File::create(&tmp_path)?;
+        file.write_all(buf)?;
+        file.sync_all()?;
+        std::fs::rename(&tmp_path, path)
+    })();
+    if let Err(e) = res {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use serde_json::json;
+
+    #[test]
+    fn test_settings() {
+        let tmp = temp_dir();
+        let path = tmp.path().join("tr-viewer").join("settings.json");
+
+        let mut settings = Settings::load(Some(path.clone()));
+        assert!(settings.take_snapshot().unwrap().is_none());
+        assert_eq!(settings.set("folder.listOrder", Some(json!([{"nm": "Nm", "asc": "Asc"}]))).unwrap(), None);
+        assert_eq!(settings.set("folder.view", Some(json!("list"))).unwrap(), None);
+        assert_eq!(settings.set("viewer.wrap", Some(json!(true))).unwrap(), None);
+        assert_eq!(settings.set("viewer.wrap", None).unwrap(), Some(json!(true)));
+        assert!(settings.set("nokey", Some(json!(1))).is_err());
+        assert!(settings.set("a..b", Some(json!(1))).is_err());
+        let (snap_path, buf) = settings.take_snapshot().unwrap().unwrap();
+        assert!(settings.take_snapshot().unwrap().is_none());
+        write_atomic(&snap_path, &buf).unwrap();
+
+        let settings = Settings::load(Some(path.clone()));
+        assert_eq!(settings.get("folder.view"), Some(json!("list")));
+        assert_eq!(settings.list("folder.").len(), 2);
+        assert_eq!(settings.get("viewer.wrap"), None);
+
+        std::fs::write(&path, "{").unwrap();
+        assert!(Settings::load(Some(path.clone())).list("").is_empty());
+        assert!(path.with_extension("json.bad").exists());
+
+        std::fs::write(&path, r#"{"values": {"folder.view": "grid"}}"#).unwrap();
+        let mut settings = Settings::load(Some(path.clone()));
+        assert_eq!(settings.get("folder.view"), Some(json!("grid")));
+        let (_, buf) = settings.take_snapshot().unwrap().unwrap();
+        assert_eq!(serde_json::from_slice::<Value>(&buf).unwrap()["version"], json!(SETTINGS_VERSION));
+
+        std::fs::write(&path, r#"{"version": 99, "values": {"app.theme": "dark"}}"#).unwrap();
+        let mut settings = Settings::load(Some(path.clone()));
+        settings.set("app.theme", Some(json!("light"))).unwrap();
+        assert!(settings.take_snapshot().unwrap().is_none());
+        assert!(std::fs::read_to_string(&path).unwrap().contains("dark"));
+    }
+}
@@ -0,0 +1,296 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use rusqlite::types::{Value, ValueRef};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use rusqlite::{params_from_iter, Connection, OpenFlags};
+use crate::archive::split_container_path;
+use crate::models::{ApiError, ColType, DbCol, DbContent, FilterOp, Item, OrderAsc, TableFilter, TableSort};
+
+type Result<T> = std::result::Result<T, ApiError>;
+
+pub const DB_TAKE_N: usize = 100;
+pub const DB_TAKE_MAX: usize = 1000;
+const DB_EXTS: &[&str] = &[".db", ".sqlite", ".sqlite3", ".db3"];
+const DB_MAGIC: &[u8; 16] = b"SQLite format 3\0";
+const BLOB_PREVIEW_LEN: usize = 32;
+/// rows counted for a listing, a table or a view over it has no count
+pub const DB_COUNT_MAX: u64 = 100_000;
+
+/// path of a `file:` uri
+const URI_PATH: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'/').remove(b':').remove(b'-').remove(b'_').remove(b'.').remove(b'~');
+
+pub fn is_db(nm: &str) -> bool {
+    let nm = nm.to_lowercase();
+    DB_EXTS.iter().any(|ext| nm.ends_with(ext))
+}
+
+///
+/// by the header, `Thumbs.db` is not a sqlite file
+pub fn is_db_file(path: &Path) -> bool {
+    let mut magic = [0u8; 16];
+    path.is_file() && File::open(path).and_then(|mut file| file.read_exact(&mut magic)).is_ok() && &magic == DB_MAGIC
+}
+
+///
+/// `C:\data\app.db\users` -> (`C:\data\app.db`, `users`)
+pub fn split_db_path(path: &Path) -> Option<(PathBuf, String)> {
+    split_container_path(path, |p| {
+        p.file_name().is_some_and(|nm| is_db(&nm.to_string_lossy())) && is_db_file(p)
+    })
+}
+
+///
+/// `C:\data\a b.db` -> `file:///C:/data/a%20b.db?mode=ro&immutable=1`
+fn to_db_uri(path: &Path) -> String {
+    let path_str = path.to_string_lossy().replace('\\', "/");
+    let path_str = if path_str.starts_with('/') { path_str } else { format!("/{}", path_str) };
+    format!("file://{}?mode=ro&immutable=1", utf8_percent_encode(&path_str, URI_PATH))
+}
+
+///
+/// read only and immutable, nothing is written even `-shm` or `-wal` of a WAL database
+fn open_db(path: &Path) -> Result<Connection> {
+    let flags = OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_URI | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+    let conn = Connection::open_with_flags(to_db_uri(path), flags)?;
+    conn.pragma_update(None, "query_only", true)?;
+    Ok(conn)
+}
+
+fn quote_ident(nm: &str) -> String {
+    format!("\"{}\"", nm.replace('"', "\"\""))
+}
+
+///
+/// (type, name, sql) of tables and views, without internal tables
+fn get_objects(conn: &Connection) -> Result<Vec<(String, String, Option<String>)>> {
+    let mut stmt = conn.prepare(
+        "SELECT type, name, sql FROM sqlite_master WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' ORDER BY name")?;
+    let objects = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(objects)
+}
+
+fn count_rows(conn: &Connection, nm: &str) -> Result<u64> {
+    Ok(conn.query_row(&format!("SELECT COUNT(*) FROM {}", quote_ident(nm)), [], |row| row.get(0))?)
+}
+
+///
+/// rows up to `DB_COUNT_MAX`, `None` over it. a large table is not scanned for a listing
+fn count_rows_capped(conn: &Connection, nm: &str) -> Result<Option<u64>> {
+    let sql = format!("SELECT COUNT(*) FROM (SELECT 1 FROM {} LIMIT {})", quote_ident(nm), DB_COUNT_MAX + 1);
+    let cnt: u64 = conn.query_row(&sql, [], |row| row.get(0))?;
+    Ok((cnt <= DB_COUNT_MAX).then_some(cnt))
+}
+
+///
+/// tables and views of the database as items, with the row count up to `DB_COUNT_MAX`
+///
+/// - mt: `sqlite/table` or `sqlite/view`
+pub fn get_db_items(path: &Path, inner: &str) -> Result<Vec<Item>> {
+    if !inner.is_empty() {
+        return Err(ApiError::Db(format!("Err Not Found: {:?} {}", path, inner)));
+    }
+    let conn = open_db(path)?;
+    get_objects(&conn)?
+        .into_iter()
+        .map(|(kind, nm, _)| Ok(Item {
+            cnt: count_rows_capped(&conn, &nm)?,
+            mt: Some(format!("sqlite/{}", kind)),
+            nm,
+            dir: false,
+            ..Item::default()
+        }))
+        .collect()
+}
+
+///
+/// by the affinity of the declared type
+fn get_col_type(decl: &str) -> ColType {
+    let decl = decl.to_uppercase();
+    if decl.contains("INT") {
+        ColType::Int
+    } else if decl.contains("REAL") || decl.contains("FLOA") || decl.contains("DOUB") || decl.contains("NUM") || decl.contains("DEC") {
+        ColType::Float
+    } else if decl.contains("BOOL") {
+        ColType::Bool
+    } else if decl.contains("DATE") || decl.contains("TIME") {
+        ColType::Date
+    } else {
+        ColType::String
+    }
+}
+
+fn get_cols(conn: &Connection, nm: &str) -> Result<Vec<DbCol>> {
+    let mut stmt = conn.prepare("SELECT name, type, \"notnull\", dflt_value, pk FROM pragma_table_info(?1)")?;
+    let cols = stmt.query_map([nm], |row| {
+        let decl: String = row.get(1)?;
+        Ok(DbCol {
+            nm: row.get(0)?,
+            kind: get_col_type(&decl),
+            decl,
+            not_null: row.get(2)?,
+            dflt: row.get(3)?,
+            pk: row.get::<_, i64>(4)? > 0,
+        })
+    })?.collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(cols)
+}
+
+fn to_text(value: ValueRef) -> Option<String> {
+    match value {
+        ValueRef::Null => None,
+        ValueRef::Integer(i) => Some(i.to_string()),
+        ValueRef::Real(f) => Some(f.to_string()),
+        ValueRef::Text(t) => Some(String::from_utf8_lossy(t).to_string()),
+        ValueRef::Blob(b) => {
+            let hex: String = b.iter().take(BLOB_PREVIEW_LEN).map(|byte| format!("{:02X}", byte)).collect();
+            Some(format!("x'{}{}' ({} bytes)", hex, if b.len() > BLOB_PREVIEW_LEN { "…" } else { "" }, b.len()))
+        }
+    }
+}
+
+///
+/// numbers are bound as numbers, so `Gt` compares `10 > 9`
+fn to_param(val: &str) -> Value {
+    if let Ok(i) = val.parse::<i64>() {
+        Value::Integer(i)
+    } else if let Ok(f) = val.parse::<f64>() {
+        Value::Real(f)
+    } else {
+        Value::Text(val.to_string())
+    }
+}
+
+fn to_where(cols: &[DbCol], filters: &[TableFilter]) -> Result<(String, Vec<Value>)> {
+    let mut conds: Vec<String> = vec![];
+    let mut params: Vec<Value> = vec![];
+    for filter in filters {
+        let col = cols.get(filter.col).ok_or_else(|| ApiError::Db(format!("Err Col: {}", filter.col)))?;
+        let ident = quote_ident(&col.nm);
+        let cond = match filter.op {
+            FilterOp::Contains => {
+                let escaped = filter.val.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+                params.push(Value::Text(format!("%{}%", escaped)));
+                format!("{} LIKE ? ESCAPE '\\'", ident)
+            }
+            op => {
+                params.push(to_param(&filter.val));
+                let sym = match op {
+                    FilterOp::Eq => "=",
+                    FilterOp::Ne => "<>",
+                    FilterOp::Gt => ">",
+                    FilterOp::Ge => ">=",
+                    FilterOp::Lt => "<",
+                    _ => "<=",
+                };
+                format!("{} {} ?", ident, sym)
+            }
+        };
+        conds.push(cond);
+    }
+    let where_str = if conds.is_empty() { String::new() } else { format!(" WHERE {}", conds.join(" AND ")) };
+    Ok((where_str, params))
+}
+
+///
+/// a page of rows of the table or view `inner`, `NULL` is `None`
+pub fn read_db_rows(path: &Path, inner: &str, sorts: &[TableSort], filters: &[TableFilter],
+                    skip_n: usize, take_n: usize) -> Result<DbContent> {
+    let conn = open_db(path)?;
+    let (kind, nm, sql) = get_objects(&conn)?
+        .into_iter()
+        .find(|(_, nm, _)| nm == inner)
+        .ok_or_else(|| ApiError::Db(format!("Err Not Found: {:?} {}", path, inner)))?;
+    let cols = get_cols(&conn, &nm)?;
+    let from = quote_ident(&nm);
+    let (where_str, mut params) = to_where(&cols, filters)?;
+    let tot: u64 = conn.query_row(&format!("SELECT COUNT(*) FROM {}{}", from, where_str),
+                                  params_from_iter(params.iter()), |row| row.get(0))?;
+    // counted again only with filters
+    let row_cnt = if filters.is_empty() { tot } else { count_rows(&conn, &nm)? };
+
+    let mut order_by: Vec<String> = vec![];
+    for sort in sorts {
+        let col = cols.get(sort.col).ok_or_else(|| ApiError::Db(format!("Err Col: {}", sort.col)))?;
+        let asc = if sort.asc == OrderAsc::Asc { "ASC" } else { "DESC" };
+        order_by.push(format!("{} {} NULLS LAST", quote_ident(&col.nm), asc));
+    }
+    let order_str = if order_by.is_empty() { String::new() } else { format!(" ORDER BY {}", order_by.join(", ")) };
+    params.push(Value::Integer(take_n as i64));
+    params.push(Value::Integer(skip_n as i64));
+    let mut stmt = conn.prepare(&format!("SELECT * FROM {}{}{} LIMIT ? OFFSET ?", from, where_str, order_str))?;
+    let col_cnt = stmt.column_count();
+    let rows = stmt.query_map(params_from_iter(params.iter()), |row| {
+        (0..col_cnt).map(|i| row.get_ref(i).map(to_text)).collect::<rusqlite::Result<Vec<_>>>()
+    })?.collect::<rusqlite::Result<Vec<_>>>()?;
+
+    Ok(DbContent {
+        path: path.join(&nm).to_string_lossy().to_string(),
+        kind,
+        sql,
+        cols,
+        row_cnt,
+        tot,
+        skip_n,
+        take_n: rows.len(),
+        rows,
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    fn write_db(path: &Path) {
+        let _ = std::fs::remove_file(path);
+        let conn = Connection::open(path).unwrap();
+        conn.query_row("PRAGMA journal_mode=WAL", [], |_| Ok(())).unwrap();
+        conn.execute_batch("
+            CREATE TABLE users (id INTEGER PRIMARY KEY, nm TEXT NOT NULL, score REAL, avatar BLOB);
+            INSERT INTO users (nm, score, avatar) VALUES ('kim', 9.5, x'0102'), ('lee', 10, NULL), ('park_1', NULL, NULL);
+            CREATE VIEW top AS SELECT nm FROM users WHERE score > 9;
+        ").unwrap();
+    }
+
+    #[test]
+    fn test_db() {
+        let tmp = temp_dir();
+        let path = tmp.path().join("db.sqlite");
+        write_db(&path);
+
+        let (db, inner) = split_db_path(&path.join("users")).unwrap();
+        assert_eq!((db, inner.as_str()), (path.clone(), "users"));
+        let items = get_db_items(&path, "").unwrap();
+        let nms: Vec<(&str, Option<&str>, Option<u64>)> = items.iter().map(|item| (item.nm.as_str(), item.mt.as_deref(), item.cnt)).collect();
+        assert_eq!(nms, vec![("top", Some("sqlite/view"), Some(2)), ("users", Some("sqlite/table"), Some(3))]);
+        let conn = open_db(&path).unwrap();
+        assert!(conn.execute("DELETE FROM users", []).is_err());
+        assert_eq!(count_rows_capped(&conn, "users").unwrap(), Some(3));
+
+        let sorts = vec![TableSort { col: 2, asc: OrderAsc::Desc }];
+        let content = read_db_rows(&path, "users", &sorts, &[], 0, 10).unwrap();
+        assert_eq!((content.kind.as_str(), content.row_cnt, content.tot), ("table", 3, 3));
+        assert_eq!((content.cols[0].pk, content.cols[1].not_null, content.cols[2].kind), (true, true, ColType::Float));
+        assert_eq!(content.rows.iter().map(|row| row[1].as_deref()).collect::<Vec<_>>(), vec![Some("lee"), Some("kim"), Some("park_1")]);
+        assert_eq!(content.rows[1][3].as_deref(), Some("x'0102' (2 bytes)"));
+        assert_eq!(content.rows[2][2], None);
+
+        let filters = vec![TableFilter { col: 1, op: FilterOp::Contains, val: String::from("K_") }];
+        let content = read_db_rows(&path, "users", &[], &filters, 0, 10).unwrap();
+        assert_eq!((content.tot, content.rows[0][1].as_deref()), (1, Some("park_1")));
+        let filters = vec![TableFilter { col: 2, op: FilterOp::Ge, val: String::from("10") }];
+        assert_eq!(read_db_rows(&path, "users", &[], &filters, 0, 10).unwrap().tot, 1);
+        assert!(read_db_rows(&path, "nothing", &[], &[], 0, 10).is_err());
+        assert!(!path.with_extension("sqlite-shm").exists());
+    }
+
+    #[test]
+    fn test_to_db_uri() {
+        assert_eq!(to_db_uri(Path::new("/data/a b#1.db")), "file:///data/a%20b%231.db?mode=ro&immutable=1");
+        assert_eq!(to_db_uri(Path::new(r"C:\data\app.db")), "file:///C:/data/app.db?mode=ro&immutable=1");
+    }
+}
@@ -0,0 +1,96 @@
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use crate::system_time_ext::SystemTimeExt;
+
+pub const STREAM_SCHEME: &str = "stream";
+/// a range is answered by a chunk at most, the client asks for the rest
+pub const STREAM_CHUNK_LEN: u64 = 4 * 1024 * 1024;
+/// a request without a range gets the whole file up to this size, the first chunk over it
+pub const STREAM_FULL_MAX: u64 = 64 * 1024 * 1024;
+
+///
+/// `/C%3A%5Cvideo%5Ca.mp4` -> `C:\video\a.mp4`
+pub fn decode_uri_path(path: &str) -> Option<String> {
+    let path_str = percent_encoding::percent_decode_str(path.strip_prefix('/').unwrap_or(path))
+        .decode_utf8()
+        .ok()?
+        .to_string();
+    if path_str.is_empty() {
+        return None;
+    }
+    Some(path_str)
+}
+
+///
+/// validator of mtime + size, `"1a2b-5f3c"`. strong so that `If-Range` can use it
+pub fn get_etag(meta: &Metadata) -> String {
+    let tm_ms = meta.modified().map(|t| t.to_ms()).unwrap_or(0);
+    format!("\"{:x}-{:x}\"", tm_ms, meta.len())
+}
+
+///
+/// by the magic bytes, by the extension if unknown
+pub fn get_stream_mimetype(path: &Path, sample: &[u8]) -> String {
+    match infer::get(sample) {
+        Some(infer_type) => infer_type.mime_type().to_string(),
+        None => mime_guess::from_path(path).first_or_octet_stream().to_string(),
+    }
+}
+
+///
+/// byte range of the `Range` header, inclusive
+///
+/// - `None`: no range or not a byte range, the whole file
+/// - `Some(Err(()))`: not satisfiable, `416`
+///
+/// only the first of multiple ranges is served, up to `STREAM_CHUNK_LEN`
+pub fn parse_range(header: Option<&str>, sz: u64) -> Option<Result<(u64, u64), ()>> {
+    let spec = header?.trim().strip_prefix("bytes=")?.split(',').next()?.trim();
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = (start.trim(), end.trim());
+    let range = match (start.parse::<u64>().ok(), end.parse::<u64>().ok()) {
+        // `bytes=-500` is the last 500 bytes
+        (None, Some(suffix)) if start.is_empty() && suffix > 0 && sz > 0 => Ok((sz.saturating_sub(suffix), sz - 1)),
+        (Some(start), None) if end.is_empty() && start < sz => Ok((start, sz - 1)),
+        (Some(start), Some(end)) if start <= end && start < sz => Ok((start, end.min(sz - 1))),
+        (None, None) => return None,
+        _ => Err(()),
+    };
+    Some(range.map(|(start, end)| (start, end.min(start + STREAM_CHUNK_LEN - 1))))
+}
+
+///
+/// `path` is under one of the roots, both canonical
+pub fn is_under_roots(path: &Path, roots: &[PathBuf]) -> bool {
+    roots.iter().any(|root| path.starts_with(root))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range(Some("bytes=0-99"), 1000), Some(Ok((0, 99))));
+        assert_eq!(parse_range(Some("bytes=900-2000"), 1000), Some(Ok((900, 999))));
+        assert_eq!(parse_range(Some("bytes=-100"), 1000), Some(Ok((900, 999))));
+        assert_eq!(parse_range(Some("bytes=10-"), 1000), Some(Ok((10, 999))));
+        assert_eq!(parse_range(Some("bytes=0-"), STREAM_CHUNK_LEN * 2), Some(Ok((0, STREAM_CHUNK_LEN - 1))));
+        assert_eq!(parse_range(Some("bytes=0-99999999999"), STREAM_CHUNK_LEN * 4), Some(Ok((0, STREAM_CHUNK_LEN - 1))));
+        assert_eq!(parse_range(Some("bytes=-99999999999"), STREAM_CHUNK_LEN * 4), Some(Ok((0, STREAM_CHUNK_LEN - 1))));
+        assert_eq!(parse_range(Some("bytes=0-1, 5-6"), 1000), Some(Ok((0, 1))));
+        assert_eq!(parse_range(Some("bytes=1000-"), 1000), Some(Err(())));
+        assert_eq!(parse_range(Some("bytes=5-1"), 1000), Some(Err(())));
+        assert_eq!(parse_range(Some("items=0-1"), 1000), None);
+        assert_eq!(parse_range(None, 1000), None);
+    }
+
+    #[test]
+    fn test_is_under_roots() {
+        let roots = vec![PathBuf::from("/data/videos")];
+        assert!(is_under_roots(Path::new("/data/videos/a.mp4"), &roots));
+        assert!(!is_under_roots(Path::new("/data/videos2/a.mp4"), &roots));
+        assert_eq!(decode_uri_path("/%2Fdata%2Fa%20b.mp4").as_deref(), Some("/data/a b.mp4"));
+    }
+}
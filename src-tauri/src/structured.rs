@@ -0,0 +1,305 @@
+use std::path::Path;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use crate::models::{NodeType, ParseErr, StructFormat, StructNode};
+
+pub const STRUCT_MAX_LEN: u64 = 64 * 1024 * 1024;
+pub const STRUCT_TAKE_N: usize = 1000;
+const PREVIEW_LEN: usize = 80;
+const PREVIEW_ITEMS: usize = 10;
+
+///
+/// format by file name, then by language of the text (`json`, `yaml`, `xml`)
+pub fn get_struct_format(nm: &str, language: Option<&str>) -> Option<StructFormat> {
+    let file_nm = Path::new(nm).file_name()?.to_string_lossy().to_lowercase();
+    // toml is shown as ini in monaco
+    if file_nm.ends_with(".toml") || file_nm == "cargo.lock" || file_nm == "pipfile" {
+        return Some(StructFormat::Toml);
+    }
+    match language? {
+        "json" => Some(StructFormat::Json),
+        "yaml" => Some(StructFormat::Yaml),
+        "xml" => Some(StructFormat::Xml),
+        _ => None,
+    }
+}
+
+///
+/// byte position to 1-based line and utf-16 col
+fn to_parse_err(text: &str, pos: usize, msg: String) -> ParseErr {
+    let mut pos = pos.min(text.len());
+    while !text.is_char_boundary(pos) {
+        pos -= 1;
+    }
+    let head = &text[..pos];
+    let line_start = head.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    ParseErr {
+        msg,
+        line: head.matches('\n').count() + 1,
+        col: head[line_start..].encode_utf16().count() + 1,
+    }
+}
+
+pub fn parse_struct(text: &str, format: StructFormat) -> Result<Value, ParseErr> {
+    match format {
+        StructFormat::Json => serde_json::from_str(text).map_err(|e| {
+            // serde_json col is 1-based bytes
+            let line_start: usize = text.split_inclusive('\n').take(e.line().saturating_sub(1)).map(|l| l.len()).sum();
+            to_parse_err(text, line_start + e.column().saturating_sub(1), e.to_string())
+        }),
+        StructFormat::Yaml => parse_yaml(text),
+        StructFormat::Toml => match toml::from_str::<toml::Table>(text) {
+            Ok(table) => Ok(toml_to_json(toml::Value::Table(table))),
+            Err(e) => Err(to_parse_err(text, e.span().map(|span| span.start).unwrap_or(0), e.message().to_string())),
+        },
+        StructFormat::Xml => parse_xml(text),
+    }
+}
+
+///
+/// multiple documents are an array
+fn parse_yaml(text: &str) -> Result<Value, ParseErr> {
+    let mut docs: Vec<Value> = vec![];
+    for doc in serde_yaml::Deserializer::from_str(text) {
+        match Value::deserialize(doc) {
+            Ok(value) => docs.push(value),
+            Err(e) => {
+                let pos = e.location().map(|loc| loc.index()).unwrap_or(0);
+                return Err(to_parse_err(text, pos, e.to_string()));
+            }
+        }
+    }
+    Ok(match docs.len() {
+        0 => Value::Null,
+        1 => docs.remove(0),
+        _ => Value::Array(docs),
+    })
+}
+
+fn toml_to_json(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::from(i),
+        toml::Value::Float(f) => serde_json::Number::from_f64(f).map(Value::Number).unwrap_or(Value::String(f.to_string())),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(dt) => Value::String(dt.to_string()),
+        toml::Value::Array(arr) => Value::Array(arr.into_iter().map(toml_to_json).collect()),
+        toml::Value::Table(table) => Value::Object(table.into_iter().map(|(k, v)| (k, toml_to_json(v))).collect()),
+    }
+}
+
+struct XmlElem {
+    nm: String,
+    map: Map<String, Value>,
+    text: String,
+}
+
+///
+/// `<a x="1"><b>t</b><b>u</b></a>` -> `{"a": {"@x": "1", "b": ["t", "u"]}}`
+///
+/// text of an element with attributes or children is `#text`
+fn parse_xml(text: &str) -> Result<Value, ParseErr> {
+    let mut reader = Reader::from_str(text);
+    reader.config_mut().trim_text(true);
+    let mut stack: Vec<XmlElem> = vec![XmlElem { nm: String::new(), map: Map::new(), text: String::new() }];
+    let xml_err = |reader: &Reader<&[u8]>, e: quick_xml::Error| {
+        to_parse_err(text, reader.error_position() as usize, e.to_string())
+    };
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                let elem = new_xml_elem(&e).map_err(|e| xml_err(&reader, e))?;
+                stack.push(elem);
+            }
+            Ok(Event::Empty(e)) => {
+                let elem = new_xml_elem(&e).map_err(|e| xml_err(&reader, e))?;
+                close_xml_elem(&mut stack, elem);
+            }
+            Ok(Event::End(_)) => {
+                if let Some(elem) = stack.pop() {
+                    close_xml_elem(&mut stack, elem);
+                }
+            }
+            Ok(Event::Text(e)) => {
+                let s = e.unescape().map_err(|e| xml_err(&reader, e))?;
+                if let Some(elem) = stack.last_mut() {
+                    elem.text.push_str(&s);
+                }
+            }
+            Ok(Event::CData(e)) => {
+                if let Some(elem) = stack.last_mut() {
+                    elem.text.push_str(&String::from_utf8_lossy(&e));
+                }
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(e) => return Err(xml_err(&reader, e)),
+        }
+    }
+    if stack.len() > 1 {
+        let nm = stack.last().map(|elem| elem.nm.clone()).unwrap_or_default();
+        return Err(to_parse_err(text, text.len(), format!("Err Unclosed: <{}>", nm)));
+    }
+    Ok(Value::Object(stack.remove(0).map))
+}
+
+fn new_xml_elem(e: &quick_xml::events::BytesStart) -> Result<XmlElem, quick_xml::Error> {
+    let mut map = Map::new();
+    for attr in e.attributes() {
+        let attr = attr?;
+        let key = format!("@{}", String::from_utf8_lossy(attr.key.as_ref()));
+        map.insert(key, Value::String(attr.unescape_value()?.to_string()));
+    }
+    Ok(XmlElem {
+        nm: String::from_utf8_lossy(e.name().as_ref()).to_string(),
+        map,
+        text: String::new(),
+    })
+}
+
+fn close_xml_elem(stack: &mut [XmlElem], elem: XmlElem) {
+    let XmlElem { nm, mut map, text } = elem;
+    let value = if map.is_empty() {
+        Value::String(text)
+    } else {
+        if !text.is_empty() {
+            map.insert(String::from("#text"), Value::String(text));
+        }
+        Value::Object(map)
+    };
+    let Some(parent) = stack.last_mut() else {
+        return;
+    };
+    // repeated elements are an array
+    match parent.map.get_mut(&nm) {
+        Some(Value::Array(arr)) => arr.push(value),
+        Some(prev) => {
+            let first = prev.take();
+            *prev = Value::Array(vec![first, value]);
+        }
+        None => {
+            parent.map.insert(nm, value);
+        }
+    }
+}
+
+///
+/// `/a` + `b/c` -> `/a/b~1c`
+pub fn get_child_path(path: &str, key: &str) -> String {
+    format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1"))
+}
+
+fn get_kind(value: &Value) -> NodeType {
+    match value {
+        Value::Object(_) => NodeType::Object,
+        Value::Array(_) => NodeType::Array,
+        Value::String(_) => NodeType::String,
+        Value::Number(_) => NodeType::Number,
+        Value::Bool(_) => NodeType::Bool,
+        Value::Null => NodeType::Null,
+    }
+}
+
+fn get_preview(value: &Value) -> String {
+    let preview = match value {
+        Value::Object(map) => {
+            let keys: Vec<&str> = map.keys().take(PREVIEW_ITEMS).map(|k| k.as_str()).collect();
+            format!("{{{}{}}}", keys.join(", "), if map.len() > PREVIEW_ITEMS { ", …" } else { "" })
+        }
+        Value::Array(arr) => {
+            let items: Vec<String> = arr.iter().take(PREVIEW_ITEMS).map(|v| match v {
+                Value::Object(_) => String::from("{…}"),
+                Value::Array(_) => String::from("[…]"),
+                v => v.to_string(),
+            }).collect();
+            format!("[{}{}]", items.join(", "), if arr.len() > PREVIEW_ITEMS { ", …" } else { "" })
+        }
+        Value::String(s) => s.clone(),
+        v => v.to_string(),
+    };
+    match preview.char_indices().nth(PREVIEW_LEN) {
+        Some((idx, _)) => format!("{}…", &preview[..idx]),
+        None => preview,
+    }
+}
+
+pub fn to_node(key: &str, path: &str, value: &Value) -> StructNode {
+    StructNode {
+        key: key.to_string(),
+        path: path.to_string(),
+        kind: get_kind(value),
+        preview: get_preview(value),
+        cnt: match value {
+            Value::Object(map) => Some(map.len()),
+            Value::Array(arr) => Some(arr.len()),
+            _ => None,
+        },
+    }
+}
+
+///
+/// children of object or array, keys of array items are the indexes
+pub fn get_children(value: &Value, path: &str, skip_n: usize, take_n: usize) -> Vec<StructNode> {
+    match value {
+        Value::Object(map) => map.iter().skip(skip_n).take(take_n)
+            .map(|(k, v)| to_node(k, &get_child_path(path, k), v))
+            .collect(),
+        Value::Array(arr) => arr.iter().enumerate().skip(skip_n).take(take_n)
+            .map(|(i, v)| {
+                let k = i.to_string();
+                to_node(&k, &get_child_path(path, &k), v)
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_struct() {
+        let value = parse_struct("{\"b\": 1, \"a\": [true, null]}", StructFormat::Json).unwrap();
+        let nodes = get_children(&value, "", 0, 10);
+        assert_eq!(nodes.iter().map(|n| n.key.as_str()).collect::<Vec<_>>(), vec!["b", "a"]);
+        assert_eq!((nodes[1].kind, nodes[1].cnt, nodes[1].preview.as_str()), (NodeType::Array, Some(2), "[true, null]"));
+        assert_eq!(nodes[1].path, "/a");
+        assert_eq!(value.pointer("/a/1"), Some(&Value::Null));
+
+        let value = parse_struct("a: 1\n---\nb: x\n", StructFormat::Yaml).unwrap();
+        assert_eq!(value.pointer("/1/b"), Some(&Value::from("x")));
+
+        let value = parse_struct("[pkg]\nname = \"tr\"\ndt = 1979-05-27\n", StructFormat::Toml).unwrap();
+        assert_eq!(value.pointer("/pkg/dt"), Some(&Value::from("1979-05-27")));
+
+        let value = parse_struct("<a x=\"1\"><b>t</b><b>u</b><c/></a>", StructFormat::Xml).unwrap();
+        assert_eq!(value.pointer("/a/@x"), Some(&Value::from("1")));
+        assert_eq!(value.pointer("/a/b/1"), Some(&Value::from("u")));
+        assert_eq!(value.pointer("/a/c"), Some(&Value::from("")));
+    }
+
+    #[test]
+    fn test_parse_err() {
+        let err = parse_struct("{\n  \"a\": 1,\n  \"한\": x\n}", StructFormat::Json).unwrap_err();
+        assert_eq!((err.line, err.col), (3, 8));
+        let err = parse_struct("a = 1\nb = \n", StructFormat::Toml).unwrap_err();
+        assert_eq!(err.line, 2);
+        let err = parse_struct("a: [1\nb: 2\n", StructFormat::Yaml).unwrap_err();
+        assert!(err.line >= 2);
+        let err = parse_struct("<a><b></a>", StructFormat::Xml).unwrap_err();
+        assert_eq!(err.line, 1);
+        let err = parse_struct("<a><b></b>", StructFormat::Xml).unwrap_err();
+        assert_eq!(err.msg, "Err Unclosed: <a>");
+    }
+
+    #[test]
+    fn test_get_struct_format() {
+        assert_eq!(get_struct_format("Cargo.toml", Some("ini")), Some(StructFormat::Toml));
+        assert_eq!(get_struct_format("a.json", Some("json")), Some(StructFormat::Json));
+        assert_eq!(get_struct_format("a.txt", Some("plaintext")), None);
+    }
+}
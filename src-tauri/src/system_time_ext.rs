@@ -1,6 +1,6 @@
 pub trait SystemTimeExt {
     fn to_sec(&self) -> u64;
-    // fn to_ms(&self) -> u128;
+    fn to_ms(&self) -> u64;
 }
 
 impl SystemTimeExt for std::time::SystemTime {
@@ -10,7 +10,36 @@ impl SystemTimeExt for std::time::SystemTime {
             Err(_) => panic!("SystemTime before UNIX EPOCH!"),
         }
     }
-    // fn to_ms(&self) -> u128 {
-    //     self.duration_since(std::time::UNIX_EPOCH).unwrap().as_millis()
-    // }
+    ///
+    /// 0 for a time before the epoch such as an mtime of an extracted archive
+    fn to_ms(&self) -> u64 {
+        self.duration_since(std::time::UNIX_EPOCH).map(|dur| dur.as_millis() as u64).unwrap_or(0)
+    }
+}
+
+///
+/// civil date time (UTC) to unix time
+pub fn civil_to_sec(y: i64, m: i64, d: i64, hour: i64, minute: i64, second: i64) -> i64 {
+    // days from civil, http://howardhinnant.github.io/date_algorithms.html
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = (if y >= 0 { y } else { y - 399 }) / 400;
+    let yoe = y - era * 400;
+    let mp = (m + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146097 + doe - 719468;
+    days * 86400 + hour * 3600 + minute * 60 + second
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn test_to_ms() {
+        assert_eq!((UNIX_EPOCH + Duration::from_millis(1500)).to_ms(), 1500);
+        assert_eq!((UNIX_EPOCH - Duration::from_secs(86400)).to_ms(), 0);
+    }
 }
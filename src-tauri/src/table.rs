@@ -0,0 +1,316 @@
+use std::cmp::Ordering;
+use std::fs::File;
+use std::io::{BufReader, Seek, SeekFrom};
+use std::path::Path;
+use csv::{ByteRecord, ReaderBuilder};
+use encoding_rs::Encoding;
+use crate::models::{ApiError, ColType, FilterOp, OrderAsc, TableCol, TableFilter, TableSort};
+
+type Result<T> = std::result::Result<T, ApiError>;
+
+pub const TABLE_SAMPLE_LEN: u64 = 64 * 1024;
+pub const TABLE_TAKE_N: usize = 100;
+pub const TABLE_TAKE_MAX: usize = 1000;
+const DELIMITERS: &[u8] = b",\t;|";
+const SNIFF_ROWS: usize = 20;
+const TYPE_ROWS: usize = 1000;
+
+///
+/// columns and row offsets of a csv file
+///
+/// - offsets: byte offset of each data row, the header row is not included
+pub struct TableIndex {
+    pub encoding: &'static Encoding,
+    pub delimiter: u8,
+    pub has_header: bool,
+    pub cols: Vec<TableCol>,
+    pub offsets: Vec<u64>,
+}
+
+fn decode_field(encoding: &'static Encoding, field: &[u8]) -> String {
+    encoding.decode_without_bom_handling(field).0.into_owned()
+}
+
+fn decode_record(encoding: &'static Encoding, record: &ByteRecord) -> Vec<String> {
+    record.iter().map(|field| decode_field(encoding, field)).collect()
+}
+
+fn get_bom_len(sample: &[u8]) -> u64 {
+    if sample.starts_with(b"\xef\xbb\xbf") { 3 } else { 0 }
+}
+
+fn parse_sample(sample: &[u8], delimiter: u8, take_n: usize) -> Vec<ByteRecord> {
+    let mut reader = ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(delimiter)
+        .from_reader(&sample[get_bom_len(sample) as usize..]);
+    reader.byte_records().take(take_n).filter_map(|r| r.ok()).collect()
+}
+
+///
+/// the delimiter giving the same field count (> 1) for most sample rows. `.tsv` is always tab
+pub fn sniff_delimiter(nm: &str, sample: &[u8]) -> u8 {
+    if nm.to_lowercase().ends_with(".tsv") {
+        return b'\t';
+    }
+    DELIMITERS.iter()
+        .map(|delimiter| {
+            // the last row may be cut in the sample
+            let records = parse_sample(sample, *delimiter, SNIFF_ROWS + 1);
+            let rows = if records.len() > 1 { &records[..records.len() - 1] } else { &records[..] };
+            let first_len = rows.first().map(|r| r.len()).unwrap_or(0);
+            let same = rows.iter().filter(|r| r.len() == first_len).count();
+            let score = if first_len > 1 { same * 1000 + first_len } else { 0 };
+            (*delimiter, score)
+        })
+        .max_by_key(|(_, score)| *score)
+        .filter(|(_, score)| *score > 0)
+        .map(|(delimiter, _)| delimiter)
+        .unwrap_or(b',')
+}
+
+///
+/// the first row is a header if its fields are non-empty, distinct and not typed values
+pub fn sniff_header(sample: &[u8], delimiter: u8, encoding: &'static Encoding) -> bool {
+    let records = parse_sample(sample, delimiter, 2);
+    let Some(first) = records.first() else {
+        return false;
+    };
+    let fields = decode_record(encoding, first);
+    let mut distinct = fields.clone();
+    distinct.sort();
+    distinct.dedup();
+    distinct.len() == fields.len()
+        && fields.iter().all(|field| !field.trim().is_empty() && get_type(field.trim()) == ColType::String)
+}
+
+fn is_date(s: &str) -> bool {
+    // 2024-01-31, 2024-01-31T12:34:56, 2024/01/31 12:34
+    let b = s.as_bytes();
+    b.len() >= 10
+        && b[..4].iter().all(|c| c.is_ascii_digit())
+        && (b[4] == b'-' || b[4] == b'/') && b[7] == b[4]
+        && b[5..7].iter().all(|c| c.is_ascii_digit())
+        && b[8..10].iter().all(|c| c.is_ascii_digit())
+        && (b.len() == 10 || b[10] == b'T' || b[10] == b' ')
+}
+
+fn get_type(s: &str) -> ColType {
+    if s.parse::<i64>().is_ok() {
+        ColType::Int
+    } else if s.parse::<f64>().is_ok() {
+        ColType::Float
+    } else if s.eq_ignore_ascii_case("true") || s.eq_ignore_ascii_case("false") {
+        ColType::Bool
+    } else if is_date(s) {
+        ColType::Date
+    } else {
+        ColType::String
+    }
+}
+
+///
+/// type of all non-empty values. `Int` and `Float` make `Float`
+pub fn infer_type<'a>(values: impl Iterator<Item = &'a str>) -> ColType {
+    let mut ret: Option<ColType> = None;
+    for value in values.map(|v| v.trim()).filter(|v| !v.is_empty()) {
+        ret = match (ret, get_type(value)) {
+            (None, kind) => Some(kind),
+            (Some(a), b) if a == b => Some(a),
+            (Some(ColType::Int), ColType::Float) | (Some(ColType::Float), ColType::Int) => Some(ColType::Float),
+            _ => return ColType::String,
+        };
+    }
+    ret.unwrap_or_default()
+}
+
+fn new_reader<R: std::io::Read>(reader: R, delimiter: u8) -> csv::Reader<R> {
+    ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(delimiter)
+        .from_reader(reader)
+}
+
+///
+/// scan the file once for the header, column types and row offsets
+pub fn build_index(path: &Path, encoding: &'static Encoding, delimiter: u8, has_header: bool) -> Result<TableIndex> {
+    let mut file = BufReader::new(File::open(path)?);
+    let mut head = [0u8; 3];
+    let n = std::io::Read::read(&mut file, &mut head)?;
+    let bom_len = get_bom_len(&head[..n]);
+    file.seek(SeekFrom::Start(bom_len))?;
+
+    let mut reader = new_reader(file, delimiter);
+    let mut record = ByteRecord::new();
+    let mut header: Vec<String> = vec![];
+    let mut samples: Vec<Vec<String>> = vec![];
+    let mut offsets: Vec<u64> = vec![];
+    let mut is_first = true;
+    loop {
+        let pos = bom_len + reader.position().byte();
+        if !reader.read_byte_record(&mut record)? {
+            break;
+        }
+        if is_first && has_header {
+            header = decode_record(encoding, &record);
+        } else {
+            if samples.len() < TYPE_ROWS {
+                samples.push(decode_record(encoding, &record));
+            }
+            offsets.push(pos);
+        }
+        is_first = false;
+    }
+
+    let col_cnt = samples.iter().map(|row| row.len()).chain([header.len()]).max().unwrap_or(0);
+    let cols = (0..col_cnt).map(|i| TableCol {
+        nm: header.get(i).cloned().unwrap_or_else(|| format!("{}", i + 1)),
+        kind: infer_type(samples.iter().filter_map(|row| row.get(i).map(|v| v.as_str()))),
+    }).collect();
+    Ok(TableIndex {
+        encoding,
+        delimiter,
+        has_header,
+        cols,
+        offsets,
+    })
+}
+
+///
+/// rows at the offsets
+pub fn read_rows(path: &Path, index: &TableIndex, offsets: &[u64]) -> Result<Vec<Vec<String>>> {
+    let mut file = BufReader::new(File::open(path)?);
+    let mut record = ByteRecord::new();
+    let mut rows: Vec<Vec<String>> = Vec::with_capacity(offsets.len());
+    for offset in offsets {
+        file.seek(SeekFrom::Start(*offset))?;
+        let mut reader = new_reader(&mut file, index.delimiter);
+        reader.read_byte_record(&mut record)?;
+        rows.push(decode_record(index.encoding, &record));
+    }
+    Ok(rows)
+}
+
+enum SortKey {
+    Num(f64),
+    Str(String),
+    Empty,
+}
+
+fn to_sort_key(kind: ColType, s: &str) -> SortKey {
+    let s = s.trim();
+    if s.is_empty() {
+        return SortKey::Empty;
+    }
+    match kind {
+        ColType::Int | ColType::Float => s.parse::<f64>().map(SortKey::Num).unwrap_or(SortKey::Empty),
+        _ => SortKey::Str(s.to_string()),
+    }
+}
+
+fn cmp_sort_key(a: &SortKey, b: &SortKey) -> Ordering {
+    match (a, b) {
+        (SortKey::Num(a), SortKey::Num(b)) => a.total_cmp(b),
+        (SortKey::Str(a), SortKey::Str(b)) => a.cmp(b),
+        _ => Ordering::Equal,
+    }
+}
+
+fn is_match(cols: &[TableCol], filter: &TableFilter, value: &str) -> bool {
+    let value = value.trim();
+    let is_num = cols.get(filter.col).is_some_and(|col| col.kind == ColType::Int || col.kind == ColType::Float);
+    let ord = match (is_num, value.parse::<f64>(), filter.val.trim().parse::<f64>()) {
+        (true, Ok(a), Ok(b)) => a.partial_cmp(&b),
+        _ => Some(value.cmp(filter.val.trim())),
+    };
+    match filter.op {
+        FilterOp::Eq => ord == Some(Ordering::Equal),
+        FilterOp::Ne => ord != Some(Ordering::Equal),
+        FilterOp::Contains => value.to_lowercase().contains(&filter.val.to_lowercase()),
+        FilterOp::Gt => ord == Some(Ordering::Greater),
+        FilterOp::Ge => matches!(ord, Some(Ordering::Greater | Ordering::Equal)),
+        FilterOp::Lt => ord == Some(Ordering::Less),
+        FilterOp::Le => matches!(ord, Some(Ordering::Less | Ordering::Equal)),
+    }
+}
+
+///
+/// row numbers matching the filters in the sort order. empty values are sorted last
+pub fn get_view(path: &Path, index: &TableIndex, sorts: &[TableSort], filters: &[TableFilter]) -> Result<Vec<usize>> {
+    let mut file = BufReader::new(File::open(path)?);
+    let first = index.offsets.first().copied().unwrap_or(0);
+    file.seek(SeekFrom::Start(first))?;
+    let mut reader = new_reader(file, index.delimiter);
+    let mut record = ByteRecord::new();
+    let mut rows: Vec<(usize, Vec<SortKey>)> = vec![];
+    let mut row_no = 0;
+    while row_no < index.offsets.len() && reader.read_byte_record(&mut record)? {
+        let field = |col: usize| decode_field(index.encoding, record.get(col).unwrap_or_default());
+        if filters.iter().all(|filter| is_match(&index.cols, filter, &field(filter.col))) {
+            let keys = sorts.iter()
+                .map(|sort| to_sort_key(index.cols.get(sort.col).map(|col| col.kind).unwrap_or_default(), &field(sort.col)))
+                .collect();
+            rows.push((row_no, keys));
+        }
+        row_no += 1;
+    }
+    rows.sort_by(|(_, a), (_, b)| {
+        for (i, sort) in sorts.iter().enumerate() {
+            let ord = match (&a[i], &b[i]) {
+                (SortKey::Empty, SortKey::Empty) => Ordering::Equal,
+                (SortKey::Empty, _) => Ordering::Greater,
+                (_, SortKey::Empty) => Ordering::Less,
+                (a, b) => match sort.asc {
+                    OrderAsc::Asc => cmp_sort_key(a, b),
+                    OrderAsc::Desc => cmp_sort_key(b, a),
+                },
+            };
+            if ord != Ordering::Equal {
+                return ord;
+            }
+        }
+        Ordering::Equal
+    });
+    Ok(rows.into_iter().map(|(row_no, _)| row_no).collect())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn test_sniff() {
+        let sample = b"name;age;city\nkim;30;seoul\nlee;;\"a;b\"\n";
+        assert_eq!(sniff_delimiter("a.csv", sample), b';');
+        assert_eq!(sniff_delimiter("a.tsv", sample), b'\t');
+        assert!(sniff_header(sample, b';', encoding_rs::UTF_8));
+        assert!(!sniff_header(b"1,2\n3,4\n", b',', encoding_rs::UTF_8));
+        assert_eq!(infer_type(["1", "", "2.5"].into_iter()), ColType::Float);
+        assert_eq!(infer_type(["2024-01-31", "2024-02-01 10:00"].into_iter()), ColType::Date);
+        assert_eq!(infer_type(["1", "x"].into_iter()), ColType::String);
+    }
+
+    #[test]
+    fn test_table() {
+        let tmp = temp_dir();
+        let path = tmp.path().join("table.csv");
+        std::fs::write(&path, "\u{feff}name,age\nkim,30\n\"lee\nsun\",9\npark,\nchoi,100\n").unwrap();
+        let index = build_index(&path, encoding_rs::UTF_8, b',', true).unwrap();
+        assert_eq!(index.cols.iter().map(|c| (c.nm.as_str(), c.kind)).collect::<Vec<_>>(),
+                   vec![("name", ColType::String), ("age", ColType::Int)]);
+        assert_eq!(index.offsets.len(), 4);
+        let rows = read_rows(&path, &index, &index.offsets[1..2]).unwrap();
+        assert_eq!(rows, vec![vec![String::from("lee\nsun"), String::from("9")]]);
+
+        let sorts = vec![TableSort { col: 1, asc: OrderAsc::Desc }];
+        assert_eq!(get_view(&path, &index, &sorts, &[]).unwrap(), vec![3, 0, 1, 2]);
+        let filters = vec![TableFilter { col: 1, op: FilterOp::Gt, val: String::from("10") }];
+        assert_eq!(get_view(&path, &index, &[], &filters).unwrap(), vec![0, 3]);
+        let filters = vec![TableFilter { col: 0, op: FilterOp::Contains, val: String::from("SUN") }];
+        assert_eq!(get_view(&path, &index, &[], &filters).unwrap(), vec![1]);
+    }
+}
@@ -0,0 +1,5 @@
+///
+/// unique dir of a test, removed when dropped even if an assert fails
+pub fn temp_dir() -> tempfile::TempDir {
+    tempfile::Builder::new().prefix("tr_viewer_test_").tempdir().unwrap()
+}
@@ -0,0 +1,426 @@
+use encoding_rs::{Encoding, DecoderResult};
+use chardetng::EncodingDetector;
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use crate::models::{ApiError, DecodeErr, Detection, Eol, EolInfo, IndentInfo};
+
+type Result<T> = std::result::Result<T, ApiError>;
+
+const ENCODINGS: &[&Encoding] = &[
+    encoding_rs::UTF_8,
+    encoding_rs::UTF_16LE,
+    encoding_rs::UTF_16BE,
+    encoding_rs::EUC_KR,
+    encoding_rs::SHIFT_JIS,
+    encoding_rs::EUC_JP,
+    encoding_rs::ISO_2022_JP,
+    encoding_rs::GBK,
+    encoding_rs::GB18030,
+    encoding_rs::BIG5,
+    encoding_rs::WINDOWS_1250,
+    encoding_rs::WINDOWS_1251,
+    encoding_rs::WINDOWS_1252,
+    encoding_rs::WINDOWS_1253,
+    encoding_rs::WINDOWS_1254,
+    encoding_rs::WINDOWS_1255,
+    encoding_rs::WINDOWS_1256,
+    encoding_rs::WINDOWS_1257,
+    encoding_rs::WINDOWS_1258,
+    encoding_rs::WINDOWS_874,
+    encoding_rs::ISO_8859_2,
+    encoding_rs::ISO_8859_3,
+    encoding_rs::ISO_8859_4,
+    encoding_rs::ISO_8859_5,
+    encoding_rs::ISO_8859_6,
+    encoding_rs::ISO_8859_7,
+    encoding_rs::ISO_8859_8,
+    encoding_rs::ISO_8859_8_I,
+    encoding_rs::ISO_8859_10,
+    encoding_rs::ISO_8859_13,
+    encoding_rs::ISO_8859_14,
+    encoding_rs::ISO_8859_15,
+    encoding_rs::ISO_8859_16,
+    encoding_rs::KOI8_R,
+    encoding_rs::KOI8_U,
+    encoding_rs::IBM866,
+    encoding_rs::MACINTOSH,
+    encoding_rs::X_MAC_CYRILLIC,
+    encoding_rs::X_USER_DEFINED,
+];
+
+pub const DECODE_ERR_MAX: usize = 1000;
+pub const TEXT_MAX_LEN: u64 = 5 * 1024 * 1024;
+pub const TEXT_SAMPLE_LEN: u64 = 16 * 1024;
+
+///
+/// bytes of a text file
+///
+/// - sz, tm_ms, mimetype: of the file
+/// - codec: the buffer is decompressed with
+/// - is_over: larger than the max length, the buffer is only a sample
+pub struct TextBuf {
+    pub sz: u64,
+    pub tm_ms: Option<u64>,
+    pub mimetype: String,
+    pub codec: Option<&'static str>,
+    pub buffer: Vec<u8>,
+    pub is_over: bool,
+}
+
+pub struct Decoded {
+    pub text: String,
+    pub encoding: &'static Encoding,
+    pub errs: Vec<DecodeErr>,
+    pub err_cnt: usize,
+    pub bom: bool,
+}
+
+pub fn list_encodings() -> Vec<String> {
+    ENCODINGS.iter().map(|enc| enc.name().to_string()).collect()
+}
+
+/// settings key of the remembered encodings
+pub const ENC_KEY: &str = "text.encodings";
+pub const ENC_MAX: usize = 1000;
+
+///
+/// encoding remembered for a file
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RememberedEnc {
+    pub path: String,
+    pub enc: String,
+}
+
+///
+/// the encoding of the path set last, or removed by `None`. the oldest over `ENC_MAX` are dropped
+pub fn update_encodings(mut items: Vec<RememberedEnc>, path: &str, opt_enc: Option<String>) -> Vec<RememberedEnc> {
+    items.retain(|item| item.path != path);
+    if let Some(enc) = opt_enc {
+        items.push(RememberedEnc { path: path.to_string(), enc });
+    }
+    let over = items.len().saturating_sub(ENC_MAX);
+    items.drain(..over);
+    items
+}
+
+///
+/// encoding by WHATWG label
+///
+/// `"cp949"`, `"euc-kr"` -> `EUC-KR`, `"sjis"` -> `Shift_JIS`
+pub fn get_encoding(label: &str) -> Result<&'static Encoding> {
+    let label = label.trim();
+    // windows code page names that are not WHATWG labels
+    let opt_alias = match label.to_lowercase().as_str() {
+        "cp949" | "ms949" | "uhc" => Some(encoding_rs::EUC_KR),
+        "cp932" | "ms932" => Some(encoding_rs::SHIFT_JIS),
+        "cp936" | "ms936" => Some(encoding_rs::GBK),
+        "cp950" | "ms950" => Some(encoding_rs::BIG5),
+        _ => None,
+    };
+    if let Some(enc) = opt_alias {
+        return Ok(enc);
+    }
+    match Encoding::for_label(label.as_bytes()) {
+        Some(enc) if enc != encoding_rs::REPLACEMENT => Ok(enc),
+        _ => Err(ApiError::Encoding(format!("Err Label: {}", label))),
+    }
+}
+
+pub fn detect_encoding(buffer: &[u8]) -> (&'static Encoding, bool) {
+    let mut detector = EncodingDetector::new();
+    detector.feed(buffer, true);
+    detector.guess_assess(None, true)
+}
+
+///
+/// decode with replacement characters and keep malformed positions
+///
+/// - bom_sniff: `true` then BOM decides the encoding, `false` then only the BOM of `encoding` is removed
+/// - errs: up to `DECODE_ERR_MAX`, `err_cnt` is the total count
+pub fn decode_text(buffer: &[u8], encoding: &'static Encoding, bom_sniff: bool) -> Decoded {
+    let mut decoder = if bom_sniff {
+        encoding.new_decoder()
+    } else {
+        encoding.new_decoder_with_bom_removal()
+    };
+    let cap = decoder.max_utf8_buffer_length_without_replacement(buffer.len()).unwrap_or(buffer.len());
+    let mut text = String::with_capacity(cap);
+    let mut errs: Vec<DecodeErr> = vec![];
+    let mut err_cnt = 0;
+    let mut read_tot = 0;
+    // utf-16 length of text[..counted]
+    let mut counted = 0;
+    let mut chr_pos = 0;
+    loop {
+        let (result, read) = decoder.decode_to_string_without_replacement(&buffer[read_tot..], &mut text, true);
+        read_tot += read;
+        match result {
+            DecoderResult::InputEmpty => break,
+            DecoderResult::OutputFull => {
+                text.reserve(std::cmp::max((buffer.len() - read_tot) * 3, 16));
+            }
+            DecoderResult::Malformed(bad_len, extra) => {
+                err_cnt += 1;
+                if errs.len() < DECODE_ERR_MAX {
+                    chr_pos += text[counted..].encode_utf16().count();
+                    counted = text.len();
+                    let end = read_tot - extra as usize;
+                    errs.push(DecodeErr {
+                        byte_pos: end.saturating_sub(bad_len as usize) as u64,
+                        byte_len: bad_len as u32,
+                        chr_pos,
+                    });
+                }
+                text.push(char::REPLACEMENT_CHARACTER);
+            }
+        }
+    }
+    // the decoder removes the BOM only if it belongs to the decoded encoding
+    let bom = matches!(Encoding::for_bom(buffer), Some((enc, _)) if enc == decoder.encoding());
+    Decoded {
+        text,
+        bom,
+        encoding: decoder.encoding(),
+        errs,
+        err_cnt,
+    }
+}
+
+///
+/// how the encoding of `detect_encoding` was found, `assess` of the guess
+pub fn get_detection(buffer: &[u8], decoded: &Decoded, assess: bool) -> Detection {
+    if Encoding::for_bom(buffer).is_some() {
+        return Detection::Bom;
+    }
+    if buffer.is_ascii() {
+        return Detection::Ascii;
+    }
+    match (decoded.encoding == encoding_rs::UTF_8 && decoded.err_cnt == 0, assess) {
+        (true, _) => Detection::Utf8,
+        (false, true) => Detection::Guess,
+        (false, false) => Detection::WeakGuess,
+    }
+}
+
+pub fn get_eol_info(text: &str) -> EolInfo {
+    let bytes = text.as_bytes();
+    let mut info = EolInfo::default();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\r' if bytes.get(i + 1) == Some(&b'\n') => {
+                info.crlf += 1;
+                i += 1;
+            }
+            b'\r' => info.cr += 1,
+            b'\n' => info.lf += 1,
+            _ => {}
+        }
+        i += 1;
+    }
+    let counts = [(Eol::Lf, info.lf), (Eol::Crlf, info.crlf), (Eol::Cr, info.cr)];
+    info.eol = match counts.iter().filter(|(_, n)| *n > 0).count() {
+        0 => Eol::None,
+        1 => counts.iter().find(|(_, n)| *n > 0).map(|(eol, _)| eol.clone()).unwrap_or_default(),
+        _ => Eol::Mixed,
+    };
+    info.dominant = counts.iter()
+        .filter(|(_, n)| *n > 0)
+        .max_by_key(|(_, n)| *n)
+        .map(|(eol, _)| eol.clone())
+        .unwrap_or_default();
+    info
+}
+
+pub fn has_final_newline(text: &str) -> bool {
+    text.ends_with('\n') || text.ends_with('\r')
+}
+
+pub fn get_indent_info(text: &str) -> IndentInfo {
+    let mut info = IndentInfo::default();
+    // space indent width delta between consecutive indented lines -> count
+    let mut deltas: HashMap<usize, usize> = HashMap::new();
+    let mut prev = 0;
+    // lines of `\r\n`, `\n` or `\r` like `get_eol_info`, the empty piece within `\r\n` is skipped as blank
+    for line in text.split(['\r', '\n']) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let indent: &str = &line[..line.len() - line.trim_start_matches([' ', '\t']).len()];
+        let has_tab = indent.contains('\t');
+        let has_space = indent.contains(' ');
+        match (has_tab, has_space) {
+            (true, true) => info.mixed += 1,
+            (true, false) => info.tab += 1,
+            (false, true) => info.space += 1,
+            _ => {}
+        }
+        if has_tab {
+            continue;
+        }
+        let width = indent.len();
+        let delta = width.abs_diff(prev);
+        if delta > 1 {
+            *deltas.entry(delta).or_insert(0) += 1;
+        }
+        prev = width;
+    }
+    info.width = deltas.into_iter()
+        .max_by(|(a_delta, a_cnt), (b_delta, b_cnt)| a_cnt.cmp(b_cnt).then(b_delta.cmp(a_delta)))
+        .map(|(delta, _)| delta);
+    info
+}
+
+///
+/// replace all line endings with `eol`. `Mixed`, `None` keep the text as is.
+pub fn convert_eol(text: &str, eol: &Eol) -> String {
+    let sep = match eol {
+        Eol::Lf => "\n",
+        Eol::Crlf => "\r\n",
+        Eol::Cr => "\r",
+        Eol::Mixed | Eol::None => return text.to_string(),
+    };
+    let mut ret = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\r' => {
+                if chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                ret.push_str(sep);
+            }
+            '\n' => ret.push_str(sep),
+            _ => ret.push(c),
+        }
+    }
+    ret
+}
+
+fn get_bom(encoding: &'static Encoding) -> &'static [u8] {
+    if encoding == encoding_rs::UTF_8 {
+        b"\xef\xbb\xbf"
+    } else if encoding == encoding_rs::UTF_16LE {
+        b"\xff\xfe"
+    } else if encoding == encoding_rs::UTF_16BE {
+        b"\xfe\xff"
+    } else {
+        b""
+    }
+}
+
+///
+/// encode text to bytes of `encoding`
+///
+/// characters that `encoding` can not represent are an error, not replaced.
+pub fn encode_text(text: &str, encoding: &'static Encoding, bom: bool) -> Result<Vec<u8>> {
+    let mut buffer: Vec<u8> = if bom { get_bom(encoding).to_vec() } else { vec![] };
+    // encoding_rs encodes utf-16 as utf-8
+    if encoding == encoding_rs::UTF_16LE {
+        buffer.extend(text.encode_utf16().flat_map(|u| u.to_le_bytes()));
+    } else if encoding == encoding_rs::UTF_16BE {
+        buffer.extend(text.encode_utf16().flat_map(|u| u.to_be_bytes()));
+    } else {
+        let (bytes, _, had_errors) = encoding.encode(text);
+        if had_errors {
+            let (idx, c) = text.char_indices()
+                .find(|(_, c)| encoding.encode(c.encode_utf8(&mut [0u8; 4])).2)
+                .unwrap_or((0, char::REPLACEMENT_CHARACTER));
+            return Err(ApiError::Encoding(format!("Err Unmappable: {:?} at {} in {}", c, idx, encoding.name())));
+        }
+        buffer.extend_from_slice(&bytes);
+    }
+    Ok(buffer)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_encoding() {
+        assert_eq!(get_encoding("cp949").unwrap(), encoding_rs::EUC_KR);
+        assert_eq!(get_encoding(" Shift_JIS ").unwrap(), encoding_rs::SHIFT_JIS);
+        assert!(get_encoding("iso-2022-kr").is_err());
+        assert!(get_encoding("abc").is_err());
+    }
+
+    #[test]
+    fn test_decode_text() {
+        let buffer = b"ab\xffc\xea\xb0\x80\xff";
+        let decoded = decode_text(buffer, encoding_rs::UTF_8, true);
+        assert_eq!(decoded.text, "ab\u{fffd}c\u{ac00}\u{fffd}");
+        assert_eq!(decoded.err_cnt, 2);
+        assert_eq!(decoded.errs[0].byte_pos, 2);
+        assert_eq!(decoded.errs[0].chr_pos, 2);
+        assert_eq!(decoded.errs[1].byte_pos, 7);
+        assert_eq!(decoded.errs[1].chr_pos, 5);
+        assert_eq!(get_detection(buffer, &decoded, true), Detection::Guess);
+        assert_eq!(get_detection(buffer, &decoded, false), Detection::WeakGuess);
+        let decoded = decode_text("가".as_bytes(), encoding_rs::UTF_8, true);
+        assert_eq!(get_detection("가".as_bytes(), &decoded, false), Detection::Utf8);
+        assert_eq!(get_detection(b"abc", &decoded, false), Detection::Ascii);
+
+        let decoded = decode_text(b"\xef\xbb\xbfabc", encoding_rs::UTF_8, false);
+        assert_eq!(decoded.text, "abc");
+        assert_eq!(decoded.err_cnt, 0);
+    }
+
+    #[test]
+    fn test_text_info() {
+        let eol = get_eol_info("a\r\nb\r\nc\nd");
+        assert_eq!((eol.eol, eol.dominant, eol.lf, eol.crlf, eol.cr), (Eol::Mixed, Eol::Crlf, 1, 2, 0));
+        assert_eq!(get_eol_info("a\rb\r").eol, Eol::Cr);
+        assert_eq!(get_eol_info("abc").eol, Eol::None);
+        assert!(has_final_newline("a\r\n"));
+        assert!(!has_final_newline("a\nb"));
+
+        let indent = get_indent_info("fn a() {\n    if b {\n        c();\n    }\n}\n\tx\n\t  y\n");
+        assert_eq!((indent.tab, indent.space, indent.mixed, indent.width), (1, 3, 1, Some(4)));
+        let indent = get_indent_info("fn a() {\r    if b {\r        c();\r    }\r}\r\tx\r\n\t  y\r\n");
+        assert_eq!((indent.tab, indent.space, indent.mixed, indent.width), (1, 3, 1, Some(4)));
+
+        let decoded = decode_text(b"\xef\xbb\xbfabc", encoding_rs::UTF_8, true);
+        assert!(decoded.bom);
+        let decoded = decode_text(b"\xef\xbb\xbfabc", encoding_rs::WINDOWS_1252, false);
+        assert!(!decoded.bom);
+    }
+
+    #[test]
+    fn test_encode_text() {
+        assert_eq!(convert_eol("a\nb\r\nc\rd", &Eol::Crlf), "a\r\nb\r\nc\r\nd");
+        assert_eq!(convert_eol("a\r\nb", &Eol::Lf), "a\nb");
+        assert_eq!(convert_eol("a\r\nb\n", &Eol::Mixed), "a\r\nb\n");
+
+        let bytes = encode_text("한글", encoding_rs::EUC_KR, false).unwrap();
+        assert_eq!(decode_text(&bytes, encoding_rs::EUC_KR, false).text, "한글");
+        let bytes = encode_text("a", encoding_rs::UTF_16LE, true).unwrap();
+        assert_eq!(bytes, b"\xff\xfea\x00");
+        let bytes = encode_text("a", encoding_rs::UTF_8, true).unwrap();
+        assert_eq!(bytes, b"\xef\xbb\xbfa");
+        assert!(encode_text("a한", encoding_rs::WINDOWS_1252, false).is_err());
+    }
+
+    #[test]
+    fn test_update_encodings() {
+        let items = update_encodings(vec![], "/a.txt", Some(String::from("EUC-KR")));
+        let items = update_encodings(items, "/b.txt", Some(String::from("Shift_JIS")));
+        let items = update_encodings(items, "/a.txt", Some(String::from("UTF-8")));
+        assert_eq!(items.iter().map(|item| (item.path.as_str(), item.enc.as_str())).collect::<Vec<_>>(),
+                   vec![("/b.txt", "Shift_JIS"), ("/a.txt", "UTF-8")]);
+        assert_eq!(update_encodings(items, "/b.txt", None).len(), 1);
+
+        let mut items = vec![];
+        for i in 0..=ENC_MAX {
+            items = update_encodings(items, &format!("/{}.txt", i), Some(String::from("UTF-8")));
+        }
+        assert_eq!((items.len(), items[0].path.as_str()), (ENC_MAX, "/1.txt"));
+    }
+
+    #[test]
+    fn test_list_encodings() {
+        let encodings = list_encodings();
+        assert!(encodings.iter().all(|nm| get_encoding(nm).is_ok()));
+    }
+}
@@ -0,0 +1,187 @@
+use std::fs::Metadata;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader};
+use image::metadata::Orientation;
+use sha2::{Digest, Sha256};
+use crate::img::get_exif_orientation;
+use crate::models::{ApiError, Thumb};
+use crate::stream::decode_uri_path;
+use crate::system_time_ext::SystemTimeExt;
+
+type Result<T> = std::result::Result<T, ApiError>;
+
+pub const THUMB_SCHEME: &str = "thumb";
+pub const THUMB_SIZE: u32 = 256;
+pub const THUMB_SIZE_MAX: u32 = 1024;
+pub const THUMB_WORKERS: usize = 4;
+pub const THUMB_CACHE_MAX_LEN: u64 = 512 * 1024 * 1024;
+pub const THUMB_CACHE_MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+pub fn get_thumb_dir() -> PathBuf {
+    dirs_next::cache_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("tr-viewer")
+        .join("thumb")
+}
+
+///
+/// cache key of sha-256 of `path|mtime|size|thumb size`, the same across builds
+pub fn get_thumb_key(path: &Path, meta: &Metadata, size: u32) -> String {
+    let tm_ms = meta.modified().map(|t| t.to_ms()).unwrap_or(0);
+    let digest = Sha256::digest(format!("{}|{}|{}|{}", path.to_string_lossy(), tm_ms, meta.len(), size));
+    digest[..16].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+///
+/// cached thumb of the key. jpeg for opaque images, png for images with alpha
+pub fn read_thumb_cache(key: &str) -> Option<Thumb> {
+    let dir = get_thumb_dir();
+    [("jpg", "image/jpeg"), ("png", "image/png")].iter().find_map(|(ext, mimetype)| {
+        std::fs::read(dir.join(format!("{}.{}", key, ext))).ok().map(|body| Thumb {
+            body,
+            mimetype: mimetype.to_string(),
+            etag: key.to_string(),
+        })
+    })
+}
+
+pub fn write_thumb_cache(thumb: &Thumb) -> Result<()> {
+    let dir = get_thumb_dir();
+    std::fs::create_dir_all(&dir)?;
+    let ext = if thumb.mimetype == "image/png" { "png" } else { "jpg" };
+    let path = dir.join(format!("{}.{}", thumb.etag, ext));
+    let tmp_path = dir.join(format!("{}.{}.{}.tmp", thumb.etag, ext, std::process::id()));
+    std::fs::write(&tmp_path, &thumb.body)?;
+    std::fs::rename(&tmp_path, &path)?;
+    Ok(())
+}
+
+///
+/// removes thumbs made before `max_age`, then the oldest over `max_len` in total. (removed count)
+pub fn prune_thumb_cache(dir: &Path, max_len: u64, max_age: Duration) -> Result<usize> {
+    let now = SystemTime::now();
+    let mut files: Vec<(PathBuf, SystemTime, u64)> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let meta = entry.metadata().ok()?;
+            meta.is_file().then(|| (entry.path(), meta.modified().unwrap_or(now), meta.len()))
+        })
+        .collect();
+    files.sort_by(|a, b| b.1.cmp(&a.1));
+    let mut tot: u64 = 0;
+    let mut cnt = 0;
+    for (path, tm, len) in files {
+        let is_old = now.duration_since(tm).is_ok_and(|age| age > max_age);
+        if !is_old {
+            tot += len;
+        }
+        if (is_old || tot > max_len) && std::fs::remove_file(&path).is_ok() {
+            cnt += 1;
+        }
+    }
+    Ok(cnt)
+}
+
+///
+/// decode, downscale to fit in `size` x `size` and rotate by the exif orientation
+pub fn make_thumb(path: &Path, size: u32, key: &str) -> Result<Thumb> {
+    let mut decoder = ImageReader::open(path)?.with_guessed_format()?.into_decoder()?;
+    let orientation = match decoder.orientation() {
+        Ok(Orientation::NoTransforms) | Err(_) => get_exif_orientation(path)
+            .and_then(|o| Orientation::from_exif(o as u8))
+            .unwrap_or(Orientation::NoTransforms),
+        Ok(orientation) => orientation,
+    };
+    let img = DynamicImage::from_decoder(decoder)?;
+    encode_thumb(img, orientation, size, key)
+}
+
+///
+/// thumb of an encoded image in memory, e.g. the cover art of an audio file
+pub fn make_thumb_from_buf(buf: &[u8], size: u32, key: &str) -> Result<Thumb> {
+    let img = image::load_from_memory(buf)?;
+    encode_thumb(img, Orientation::NoTransforms, size, key)
+}
+
+fn encode_thumb(img: DynamicImage, orientation: Orientation, size: u32, key: &str) -> Result<Thumb> {
+    let mut thumb = if img.width() > size || img.height() > size {
+        img.thumbnail(size, size)
+    } else {
+        img
+    };
+    thumb.apply_orientation(orientation);
+
+    let mut body: Vec<u8> = vec![];
+    let mimetype = if thumb.color().has_alpha() {
+        thumb.write_to(&mut Cursor::new(&mut body), ImageFormat::Png)?;
+        "image/png"
+    } else {
+        DynamicImage::ImageRgb8(thumb.to_rgb8()).write_to(&mut Cursor::new(&mut body), ImageFormat::Jpeg)?;
+        "image/jpeg"
+    };
+    Ok(Thumb {
+        body,
+        mimetype: mimetype.to_string(),
+        etag: key.to_string(),
+    })
+}
+
+///
+/// `thumb://localhost/C%3A%5Cphoto%5Ca.jpg?size=256` -> (`C:\photo\a.jpg`, 256)
+pub fn parse_thumb_uri(path: &str, query: Option<&str>) -> Option<(String, u32)> {
+    let path_str = decode_uri_path(path)?;
+    let size = query.unwrap_or("")
+        .split('&')
+        .filter_map(|kv| kv.split_once('='))
+        .find(|(k, _)| *k == "size")
+        .and_then(|(_, v)| v.parse::<u32>().ok())
+        .unwrap_or(THUMB_SIZE)
+        .clamp(16, THUMB_SIZE_MAX);
+    Some((path_str, size))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn test_parse_thumb_uri() {
+        assert_eq!(parse_thumb_uri("/C%3A%5Cphoto%5Ca.jpg", Some("size=128")), Some((String::from(r"C:\photo\a.jpg"), 128)));
+        assert_eq!(parse_thumb_uri("/%2Ftmp%2Fa.png", None), Some((String::from("/tmp/a.png"), THUMB_SIZE)));
+        assert_eq!(parse_thumb_uri("/a.png", Some("size=99999")).map(|(_, size)| size), Some(THUMB_SIZE_MAX));
+        assert_eq!(parse_thumb_uri("/", None), None);
+    }
+
+    #[test]
+    fn test_make_thumb() {
+        let tmp = temp_dir();
+        let path = tmp.path().join("make_thumb.png");
+        image::RgbImage::new(600, 300).save(&path).unwrap();
+        let thumb = make_thumb(&path, THUMB_SIZE, "test").unwrap();
+        assert_eq!(thumb.mimetype, "image/jpeg");
+        let img = image::load_from_memory(&thumb.body).unwrap();
+        assert_eq!((img.width(), img.height()), (256, 128));
+
+        let meta = path.metadata().unwrap();
+        let key = get_thumb_key(&path, &meta, THUMB_SIZE);
+        assert_eq!(key.len(), 32);
+        assert_eq!(key, get_thumb_key(&path, &meta, THUMB_SIZE));
+        assert_ne!(key, get_thumb_key(&path, &meta, THUMB_SIZE_MAX));
+    }
+
+    #[test]
+    fn test_prune_thumb_cache() {
+        let tmp = temp_dir();
+        let dir = tmp.path();
+        for nm in ["a.jpg", "b.jpg", "c.jpg"] {
+            std::fs::write(dir.join(nm), [0u8; 100]).unwrap();
+        }
+        assert_eq!(prune_thumb_cache(dir, 1000, THUMB_CACHE_MAX_AGE).unwrap(), 0);
+        assert_eq!(prune_thumb_cache(dir, 250, THUMB_CACHE_MAX_AGE).unwrap(), 1);
+        assert_eq!(prune_thumb_cache(dir, 1000, Duration::ZERO).unwrap(), 2);
+    }
+}
@@ -1,21 +1,25 @@
 import { invoke } from "@tauri-apps/api/core";
 import { revealItemInDir, openPath, openUrl } from '@tauri-apps/plugin-opener';
-import {DiskInfo, Folder, OptParams, TextContent, HomeType} from "./bindings.ts";
+import {Bookmark, DiskInfo, Folder, OptParams, TextContent, HomeType, JsonValue} from "./bindings.ts";
 
-export const readText = async (pathStr: string): Promise<TextContent> => {
-    return await invoke("read_text", {pathStr})
+export const readText = async (pathStr: string, enc?: string): Promise<TextContent> => {
+    return await invoke("read_text", {pathStr, enc})
 }
 
 export const readFolder = async (params: OptParams): Promise<Folder> => {
     return await invoke("read_folder", {params})
 }
 
-export const setState = async (key: string, val?: string): Promise<string | undefined> => {
+export const setState = async (key: string, val?: JsonValue): Promise<JsonValue | undefined> => {
     return await invoke("set_state", {key, val})
 }
 
-export const getState = async (key: string, default_val?: string): Promise<string | undefined> => {
-    return await invoke("get_state", {key, default_val})
+export const getState = async (key: string, defaultVal?: JsonValue): Promise<JsonValue | undefined> => {
+    return await invoke("get_state", {key, defaultVal})
+}
+
+export const listState = async (prefix: string): Promise<{[key: string]: JsonValue}> => {
+    return await invoke("list_state", {prefix})
 }
 
 export const getHomeDir = async (): Promise<{[key in HomeType]: string}> => {
@@ -30,6 +34,10 @@ export const getArgPath = async (): Promise<string | undefined> => {
     return await invoke("get_arg_path")
 }
 
+export const getBookmarks = async (): Promise<Bookmark[]> => {
+    return await invoke("get_bookmarks")
+}
+
 export const shellOpenPath = async (path?: string): Promise<void> => {
     if (!path) return
     return await openPath(path)
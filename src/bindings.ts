@@ -8,14 +8,284 @@ export const commands = {
 async greet(name: string) : Promise<string> {
     return await TAURI_INVOKE("greet", { name });
 },
-async readText(pathStr: string) : Promise<Result<TextContent, ApiError>> {
+/**
+ * read text
+ * 
+ * # arg
+ * - path_str
+ * - enc: encoding label (`"cp949"`, `"Shift_JIS"`, ...). If `None` then the remembered encoding of the file or detected one.
+ * The given encoding is remembered for the file.
+ */
+async readText(pathStr: string, enc: string | null) : Promise<Result<TextContent, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("read_text", { pathStr, enc }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * write text
+ * 
+ * writes a temp file and renames it over the original. files in an archive and compressed files are read only.
+ * 
+ * # arg
+ * - path_str
+ * - text
+ * - opts: pass `enc`, `bom`, `eol`, `sz`, `tm_ms` of `read_text` to keep the encoding and line endings and to detect conflicts
+ */
+async writeText(pathStr: string, text: string, opts: WriteOpts) : Promise<Result<WriteInfo, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("write_text", { pathStr, text, opts }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * set remembered encoding of the file
+ * 
+ * # arg
+ * - path_str
+ * - enc: encoding label. if `None` then forget and detect again. kept in the settings by `text.encodings`
+ */
+async setEncoding(pathStr: string, enc: string | null) : Promise<Result<string | null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_encoding", { pathStr, enc }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async listEncodings() : Promise<string[]> {
+    return await TAURI_INVOKE("list_encodings");
+},
+/**
+ * image info
+ * 
+ * dimensions, color type, frame count of animated images and exif
+ */
+async imageInfo(pathStr: string) : Promise<Result<ImgInfo, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("image_info", { pathStr }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * read JSON, YAML, TOML or XML as a tree
+ * 
+ * returns the node at `path_expr` and its children. the parsed document is cached,
+ * so call again with the `path` of a child to expand it.
+ * 
+ * # arg
+ * - path_str
+ * - path_expr: json pointer (`/servers/0`). If `None` then the root
+ * - skip_n, take_n: paging of the children (default 1000)
+ */
+async readStructured(pathStr: string, pathExpr: string | null, skipN: number | null, takeN: number | null) : Promise<Result<StructContent, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("read_structured", { pathStr, pathExpr, skipN, takeN }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * read a page of CSV/TSV rows
+ * 
+ * delimiter, header and encoding are sniffed unless given. row offsets, and row orders
+ * of sorts and filters are cached per file.
+ * 
+ * # arg
+ * - path_str
+ * - opts: `sorts`, `filters`, `skip_n`, `take_n` (default 100, max 1000)
+ */
+async readTable(pathStr: string, opts: TableOpts) : Promise<Result<TableContent, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("read_table", { pathStr, opts }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * read a page of rows of a table or view in a SQLite database
+ * 
+ * the database is opened read only. tables are listed by `read_folder` of the database file.
+ * 
+ * # arg
+ * - path_str: path of the table (`C:\data\app.db\users`)
+ * - opts: `sorts`, `filters`, `skip_n`, `take_n` (default 100, max 1000). others are ignored
+ */
+async readDb(pathStr: string, opts: TableOpts) : Promise<Result<DbContent, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("read_db", { pathStr, opts }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * render markdown to sanitized html with heading ids and a table of contents
+ * 
+ * relative images are rewritten to stream urls of the folder of the file.
+ * 
+ * # arg
+ * - path_str
+ */
+async renderMarkdown(pathStr: string) : Promise<Result<MarkdownContent, ApiError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("read_text", { pathStr }) };
+    return { status: "ok", data: await TAURI_INVOKE("render_markdown", { pathStr }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * read a html file sanitized for the preview
+ * 
+ * scripts, event handlers and remote images are removed by a whitelist, local images
+ * are rewritten to stream urls. what was removed is counted by name.
+ * 
+ * # arg
+ * - path_str
+ */
+async readHtmlSafe(pathStr: string) : Promise<Result<HtmlContent, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("read_html_safe", { pathStr }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * extract text and properties of docx, xlsx, pptx, odt, ods and odp
+ * 
+ * paragraphs of documents, cell values of sheets (up to 1000 rows each) or paragraphs of slides.
+ * 
+ * # arg
+ * - path_str
+ */
+async readOffice(pathStr: string) : Promise<Result<OfficeContent, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("read_office", { pathStr }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * read page count, document info, outline and text of pages of a pdf
+ * 
+ * # arg
+ * - path_str
+ * - pages: 1-based page numbers to extract text (max 100). If `None` then the first 10 pages
+ */
+async readPdf(pathStr: string, pages: number[] | null) : Promise<Result<PdfContent, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("read_pdf", { pathStr, pages }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * read duration, tracks, tags and cover art of an audio or video file
+ * 
+ * the cover art is served by `thumb://localhost/{path}`
+ * 
+ * # arg
+ * - path_str
+ */
+async mediaInfo(pathStr: string) : Promise<Result<MediaInfo, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("media_info", { pathStr }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * line and word diff of two text files, decoded like `read_text`
+ * 
+ * a file larger than 5MB is cut to its head and `is_over` is set
+ * 
+ * # arg
+ * - left, right: paths of the old and the new file
+ * - opts: ignore whitespace or line endings, context lines and word diff
+ */
+async diffFiles(left: string, right: string, opts: DiffOpts) : Promise<Result<DiffContent, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("diff_files", { left, right, opts }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * read hex dump
+ * 
+ * # arg
+ * - path_str
+ * - offset: start byte offset
+ * - len: byte length (max 64KB)
+ */
+async readHex(pathStr: string, offset: number, len: number) : Promise<Result<HexContent, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("read_hex", { pathStr, offset, len }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * search byte pattern
+ * 
+ * # arg
+ * - path_str
+ * - pattern: hex bytes `"89 50 4e 47"` or quoted text `"\"IHDR\""`
+ * - offset: start byte offset
+ * - take_n: max match count. `next_offset` is set if more matches exist
+ * - id: to cancel by `cancel_search`
+ */
+async searchHex(pathStr: string, pattern: string, offset: number, takeN: number | null, id: string | null) : Promise<Result<HexSearch, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("search_hex", { pathStr, pattern, offset, takeN, id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * regex search of a text file of any size, streamed in its encoding
+ * 
+ * returns up to `take_n` matches (default 100, max 1000). pass `next_offset` and `next_line`
+ * as `offset` and `line` for the next page
+ * 
+ * # arg
+ * - path_str
+ * - pattern: regex, or plain text if `literal`
+ * - opts: encoding, case, whole word, context lines, start of the page and the id to cancel
+ */
+async searchInFile(pathStr: string, pattern: string, opts: SearchOpts) : Promise<Result<TextSearch, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("search_in_file", { pathStr, pattern, opts }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * cancel a running `search_in_file` of `opts.id` or `search_hex` of `id`
+ * 
+ * # arg
+ * - id
+ */
+async cancelSearch(id: string) : Promise<boolean> {
+    return await TAURI_INVOKE("cancel_search", { id });
+},
 async readFolder(params: OptParams) : Promise<Result<Folder, ApiError>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("read_folder", { params }) };
@@ -27,15 +297,17 @@ async readFolder(params: OptParams) : Promise<Result<Folder, ApiError>> {
 /**
  * set state
  * 
+ * saved to `settings.json` in the config dir
+ * 
  * # arg
- * - key
- * - opt_val: if `None` then delete cache
+ * - key: `namespace.name` such as `folder.listOrder`
+ * - val: json value. if `None` then delete it
  * 
  * # Examples
  * ```
  * ```
  */
-async setState(key: string, val: string | null) : Promise<Result<string | null, ApiError>> {
+async setState(key: string, val: JsonValue | null) : Promise<Result<JsonValue | null, ApiError>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("set_state", { key, val }) };
 } catch (e) {
@@ -47,10 +319,10 @@ async setState(key: string, val: string | null) : Promise<Result<string | null,
  * get state
  * 
  * # arg
- * - key
- * - default_val: If the key does not exist in the cache, insert the default value and return it.
+ * - key: `namespace.name`
+ * - default_val: If the key does not exist, save the default value and return it.
  */
-async getState(key: string, defaultVal: string | null) : Promise<Result<string | null, ApiError>> {
+async getState(key: string, defaultVal: JsonValue | null) : Promise<Result<JsonValue | null, ApiError>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("get_state", { key, defaultVal }) };
 } catch (e) {
@@ -58,6 +330,35 @@ async getState(key: string, defaultVal: string | null) : Promise<Result<string |
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * states of the keys starting with the prefix
+ * 
+ * # arg
+ * - prefix: `"folder."` for a namespace
+ */
+async listState(prefix: string) : Promise<Result<Partial<{ [key in string]: JsonValue }>, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_state", { prefix }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * `StateChanged` events of the window are filtered by the prefix.
+ * Listen with the window (`events.stateChanged(getCurrentWebviewWindow())`), a global listener gets all of them.
+ * 
+ * # arg
+ * - prefix: `"folder."`. If `None` then clear the prefixes of the window
+ */
+async subscribeState(prefix: string | null) : Promise<Result<string[], ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("subscribe_state", { prefix }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async getHomeDir() : Promise<Result<Partial<{ [key in HomeType]: string }>, ApiError>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("get_home_dir") };
@@ -81,12 +382,143 @@ async getArgPath() : Promise<Result<string | null, ApiError>> {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
+},
+/**
+ * access policy of the paths, read once at the start. A path outside of it is a `Policy` error
+ */
+async getPolicy() : Promise<Result<AccessPolicy, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_policy") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * bookmarks in order, `dir` and `exists` are checked now
+ * changes of the bookmarks are notified as `StateChanged` of the key `bookmark.items`
+ */
+async getBookmarks() : Promise<Result<Bookmark[], ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_bookmarks") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * add a bookmark at the end
+ * 
+ * # arg
+ * - path_str: folder or file
+ * - nm: If `None` then the file name
+ * - group: If `None` then no group
+ */
+async addBookmark(pathStr: string, nm: string | null, group: string | null) : Promise<Result<Bookmark, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("add_bookmark", { pathStr, nm, group }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * remove a bookmark
+ * 
+ * # arg
+ * - id
+ */
+async removeBookmark(id: string) : Promise<Result<Bookmark, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_bookmark", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * rename a bookmark
+ * 
+ * # arg
+ * - id
+ * - nm
+ */
+async renameBookmark(id: string, nm: string) : Promise<Result<Bookmark, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("rename_bookmark", { id, nm }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * move a bookmark to a group
+ * 
+ * # arg
+ * - id
+ * - group: If `None` then out of its group
+ */
+async setBookmarkGroup(id: string, group: string | null) : Promise<Result<Bookmark, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_bookmark_group", { id, group }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * reorder bookmarks
+ * 
+ * # arg
+ * - ids: bookmarks in the new order, the others follow as they were
+ */
+async reorderBookmarks(ids: string[]) : Promise<Result<Bookmark[], ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("reorder_bookmarks", { ids }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * write bookmarks to a json file and return the count
+ * 
+ * # arg
+ * - path_str
+ */
+async exportBookmarks(pathStr: string) : Promise<Result<number, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("export_bookmarks", { pathStr }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * read bookmarks of a json file and return the count added. Paths already bookmarked are skipped.
+ * 
+ * # arg
+ * - path_str
+ * - replace: remove the current bookmarks first
+ */
+async importBookmarks(pathStr: string, replace: boolean) : Promise<Result<number, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("import_bookmarks", { pathStr, replace }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
 }
 }
 
 /** user-defined events **/
 
 
+export const events = __makeEvents__<{
+stateChanged: StateChanged
+}>({
+stateChanged: "state-changed"
+})
 
 /** user-defined constants **/
 
@@ -94,17 +526,336 @@ async getArgPath() : Promise<Result<string | null, ApiError>> {
 
 /** user-defined types **/
 
-export type ApiError = { Io: string } | { Json: string } | { Folder: string } | { DirApi: string }
+/**
+ * access policy of the paths, `policy.json` in the config dir or the file of `TR_VIEWER_POLICY`
+ * 
+ * - allow: roots that can be accessed. If empty then any path that is not denied
+ * - deny: roots that can not be accessed, even under an allowed root
+ * - read_only: files are not written
+ */
+export type AccessPolicy = { allow: string[]; deny: string[]; read_only: boolean }
+export type ApiError = { Io: string } | { Json: string } | { Folder: string } | { DirApi: string } | { Hex: string } | { Encoding: string } | { Conflict: string } | { Img: string } | { Archive: string } | { Compress: string } | { Struct: string } | { Table: string } | { Html: string } | { Db: string } | { Office: string } | { Pdf: string } | { Media: string } | { Diff: string } | { Search: string } | { Policy: string } | { Settings: string } | { Bookmark: string }
+/**
+ * pinned place of the left pane
+ * 
+ * - nm: shown name, the file name if not given
+ * - group: `None` for no group
+ * - dir, exists: checked by `get_bookmarks`, `None` in the file
+ */
+export type Bookmark = { id: string; nm: string; path: string; group?: string | null; dir?: boolean | null; exists?: boolean | null }
+export type ColType = "String" | "Int" | "Float" | "Bool" | "Date"
+/**
+ * column of a database table
+ * 
+ * - decl: declared type, `kind` is by its affinity
+ * - dflt: default value as sql
+ */
+export type DbCol = { nm: string; decl: string; kind: ColType; not_null: boolean; pk: boolean; dflt?: string | null }
+/**
+ * page of a database table or view
+ * 
+ * - path: of the table, `C:\data\app.db\users`
+ * - kind: `table` or `view`, sql: `CREATE` statement
+ * - row_cnt: rows of the table, tot: rows after filters
+ * - rows: `NULL` is `None`, blobs are shown as `x'0102' (2 bytes)`
+ */
+export type DbContent = { path: string; kind: string; sql?: string | null; cols: DbCol[]; row_cnt: number; tot: number; skip_n: number; take_n: number; rows: (string | null)[][] }
+/**
+ * malformed byte sequence replaced with U+FFFD
+ * 
+ * - byte_pos: byte offset in the file
+ * - chr_pos: utf-16 offset in the text (javascript string index)
+ */
+export type DecodeErr = { byte_pos: number; byte_len: number; chr_pos: number }
+/**
+ * how the encoding of a text is detected
+ * 
+ * - Bom: by the byte order mark
+ * - Ascii: ascii only, the same in any ascii compatible encoding
+ * - Utf8: valid UTF-8
+ * - Guess: guessed from the bytes
+ * - WeakGuess: guessed without a clear winner, likely to be wrong
+ */
+export type Detection = "Bom" | "Ascii" | "Utf8" | "Guess" | "WeakGuess"
+/**
+ * diff of two text files
+ * 
+ * - left_enc, right_enc: encoding the files are decoded with
+ * - unified: unified diff text of the hunks
+ * - add_cnt, del_cnt: inserted and deleted lines
+ * - is_over: a file is cut to the max length or the hunks to the max lines
+ */
+export type DiffContent = { left: string; right: string; left_enc?: string | null; right_enc?: string | null; hunks: DiffHunk[]; unified: string; add_cnt: number; del_cnt: number; is_over: boolean }
+/**
+ * - old_start, new_start: 1-based first line, `@@ -old_start,old_len +new_start,new_len @@`
+ */
+export type DiffHunk = { old_start: number; old_len: number; new_start: number; new_len: number; lines: DiffLine[] }
+export type DiffKind = "Equal" | "Delete" | "Insert"
+/**
+ * - old_no, new_no: 1-based line number in the left and the right
+ * - text: without the line ending
+ * - words: changed words of a replaced line
+ */
+export type DiffLine = { kind: DiffKind; old_no?: number | null; new_no?: number | null; text: string; words?: DiffWord[] | null }
+/**
+ * diff options
+ * 
+ * - ignore_ws: ignore all whitespace in lines
+ * - ignore_eol: ignore `\r\n` vs `\n` and a missing final newline
+ * - ctx: lines of context around changes, 3 if `None`
+ * - words: word diff of changed lines
+ */
+export type DiffOpts = { ignore_ws?: boolean | null; ignore_eol?: boolean | null; ctx?: number | null; words?: boolean | null }
+export type DiffWord = { kind: DiffKind; text: string }
 export type DiskInfo = { path: string }
+/**
+ * document properties
+ * 
+ * - author: creator of the document, modified_by: last modified by
+ * - created, modified: as stored, `2024-05-01T10:00:00Z`
+ * - pages: pages of docx, odt or slides of pptx
+ */
+export type DocProps = { title?: string | null; subject?: string | null; author?: string | null; modified_by?: string | null; created?: string | null; modified?: string | null; app?: string | null; pages?: number | null }
+export type Eol = "None" | "Lf" | "Crlf" | "Cr" | "Mixed"
+/**
+ * line ending counts
+ * 
+ * - eol: the only kind used, `Mixed` if more than one kind, `None` if single line
+ * - dominant: the most used kind
+ */
+export type EolInfo = { eol: Eol; dominant: Eol; lf: number; crlf: number; cr: number }
+/**
+ * exif of the primary image
+ * 
+ * - dt: capture date `2024-01-31 12:34:56`, dt_sec: unix time of dt
+ * - orientation: 1 ~ 8
+ * - lat, lon: degrees, alt: meters
+ */
+export type ExifInfo = { make?: string | null; model?: string | null; lens?: string | null; dt?: string | null; dt_sec?: number | null; orientation?: number | null; exposure?: string | null; f_number?: number | null; iso?: number | null; focal_len?: number | null; lat?: number | null; lon?: number | null; alt?: number | null; tags: ExifTag[] }
+export type ExifTag = { ifd: number; tag: string; val: string }
+export type FilterOp = "Eq" | "Ne" | "Contains" | "Gt" | "Ge" | "Lt" | "Le"
 export type Folder = { item: Item; path_param: string; base_nm: string; tot?: number | null; cnt?: number | null; skip_n?: number | null; take_n?: number | null; ordering?: OrdItem[] | null }
+export type HexContent = { path: string; mimetype: string; magic?: MagicInfo | null; sz: number; offset: number; len: number; rows: HexRow[] }
+export type HexRow = { offset: number; hex: string; ascii: string }
+/**
+ * - next_offset: of the next match over `take_n`, or to resume from if cancelled
+ * - is_cancelled: stopped by `cancel_search`, `offsets` are those found until then
+ */
+export type HexSearch = { path: string; pattern: string; offsets: number[]; next_offset?: number | null; is_cancelled: boolean }
 export type HomeType = "RootDir" | "HomeDir" | "DownloadDir" | "VideoDir" | "DocumentDir" | "DesktopDir" | "PictureDir" | "AudioDir" | "ConfigDir" | "DataDir" | "DataLocalDir" | "CacheDir" | "FontDir" | "PublicDir" | "ExecutableDir" | "RuntimeDir" | "TemplateDir"
-export type Item = { nm: string; dir: boolean; ext?: string | null; mt?: string | null; sz?: number | null; tm?: number | null; items?: Item[] | null }
-export type MetaType = "Sz" | "Tm" | "Mt" | "Ext"
+/**
+ * sanitized html of a html file
+ * 
+ * - removed_tags, removed_attrs: scripts, event handlers, frames, forms, `javascript:` urls, ...
+ * - remote_urls: images from other hosts, not loaded
+ */
+export type HtmlContent = { path: string; html: string; removed_tags: RemovedItem[]; removed_attrs: RemovedItem[]; remote_urls: string[]; sz?: number | null; tm_ms?: number | null }
+export type ImgInfo = { path: string; format: string; mimetype: string; w: number; h: number; color: string; frames?: number | null; exif?: ExifInfo | null }
+/**
+ * image meta of listing
+ * 
+ * - dt: capture date (unix time) from exif
+ */
+export type ImgMeta = { w: number; h: number; dt?: number | null }
+/**
+ * indentation counts of lines
+ * 
+ * - mixed: lines indented with both tab and space
+ * - width: guessed space indent width
+ */
+export type IndentInfo = { tab: number; space: number; mixed: number; width?: number | null }
+export type Item = { nm: string; dir: boolean; ext?: string | null; mt?: string | null; sz?: number | null; tm?: number | null; lang?: string | null; img?: ImgMeta | null; cnt?: number | null; pages?: number | null; media?: MediaMeta | null; items?: Item[] | null }
+export type JsonValue = null | boolean | number | string | JsonValue[] | Partial<{ [key in string]: JsonValue }>
+export type MagicInfo = { mimetype: string; ext: string; kind: string; desc?: string | null }
+/**
+ * sanitized html of a markdown document
+ * 
+ * - toc: headings in order
+ */
+export type MarkdownContent = { path: string; html: string; toc: TocItem[]; sz?: number | null; tm_ms?: number | null }
+/**
+ * embedded cover art, served as a thumbnail of the media file
+ * 
+ * - mimetype: `image/jpeg`, ...
+ * - sz: bytes of the image
+ */
+export type MediaCover = { mimetype: string; w?: number | null; h?: number | null; sz: number }
+/**
+ * container metadata of an audio or video file
+ * 
+ * - format: container, `mp3`, `mp4`, `mkv`, ...
+ * - bitrate: overall bits per second
+ */
+export type MediaInfo = { path: string; format: string; sz: number; bitrate?: number | null; meta: MediaMeta; tracks: MediaTrack[]; tags: MediaTag[]; cover?: MediaCover | null }
+/**
+ * audio or video meta of listing
+ * 
+ * - dur_ms: duration in milliseconds
+ */
+export type MediaMeta = { dur_ms?: number | null; title?: string | null; artist?: string | null; album?: string | null }
+/**
+ * - key: standard key `TrackTitle`, `Artist`, ... or the key in the file
+ */
+export type MediaTag = { key: string; val: string }
+/**
+ * audio or video track of a media file
+ * 
+ * - codec: `mp3`, `aac`, `flac`, `h264`, ...
+ * - bits: bits per sample
+ * - w, h: resolution of a video track
+ * - dur_ms: duration in milliseconds
+ */
+export type MediaTrack = { kind: TrackKind; codec?: string | null; sample_rate?: number | null; channels?: number | null; bits?: number | null; w?: number | null; h?: number | null; dur_ms?: number | null; lang?: string | null }
+export type MetaType = "Sz" | "Tm" | "Mt" | "Ext" | "Lang" | "Img" | "Pages" | "Media"
+export type NodeType = "Object" | "Array" | "String" | "Number" | "Bool" | "Null"
+/**
+ * text of an office document
+ * 
+ * - paras: paragraphs of docx, odt
+ * - sheets: xlsx, ods
+ * - slides: pptx, odp
+ */
+export type OfficeContent = { path: string; format: OfficeFormat; props: DocProps; paras?: string[] | null; sheets?: OfficeSheet[] | null; slides?: OfficeSlide[] | null }
+export type OfficeFormat = "Docx" | "Xlsx" | "Pptx" | "Odt" | "Ods" | "Odp"
+/**
+ * - row_cnt: rows of the sheet, `rows` are up to 1000
+ */
+export type OfficeSheet = { nm: string; rows: string[][]; row_cnt: number }
+/**
+ * - no: 1-based in the order of the presentation
+ */
+export type OfficeSlide = { no: number; nm?: string | null; paras: string[] }
 export type OptParams = { path_str?: string | null; meta_types?: MetaType[] | null; ordering?: OrdItem[] | null; skip_n?: number | null; take_n?: number | null; is_pretty?: boolean | null; cache_nm?: string | null }
 export type OrdItem = { nm: OrderBy; asc: OrderAsc }
 export type OrderAsc = "Asc" | "Desc"
-export type OrderBy = "Dir" | "Nm" | "Sz" | "Tm" | "Mt" | "Ext"
-export type TextContent = { path: string; mimetype: string; enc?: string | null; text?: string | null }
+export type OrderBy = "Dir" | "Nm" | "Sz" | "Tm" | "Mt" | "Ext" | "Dt" | "Dur" | "Artist" | "Album"
+/**
+ * parse error, line and col are 1-based. col counts utf-16 units like monaco
+ */
+export type ParseErr = { msg: string; line: number; col: number }
+/**
+ * pdf document
+ * 
+ * - version: pdf version, `1.7`
+ * - pages: text of the requested pages
+ */
+export type PdfContent = { path: string; version: string; page_cnt: number; info: PdfInfo; outline: PdfOutline[]; pages: PdfPage[] }
+/**
+ * document info of a pdf
+ * 
+ * - created, modified: unix time
+ */
+export type PdfInfo = { title?: string | null; author?: string | null; subject?: string | null; keywords?: string | null; creator?: string | null; producer?: string | null; created?: number | null; modified?: number | null }
+/**
+ * bookmark of a pdf
+ * 
+ * - lvl: 1-based depth, page: 1-based page number
+ */
+export type PdfOutline = { lvl: number; title: string; page: number }
+/**
+ * - no: 1-based page number
+ * - err: set instead of text if the text can not be extracted
+ */
+export type PdfPage = { no: number; text?: string | null; err?: string | null }
+/**
+ * removed tags or attributes of the same name
+ */
+export type RemovedItem = { nm: string; cnt: number }
+/**
+ * text search options
+ * 
+ * - enc: encoding label. If `None` then the remembered encoding of the file or detected one
+ * - literal: the pattern is plain text, not a regex
+ * - ctx: lines of context before and after a match, 0 if `None`
+ * - offset, line: where to start, `next_offset` and `next_line` of the previous page
+ * - id: to cancel the search with `cancel_search`
+ */
+export type SearchOpts = { enc?: string | null; ignore_case?: boolean | null; whole_word?: boolean | null; literal?: boolean | null; ctx?: number | null; offset?: number | null; line?: number | null; take_n?: number | null; id?: string | null }
+/**
+ * event of a state set or removed by `set_state`, or saved as the default by `get_state`.
+ * a window of `subscribe_state` gets the keys of its prefixes only
+ * 
+ * - old, new: `None` if it did not exist or is removed
+ * - source: label of the window that changed it
+ */
+export type StateChanged = { key: string; old: JsonValue | null; new: JsonValue | null; source: string | null }
+/**
+ * node and its children of a structured document
+ * 
+ * - err: set instead of node and items if the document can not be parsed
+ */
+export type StructContent = { path: string; format?: StructFormat | null; node?: StructNode | null; items?: StructNode[] | null; tot?: number | null; skip_n?: number | null; take_n?: number | null; err?: ParseErr | null }
+export type StructFormat = "Json" | "Yaml" | "Toml" | "Xml"
+/**
+ * node of a structured document
+ * 
+ * - path: json pointer of the node (`/servers/0/name`), `""` for the root
+ * - cnt: child count of object or array
+ */
+export type StructNode = { key: string; path: string; kind: NodeType; preview: string; cnt?: number | null }
+export type TableCol = { nm: string; kind: ColType }
+/**
+ * page of a table
+ * 
+ * - row_cnt: data rows of the file, tot: rows after filters
+ * - row_nos: 0-based data row numbers of `rows` in the file
+ */
+export type TableContent = { path: string; enc: string; delimiter: string; has_header: boolean; cols: TableCol[]; row_cnt: number; tot: number; skip_n: number; take_n: number; row_nos: number[]; rows: string[][] }
+/**
+ * `Contains` ignores case. `Gt`, `Ge`, `Lt`, `Le` compare numbers in `Int`, `Float` columns
+ */
+export type TableFilter = { col: number; op: FilterOp; val: string }
+/**
+ * table options
+ * 
+ * - enc, delimiter, has_header: sniffed if `None`. `delimiter` is one character (`"\t"`)
+ * - filters: rows matching all of them
+ */
+export type TableOpts = { enc?: string | null; delimiter?: string | null; has_header?: boolean | null; sorts?: TableSort[] | null; filters?: TableFilter[] | null; skip_n?: number | null; take_n?: number | null }
+export type TableSort = { col: number; asc: OrderAsc }
+/**
+ * text of the file
+ * 
+ * - detection: how the encoding was detected, `None` if it is given or remembered
+ * - sz, tm_ms: of the file
+ * - codec: `gzip`, `bzip2`, `xz` or `zstd` if the text is decompressed from the file
+ */
+export type TextContent = { path: string; mimetype: string; enc?: string | null; text?: string | null; language?: string | null; detection?: Detection | null; errs?: DecodeErr[] | null; err_cnt?: number | null; bom?: boolean | null; eol?: EolInfo | null; final_newline?: boolean | null; indent?: IndentInfo | null; sz?: number | null; tm_ms?: number | null; codec?: string | null }
+/**
+ * match of a text search
+ * 
+ * - line, col: 1-based. col is utf-16 like Monaco
+ * - offset, byte_len: bytes in the file
+ * - text: the line, around the match if the line is long
+ * - pos, len: utf-16 offset of the match in `text`
+ * - before, after: context lines
+ */
+export type TextMatch = { line: number; col: number; offset: number; byte_len: number; text: string; pos: number; len: number; before: string[]; after: string[] }
+/**
+ * page of a text search
+ * 
+ * - next_offset, next_line: start of the next page, `None` if the end of the file is reached
+ * - scanned: bytes searched up to
+ * - is_cancelled: stopped by `cancel_search`, `matches` are those found until then
+ */
+export type TextSearch = { path: string; pattern: string; enc: string; sz: number; matches: TextMatch[]; next_offset?: number | null; next_line?: number | null; scanned: number; is_cancelled: boolean }
+/**
+ * heading of a markdown document
+ * 
+ * - lvl: 1 to 6
+ * - id: anchor of the heading in html (`#intro`)
+ */
+export type TocItem = { lvl: number; id: string; text: string }
+export type TrackKind = "Audio" | "Video"
+export type WriteInfo = { path: string; enc: string; sz: number; tm_ms: number }
+/**
+ * write options
+ * 
+ * - enc, bom, eol: from `TextContent`. `eol` converts all line endings, `Mixed` or `None` keeps them
+ * - sz, tm_ms: from `TextContent`. if the file changed since then, `ApiError::Conflict`
+ * - force: write even if conflicted
+ */
+export type WriteOpts = { enc?: string | null; bom?: boolean | null; eol?: Eol | null; sz?: number | null; tm_ms?: number | null; force?: boolean | null }
 
 /** tauri-specta globals **/
 
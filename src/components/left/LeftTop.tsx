@@ -8,9 +8,11 @@ import {
   faVideo,
   faMusic,
   faImage,
-  faDesktop
+  faDesktop,
+  faBookmark
 } from '@fortawesome/free-solid-svg-icons'
 import type { HomePathMap } from '@/types'
+import { events, type Bookmark } from '@/bindings'
 import { useFolderTreeStore } from '@store/folderTreeStore'
 import { useFolderTreeRefStore } from '@store/folderTreeRefStore'
 import { useSelectedTreeItemStore } from '@store/selectedTreeItemStore'
@@ -42,6 +44,7 @@ function LeftTop(): React.ReactElement {
     RuntimeDir: '',
     TemplateDir: ''
   })
+  const [bookmarks, setBookmarks] = useState<Bookmark[]>([])
   const clickHomeDir = async (fullPath: string): Promise<void> => {
     await renderTreeFromPath({
       fullPath,
@@ -60,6 +63,20 @@ function LeftTop(): React.ReactElement {
     fetchHomes().then((h) => {
       setHomeDir(h)
     })
+    api.getBookmarks().then((items) => {
+      setBookmarks(items)
+    })
+    // bookmarks changed by any window
+    const unlisten = events.stateChanged.listen((event) => {
+      if (event.payload.key.startsWith('bookmark.')) {
+        api.getBookmarks().then((items) => {
+          setBookmarks(items)
+        })
+      }
+    })
+    return () => {
+      unlisten.then((f) => f())
+    }
   }, [])
   return (
     <div className="left-top">
@@ -99,6 +116,16 @@ function LeftTop(): React.ReactElement {
       >
         <Icon icon={faDesktop} />
       </div>
+      {bookmarks.map((bookmark) => (
+        <div
+          key={bookmark.id}
+          className={bookmark.exists ? 'link bookmark' : 'link bookmark missing'}
+          title={bookmark.group ? `${bookmark.group} / ${bookmark.nm}` : bookmark.nm}
+          onClick={() => bookmark.exists && clickHomeDir(bookmark.path)}
+        >
+          <Icon icon={faBookmark} />
+        </div>
+      ))}
     </div>
   )
 }
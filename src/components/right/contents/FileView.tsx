@@ -81,7 +81,7 @@ function ViewImg({ selectedItem }: FileViewProps): React.ReactElement {
   }
   return (
     <div className="view-img">
-      <img src={convertFileSrc(selectedItem?.full_path)} alt={selectedItem?.full_path} />
+      <img src={convertFileSrc(selectedItem?.full_path, 'stream')} alt={selectedItem?.full_path} />
     </div>
   )
 }
@@ -93,7 +93,7 @@ function ViewEmbed({ selectedItem }: FileViewProps): React.ReactElement {
   }
   return (
     <div className="view-embed">
-      <embed src={convertFileSrc(selectedItem?.full_path)} type={selectedItem?.mt}></embed>
+      <embed src={convertFileSrc(selectedItem?.full_path, 'stream')} type={selectedItem?.mt}></embed>
     </div>
   )
 }
@@ -171,7 +171,7 @@ function ViewAudio({ selectedItem }: FileViewProps): React.ReactElement {
   return (
     <div className="view-audio">
       <audio ref={mediaRef} controls={true} autoPlay={true}>
-        <source src={convertFileSrc(selectedItem?.full_path)} type={selectedItem?.mt} />
+        <source src={convertFileSrc(selectedItem?.full_path, 'stream')} type={selectedItem?.mt} />
       </audio>
     </div>
   )
@@ -192,7 +192,7 @@ function ViewVideo({ selectedItem }: FileViewProps): React.ReactElement {
   return (
     <div className="view-video">
       <video ref={mediaRef} controls={true} autoPlay={true}>
-        <source src={convertFileSrc(selectedItem?.full_path)} type={selectedItem?.mt} />
+        <source src={convertFileSrc(selectedItem?.full_path, 'stream')} type={selectedItem?.mt} />
       </video>
     </div>
   )
@@ -220,6 +220,7 @@ function ViewMonaco({ selectedItem }: FileViewProps): React.ReactElement {
   const editorRef = useRef<HTMLDivElement>(null)
   const monacoEditorRef = useRef<monaco.editor.IStandaloneCodeEditor | null>(null)
   const [content, setContent] = useState<string | null>(null)
+  const [language, setLanguage] = useState<string | null>(null)
 
   useEffect(() => {
     if (!selectedItem) {
@@ -228,6 +229,7 @@ function ViewMonaco({ selectedItem }: FileViewProps): React.ReactElement {
     api
       .readText(selectedItem.full_path)
       .then((txtContent) => {
+        setLanguage(txtContent?.language || null)
         setContent(txtContent?.text || '')
       })
       .catch((e) => {
@@ -245,14 +247,14 @@ function ViewMonaco({ selectedItem }: FileViewProps): React.ReactElement {
         // model,
         value: content,
         // language: 'plaintext',
-        language: getMonacoLanguage(selectedItem?.ext),
+        language: language || getMonacoLanguage(selectedItem?.ext),
         theme: 'vs',
         readOnly: true,
         automaticLayout: true,
         scrollBeyondLastLine: false
       })
     }
-  }, [content, selectedItem])
+  }, [content, language, selectedItem])
 
   return <div className="view-monaco" ref={editorRef} />
 }
@@ -87,6 +87,9 @@ function ViewImg({ item, sliderPos }: FileViewProps): React.ReactElement {
     return  <div className="col view-img"></div>
   }
   const setSelectedItem = useSelectedTreeItemStore((state) => state.setSelectedItem)
+  const streamSrc = convertFileSrc(item?.full_path, 'stream')
+  const thumbSize = Math.ceil(Math.max(sliderPos.x, sliderPos.y) / 128) * 128
+  const src = item?.ext === 'svg' ? streamSrc : `${convertFileSrc(item?.full_path, 'thumb')}?size=${thumbSize}`
   return (
     <div
       className="col view-img"
@@ -94,10 +97,15 @@ function ViewImg({ item, sliderPos }: FileViewProps): React.ReactElement {
       title={item?.nm}
     >
       <img
-        src={convertFileSrc(item?.full_path)}
+        src={src}
         loading="lazy"
         alt={item?.full_path}
         onClick={() => setSelectedItem(item)}
+        onError={(e) => {
+          if (e.currentTarget.src !== streamSrc) {
+            e.currentTarget.src = streamSrc
+          }
+        }}
       />
     </div>
   )
@@ -122,7 +130,7 @@ function ViewAudio({ item, sliderPos }: FileViewProps): React.ReactElement {
       title={item?.nm}
     >
       <audio ref={mediaRef} controls={true} autoPlay={false}>
-        <source src={convertFileSrc(item?.full_path)} type={item?.mt} />
+        <source src={convertFileSrc(item?.full_path, 'stream')} type={item?.mt} />
       </audio>
     </div>
   )
@@ -147,7 +155,7 @@ function ViewVideo({ item, sliderPos }: FileViewProps): React.ReactElement {
       title={item?.nm}
     >
       <video ref={mediaRef} controls={true} autoPlay={false}>
-        <source src={convertFileSrc(item?.full_path)} type={item?.mt} />
+        <source src={convertFileSrc(item?.full_path, 'stream')} type={item?.mt} />
       </video>
     </div>
   )